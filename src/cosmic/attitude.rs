@@ -0,0 +1,292 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use nalgebra::{Quaternion, UnitQuaternion, Vector4};
+
+use super::{Frame, Orbit, Spacecraft, State, MAX_TANKS, TANKS_IDX};
+use crate::dynamics::DynamicsError;
+use crate::errors::NyxError;
+use crate::linalg::{Const, Matrix3, OMatrix, OVector, Vector3};
+use crate::md::trajectory::Interpolatable;
+use crate::md::StateParameter;
+use crate::time::Epoch;
+
+use std::default::Default;
+use std::fmt;
+
+/// Attitude of a rigid body: orientation and angular velocity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attitude {
+    /// Unit quaternion rotating vectors from the body frame to the integration frame
+    pub q: UnitQuaternion<f64>,
+    /// Angular velocity of the body with respect to the integration frame, expressed in the body frame, in rad/s
+    pub omega_rad_s: Vector3<f64>,
+}
+
+impl Attitude {
+    pub fn new(q: UnitQuaternion<f64>, omega_rad_s: Vector3<f64>) -> Self {
+        Self { q, omega_rad_s }
+    }
+
+    /// Initializes an attitude from the DCM from the body frame to the integration frame, and the angular velocity in the body frame in rad/s.
+    pub fn from_dcm(dcm: Matrix3<f64>, omega_rad_s: Vector3<f64>) -> Self {
+        Self {
+            q: UnitQuaternion::from_matrix(&dcm),
+            omega_rad_s,
+        }
+    }
+
+    /// Returns the DCM from the body frame to the integration frame.
+    pub fn dcm(&self) -> Matrix3<f64> {
+        self.q.to_rotation_matrix().into_inner()
+    }
+
+    /// Returns the provided body frame vector (e.g. a thruster axis or a boresight) expressed in the integration frame.
+    pub fn to_inertial(&self, body_vec: &Vector3<f64>) -> Vector3<f64> {
+        self.q * body_vec
+    }
+
+    /// Returns the angle between the provided body axis and the desired direction in the integration frame, in degrees.
+    pub fn pointing_error_deg(&self, body_axis: &Vector3<f64>, target: &Vector3<f64>) -> f64 {
+        self.to_inertial(body_axis).angle(target).to_degrees()
+    }
+
+    /// Returns the angular velocity expressed in the integration frame, in rad/s.
+    pub fn omega_inertial_rad_s(&self) -> Vector3<f64> {
+        self.q * self.omega_rad_s
+    }
+}
+
+impl Default for Attitude {
+    fn default() -> Self {
+        Self {
+            q: UnitQuaternion::identity(),
+            omega_rad_s: Vector3::zeros(),
+        }
+    }
+}
+
+impl fmt::Display for Attitude {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prec = f.precision().unwrap_or(6);
+        let (roll, pitch, yaw) = self.q.euler_angles();
+        write!(
+            f,
+            "roll = {:.*} deg  pitch = {:.*} deg  yaw = {:.*} deg  ω = [{:.*}, {:.*}, {:.*}] deg/s",
+            prec,
+            roll.to_degrees(),
+            prec,
+            pitch.to_degrees(),
+            prec,
+            yaw.to_degrees(),
+            prec,
+            self.omega_rad_s[0].to_degrees(),
+            prec,
+            self.omega_rad_s[1].to_degrees(),
+            prec,
+            self.omega_rad_s[2].to_degrees(),
+        )
+    }
+}
+
+/// A spacecraft state with its attitude and inertia tensor, used to propagate the translational and rotational motion together.
+///
+/// The state transition matrix is not supported for this state.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpacecraftAttitude {
+    pub sc: Spacecraft,
+    pub attitude: Attitude,
+    /// Inertia tensor of the spacecraft about its center of mass in the body frame, in kg m^2
+    pub inertia_kg_m2: Matrix3<f64>,
+}
+
+impl SpacecraftAttitude {
+    /// Initializes a new state, the STM of the spacecraft is unset because it is not supported.
+    pub fn new(sc: Spacecraft, attitude: Attitude, inertia_kg_m2: Matrix3<f64>) -> Self {
+        let mut sc = sc;
+        sc.unset_stm();
+        sc.orbit.unset_stm();
+        Self {
+            sc,
+            attitude,
+            inertia_kg_m2,
+        }
+    }
+
+    /// Returns the rotational kinetic energy, in J.
+    pub fn rotational_energy_j(&self) -> f64 {
+        0.5 * self
+            .attitude
+            .omega_rad_s
+            .dot(&(self.inertia_kg_m2 * self.attitude.omega_rad_s))
+    }
+
+    /// Returns the angular momentum expressed in the integration frame, in kg m^2/s.
+    pub fn angular_momentum_inertial(&self) -> Vector3<f64> {
+        self.attitude
+            .to_inertial(&(self.inertia_kg_m2 * self.attitude.omega_rad_s))
+    }
+}
+
+impl Default for SpacecraftAttitude {
+    fn default() -> Self {
+        Self {
+            sc: Spacecraft::default(),
+            attitude: Attitude::default(),
+            inertia_kg_m2: Matrix3::identity(),
+        }
+    }
+}
+
+impl fmt::Display for SpacecraftAttitude {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(prec) => write!(f, "{:.*}  {:.*}", prec, self.sc, prec, self.attitude),
+            None => write!(f, "{}  {}", self.sc, self.attitude),
+        }
+    }
+}
+
+impl fmt::LowerExp for SpacecraftAttitude {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:e}  {}", self.sc, self.attitude)
+    }
+}
+
+impl State for SpacecraftAttitude {
+    type Size = Const<16>;
+    type VecLength = Const<20>;
+
+    fn zeros() -> Self {
+        Self::default()
+    }
+
+    /// The vector is organized as such:
+    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, q_x, q_y, q_z, q_w, ω_x, ω_y, ω_z, Tank masses(4)]
+    fn as_vector(&self) -> OVector<f64, Const<20>> {
+        let mut vector = OVector::<f64, Const<20>>::zeros();
+        for (i, val) in self.sc.as_vector().iter().take(9).enumerate() {
+            vector[i] = *val;
+        }
+        for (i, val) in self.attitude.q.coords.iter().enumerate() {
+            vector[i + 9] = *val;
+        }
+        for i in 0..3 {
+            vector[i + 13] = self.attitude.omega_rad_s[i];
+        }
+        for (i, mass_kg) in self.sc.tank_masses().iter().enumerate() {
+            vector[i + 16] = *mass_kg;
+        }
+        vector
+    }
+
+    /// Vector is expected to be organized as such:
+    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, q_x, q_y, q_z, q_w, ω_x, ω_y, ω_z, Tank masses(4)]
+    /// The quaternion is normalized.
+    fn set(&mut self, epoch: Epoch, vector: &OVector<f64, Const<20>>) {
+        let mut sc_vec = OVector::<f64, Const<94>>::zeros();
+        for i in 0..9 {
+            sc_vec[i] = vector[i];
+        }
+        for i in 0..MAX_TANKS {
+            sc_vec[i + TANKS_IDX] = vector[i + 16];
+        }
+        self.sc.set(epoch, &sc_vec);
+        self.attitude.q = UnitQuaternion::from_quaternion(Quaternion::from(Vector4::new(
+            vector[9], vector[10], vector[11], vector[12],
+        )));
+        self.attitude.omega_rad_s = Vector3::new(vector[13], vector[14], vector[15]);
+    }
+
+    fn stm(&self) -> Result<OMatrix<f64, Self::Size, Self::Size>, DynamicsError> {
+        Err(DynamicsError::StateTransitionMatrixUnset)
+    }
+
+    fn unset_stm(&mut self) {
+        self.sc.unset_stm();
+        self.sc.orbit.unset_stm();
+    }
+
+    fn epoch(&self) -> Epoch {
+        self.sc.epoch()
+    }
+
+    fn set_epoch(&mut self, epoch: Epoch) {
+        self.sc.set_epoch(epoch)
+    }
+
+    fn value(&self, param: StateParameter) -> Result<f64, NyxError> {
+        self.sc.value(param)
+    }
+
+    fn set_value(&mut self, param: StateParameter, val: f64) -> Result<(), NyxError> {
+        self.sc.set_value(param, val)
+    }
+}
+
+impl Interpolatable for SpacecraftAttitude {
+    /// The spacecraft is interpolated as usual, the orientation is spherically interpolated and the angular velocity linearly interpolated
+    /// between the two states surrounding the requested epoch.
+    fn interpolate(self, epoch: Epoch, states: &[Self]) -> Self {
+        let sc = Spacecraft::interpolate(
+            self.sc,
+            epoch,
+            &states.iter().map(|state| state.sc).collect::<Vec<_>>(),
+        );
+
+        let idx = states
+            .partition_point(|state| state.epoch() <= epoch)
+            .clamp(1, states.len().max(2) - 1);
+
+        let attitude = if states.len() < 2 {
+            self.attitude
+        } else {
+            let (before, after) = (&states[idx - 1], &states[idx]);
+            let t = ((epoch - before.epoch()).to_seconds()
+                / (after.epoch() - before.epoch()).to_seconds())
+            .clamp(0.0, 1.0);
+            Attitude {
+                q: before.attitude.q.slerp(&after.attitude.q, t),
+                omega_rad_s: before.attitude.omega_rad_s
+                    + t * (after.attitude.omega_rad_s - before.attitude.omega_rad_s),
+            }
+        };
+
+        Self {
+            sc,
+            attitude,
+            inertia_kg_m2: self.inertia_kg_m2,
+        }
+    }
+
+    fn frame(&self) -> Frame {
+        self.sc.orbit.frame
+    }
+
+    fn set_frame(&mut self, frame: Frame) {
+        self.sc.orbit.frame = frame;
+    }
+
+    fn export_params() -> Vec<StateParameter> {
+        Spacecraft::export_params()
+    }
+
+    fn orbit(&self) -> &Orbit {
+        &self.sc.orbit
+    }
+}
@@ -35,6 +35,7 @@ use crate::hifitime::{Epoch, Unit, SECONDS_PER_DAY};
 use crate::io::frame_serde;
 use crate::na::{Matrix3, Matrix6};
 use crate::utils::{capitalize, dcm_finite_differencing, rotv};
+use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
 use std::collections::HashMap;
@@ -53,7 +54,7 @@ pub const SS_MASS: f64 = 1.0014;
 pub const SUN_GM: f64 = 132_712_440_041.939_38;
 
 /// Enable or not light time correction for the computation of the celestial states
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum LightTimeCalc {
     /// No correction, i.e. assumes instantaneous propagation of photons
@@ -784,65 +785,96 @@ impl Cosm {
                     ssb2k,
                     LightTimeCalc::None,
                 )?;
-                let mut tgt =
-                    self.try_celestial_state(target_ephem, datetime, ssb2k, LightTimeCalc::None)?;
-                // It will take less than three iterations to converge
-                for _ in 0..3 {
-                    // Compute the light time
-                    let lt = (tgt - obs).rmag_km() / SPEED_OF_LIGHT_KMS;
-                    // Compute the new target state
-                    let lt_dt = datetime - lt * Unit::Second;
-                    tgt =
-                        self.try_celestial_state(target_ephem, lt_dt, ssb2k, LightTimeCalc::None)?;
-                }
-                // Compute the correct state
-                let mut state = Orbit::cartesian(
-                    (tgt - obs).x_km,
-                    (tgt - obs).y_km,
-                    (tgt - obs).z_km,
-                    (tgt - obs).vx_km_s,
-                    (tgt - obs).vy_km_s,
-                    (tgt - obs).vz_km_s,
+                self.try_light_time_correction(
+                    obs,
+                    |epoch| {
+                        self.try_celestial_state(target_ephem, epoch, ssb2k, LightTimeCalc::None)
+                    },
                     datetime,
                     frame,
-                );
-
-                // Include the range-rate term in the velocity computation as explained in
-                // https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/abcorr.html#Reception%20case
-                let state_acc = state.velocity() / state.rmag_km();
-                let dltdt = state.radius().dot(&state_acc) / SPEED_OF_LIGHT_KMS;
-
-                state.vx_km_s = tgt.vx_km_s * (1.0 - dltdt) - obs.vx_km_s;
-                state.vy_km_s = tgt.vy_km_s * (1.0 - dltdt) - obs.vy_km_s;
-                state.vz_km_s = tgt.vz_km_s * (1.0 - dltdt) - obs.vz_km_s;
-
-                if correction == LightTimeCalc::Aberration {
-                    // Get a unit vector that points in the direction of the object
-                    let r_hat = state.r_hat();
-                    // Get the velocity vector (of the observer) scaled with respect to the speed of light
-                    let vbyc = obs.velocity() / SPEED_OF_LIGHT_KMS;
-                    /* If the square of the length of the velocity vector is greater than or equal
-                    to one, the speed of the observer is greater than or equal to the speed of light.
-                    The observer speed is definitely out of range. */
-                    if vbyc.dot(&vbyc) >= 1.0 {
-                        warn!("observer is traveling faster than the speed of light");
-                    } else {
-                        let h_hat = r_hat.cross(&vbyc);
-                        /* If the magnitude of the vector H is zero, the observer is moving along the line
-                        of sight to the object, and no correction is required. Otherwise, rotate the
-                        position of the object by phi radians about H to obtain the apparent position. */
-                        if h_hat.norm() > std::f64::EPSILON {
-                            let phi = h_hat.norm().asin();
-                            let ab_pos = rotv(&state.radius(), &h_hat, phi);
-                            state.x_km = ab_pos[0];
-                            state.y_km = ab_pos[1];
-                            state.z_km = ab_pos[2];
-                        }
-                    }
+                    correction,
+                )
+            }
+        }
+    }
+
+    /// Returns the state of the target as seen from the observer at the provided time, with the provided light time correction.
+    ///
+    /// The observer state and the states returned by `target_at` must be expressed in the solar system barycenter frame, and the target
+    /// is queried at the light time corrected epoch. The returned state is expressed in the provided frame, which must share the
+    /// orientation of the solar system barycenter frame. This allows correcting the light time of objects which are not in the
+    /// ephemerides, such as spacecraft.
+    pub fn try_light_time_correction<F>(
+        &self,
+        obs: Orbit,
+        target_at: F,
+        datetime: Epoch,
+        frame: Frame,
+        correction: LightTimeCalc,
+    ) -> Result<Orbit, NyxError>
+    where
+        F: Fn(Epoch) -> Result<Orbit, NyxError>,
+    {
+        let mut tgt = target_at(datetime)?;
+        if correction == LightTimeCalc::None {
+            let mut state = tgt - obs;
+            state.frame = frame;
+            return Ok(state);
+        }
+        // It will take less than three iterations to converge
+        for _ in 0..3 {
+            // Compute the light time
+            let lt = (tgt - obs).rmag_km() / SPEED_OF_LIGHT_KMS;
+            // Compute the new target state
+            let lt_dt = datetime - lt * Unit::Second;
+            tgt = target_at(lt_dt)?;
+        }
+        // Compute the correct state
+        let mut state = Orbit::cartesian(
+            (tgt - obs).x_km,
+            (tgt - obs).y_km,
+            (tgt - obs).z_km,
+            (tgt - obs).vx_km_s,
+            (tgt - obs).vy_km_s,
+            (tgt - obs).vz_km_s,
+            datetime,
+            frame,
+        );
+
+        // Include the range-rate term in the velocity computation as explained in
+        // https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/abcorr.html#Reception%20case
+        let state_acc = state.velocity() / state.rmag_km();
+        let dltdt = state.radius().dot(&state_acc) / SPEED_OF_LIGHT_KMS;
+
+        state.vx_km_s = tgt.vx_km_s * (1.0 - dltdt) - obs.vx_km_s;
+        state.vy_km_s = tgt.vy_km_s * (1.0 - dltdt) - obs.vy_km_s;
+        state.vz_km_s = tgt.vz_km_s * (1.0 - dltdt) - obs.vz_km_s;
+
+        if correction == LightTimeCalc::Aberration {
+            // Get a unit vector that points in the direction of the object
+            let r_hat = state.r_hat();
+            // Get the velocity vector (of the observer) scaled with respect to the speed of light
+            let vbyc = obs.velocity() / SPEED_OF_LIGHT_KMS;
+            /* If the square of the length of the velocity vector is greater than or equal
+            to one, the speed of the observer is greater than or equal to the speed of light.
+            The observer speed is definitely out of range. */
+            if vbyc.dot(&vbyc) >= 1.0 {
+                warn!("observer is traveling faster than the speed of light");
+            } else {
+                let h_hat = r_hat.cross(&vbyc);
+                /* If the magnitude of the vector H is zero, the observer is moving along the line
+                of sight to the object, and no correction is required. Otherwise, rotate the
+                position of the object by phi radians about H to obtain the apparent position. */
+                if h_hat.norm() > std::f64::EPSILON {
+                    let phi = h_hat.norm().asin();
+                    let ab_pos = rotv(&state.radius(), &h_hat, phi);
+                    state.x_km = ab_pos[0];
+                    state.y_km = ab_pos[1];
+                    state.z_km = ab_pos[2];
                 }
-                Ok(state)
             }
         }
+        Ok(state)
     }
 
     /// Returns the state of the celestial object (target ephem) as seen in the requested frame at the provided time
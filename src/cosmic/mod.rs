@@ -135,6 +135,14 @@ pub enum AstroError {
     PartialsUndefined,
     #[snafu(display("Orbit is not hyperbolic so there is no hyperbolic anomaly."))]
     NotHyperbolic,
+    #[snafu(display(
+        "modified equinoctial elements are singular for retrograde equatorial orbits"
+    ))]
+    RetrogradeEquatorial,
+    #[snafu(display(
+        "modified equinoctial elements are undefined for rectilinear orbits (no angular momentum)"
+    ))]
+    RectilinearOrbit,
 }
 
 impl XbEpoch {
@@ -348,6 +356,10 @@ pub use self::bplane::*;
 mod spacecraft;
 pub use self::spacecraft::*;
 
+// Re-Export attitude
+mod attitude;
+pub use self::attitude::*;
+
 // Re-Export frames
 mod frames;
 pub use self::frames::*;
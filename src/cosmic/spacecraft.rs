@@ -78,8 +78,14 @@ impl From<GuidanceMode> for f64 {
     }
 }
 
+/// Maximum number of propellant tanks of a spacecraft
+pub const MAX_TANKS: usize = 4;
+
 /// A spacecraft state, composed of its orbit, its dry and fuel (wet) masses (in kg), its SRP configuration, its drag configuration, its thruster configuration, and its guidance mode.
 ///
+/// The fuel mass may optionally be split across up to `MAX_TANKS` propellant tanks, in which case the fuel mass is the sum of the propellant in all of the tanks.
+/// The tanks are defined (name, blowdown, feed of each engine) in the `Propulsion` subsystem of the dynamics, in the same order as in the spacecraft.
+///
 /// Optionally, the spacecraft state can also store the state transition matrix from the start of the propagation until the current time (i.e. trajectory STM, not step-size STM).
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "python", pyclass)]
@@ -98,6 +104,9 @@ pub struct Spacecraft {
     #[serde(default)]
     pub drag: DragConfig,
     pub thruster: Option<Thruster>,
+    /// Propellant mass in each tank in kg, if the fuel mass is split across several tanks (the single thruster draws from the first tank)
+    #[serde(default)]
+    pub tank_masses_kg: [Option<f64>; MAX_TANKS],
     /// Any extra information or extension that is needed for specific guidance laws
     #[serde(default)]
     pub mode: GuidanceMode,
@@ -115,6 +124,7 @@ impl Default for Spacecraft {
             srp: SrpConfig::default(),
             drag: DragConfig::default(),
             thruster: None,
+            tank_masses_kg: [None; MAX_TANKS],
             mode: GuidanceMode::default(),
             stm: None,
         }
@@ -271,13 +281,56 @@ impl Spacecraft {
         me
     }
 
-    /// Returns a copy of the state with a new fuel mass
+    /// Returns a copy of the state with a new fuel mass, split across the propellant tanks (if any) as in `set_value`
     pub fn with_fuel_mass(self, fuel_mass_kg: f64) -> Self {
         let mut me = self;
-        me.fuel_mass_kg = fuel_mass_kg;
+        me.set_fuel_mass(fuel_mass_kg);
         me
     }
 
+    /// Returns a copy of the state with the fuel mass split across the provided propellant tanks, in kg.
+    /// The fuel mass is set to the total propellant mass.
+    /// Errors if more than `MAX_TANKS` tanks are provided.
+    pub fn with_tank_masses(self, tank_masses_kg: &[f64]) -> Result<Self, ConfigError> {
+        if tank_masses_kg.len() > MAX_TANKS {
+            return Err(ConfigError::InvalidConfig {
+                msg: format!(
+                    "{} propellant tanks provided but at most {MAX_TANKS} are supported",
+                    tank_masses_kg.len()
+                ),
+            });
+        }
+        let mut me = self;
+        me.tank_masses_kg = [None; MAX_TANKS];
+        for (tank, mass_kg) in me.tank_masses_kg.iter_mut().zip(tank_masses_kg) {
+            *tank = Some(*mass_kg);
+        }
+        me.fuel_mass_kg = tank_masses_kg.iter().sum();
+        Ok(me)
+    }
+
+    /// Sets the fuel mass, in kg. If the fuel mass is split across several tanks, the new fuel mass is split across them in proportion
+    /// to their current propellant mass (or evenly if they are all empty), so that the fuel mass remains the sum of the tank masses.
+    fn set_fuel_mass(&mut self, fuel_mass_kg: f64) {
+        let tank_count = self.tank_masses_kg.iter().flatten().count();
+        if tank_count > 0 {
+            let total_kg: f64 = self.tank_masses_kg.iter().flatten().sum();
+            for mass_kg in self.tank_masses_kg.iter_mut().flatten() {
+                *mass_kg = if total_kg > 0.0 {
+                    fuel_mass_kg * *mass_kg / total_kg
+                } else {
+                    fuel_mass_kg / tank_count as f64
+                };
+            }
+        }
+        self.fuel_mass_kg = fuel_mass_kg;
+    }
+
+    /// Returns the propellant mass in each tank in kg, where unused tanks are empty.
+    pub fn tank_masses(&self) -> [f64; MAX_TANKS] {
+        self.tank_masses_kg.map(|mass_kg| mass_kg.unwrap_or(0.0))
+    }
+
     /// Returns a copy of the state with a new SRP area and CR
     pub fn with_srp(self, srp_area_m2: f64, cr: f64) -> Self {
         let mut me = self;
@@ -387,6 +440,15 @@ impl PartialEq for Spacecraft {
         self.orbit == other.orbit
             && (self.dry_mass_kg - other.dry_mass_kg).abs() < mass_tol
             && (self.fuel_mass_kg - other.fuel_mass_kg).abs() < mass_tol
+            && self
+                .tank_masses_kg
+                .iter()
+                .zip(other.tank_masses_kg.iter())
+                .all(|(mine, theirs)| match (mine, theirs) {
+                    (Some(mine), Some(theirs)) => (mine - theirs).abs() < mass_tol,
+                    (None, None) => true,
+                    _ => false,
+                })
             && self.srp == other.srp
             && self.drag == other.drag
     }
@@ -467,9 +529,16 @@ impl fmt::UpperHex for Spacecraft {
     }
 }
 
+/// Index of the coefficient of reflectivity in the state vector
+pub const CR_IDX: usize = 6;
+/// Index of the coefficient of drag in the state vector
+pub const CD_IDX: usize = 7;
+/// Index of the first tank mass in the state vector
+pub(crate) const TANKS_IDX: usize = 90;
+
 impl State for Spacecraft {
     type Size = Const<9>;
-    type VecLength = Const<90>;
+    type VecLength = Const<94>;
 
     fn reset_stm(&mut self) {
         self.orbit.reset_stm();
@@ -481,9 +550,9 @@ impl State for Spacecraft {
     }
 
     /// The vector is organized as such:
-    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9)]
-    fn as_vector(&self) -> OVector<f64, Const<90>> {
-        let mut vector = OVector::<f64, Const<90>>::zeros();
+    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9), Tank masses(4)]
+    fn as_vector(&self) -> OVector<f64, Const<94>> {
+        let mut vector = OVector::<f64, Const<94>>::zeros();
         // Set the orbit state info
         for (i, val) in self.orbit.to_cartesian_vec().iter().enumerate() {
             // Place the orbit state first, then skip three (Cr, Cd, Fuel), then copy orbit STM
@@ -504,17 +573,20 @@ impl State for Spacecraft {
                 vector[idx + Self::Size::dim()] = *stm_val;
             }
         }
+        for (i, mass_kg) in self.tank_masses().iter().enumerate() {
+            vector[i + TANKS_IDX] = *mass_kg;
+        }
         vector
     }
 
     /// Vector is expected to be organized as such:
-    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9)]
-    fn set(&mut self, epoch: Epoch, vector: &OVector<f64, Const<90>>) {
+    /// [X, Y, Z, Vx, Vy, Vz, Cr, Cd, Fuel mass, STM(9x9), Tank masses(4)]
+    fn set(&mut self, epoch: Epoch, vector: &OVector<f64, Const<94>>) {
         self.set_epoch(epoch);
         let sc_state =
             OVector::<f64, Self::Size>::from_column_slice(&vector.as_slice()[..Self::Size::dim()]);
         let sc_full_stm = OMatrix::<f64, Self::Size, Self::Size>::from_column_slice(
-            &vector.as_slice()[Self::Size::dim()..],
+            &vector.as_slice()[Self::Size::dim()..TANKS_IDX],
         );
 
         if self.stm.is_some() {
@@ -540,6 +612,11 @@ impl State for Spacecraft {
         self.srp.cr = sc_state[6];
         self.drag.cd = sc_state[7];
         self.fuel_mass_kg = sc_state[8];
+        for (i, tank) in self.tank_masses_kg.iter_mut().enumerate() {
+            if tank.is_some() {
+                *tank = Some(vector[i + TANKS_IDX]);
+            }
+        }
     }
 
     /// diag(STM) = [X,Y,Z,Vx,Vy,Vz,Cr,Cd,Fuel]
@@ -578,6 +655,13 @@ impl State for Spacecraft {
                 None => Err(NyxError::NoThrusterAvail),
             },
             StateParameter::GuidanceMode => Ok(self.mode.into()),
+            StateParameter::TankMass1
+            | StateParameter::TankMass2
+            | StateParameter::TankMass3
+            | StateParameter::TankMass4 => {
+                let index = param.tank_index().unwrap();
+                self.tank_masses_kg[index].ok_or(NyxError::NoTankAvail { index })
+            }
             _ => self.orbit.value(param),
         }
     }
@@ -586,7 +670,7 @@ impl State for Spacecraft {
         match param {
             StateParameter::Cd => self.drag.cd = val,
             StateParameter::Cr => self.srp.cr = val,
-            StateParameter::FuelMass => self.fuel_mass_kg = val,
+            StateParameter::FuelMass => self.set_fuel_mass(val),
             StateParameter::Isp => match self.thruster {
                 Some(ref mut thruster) => thruster.isp_s = val,
                 None => return Err(NyxError::NoThrusterAvail),
@@ -595,6 +679,19 @@ impl State for Spacecraft {
                 Some(ref mut thruster) => thruster.thrust_N = val,
                 None => return Err(NyxError::NoThrusterAvail),
             },
+            StateParameter::TankMass1
+            | StateParameter::TankMass2
+            | StateParameter::TankMass3
+            | StateParameter::TankMass4 => {
+                let index = param.tank_index().unwrap();
+                match self.tank_masses_kg[index] {
+                    Some(ref mut mass_kg) => {
+                        self.fuel_mass_kg += val - *mass_kg;
+                        *mass_kg = val;
+                    }
+                    None => return Err(NyxError::NoTankAvail { index }),
+                }
+            }
             _ => return self.orbit.set_value(param, val),
         }
         Ok(())
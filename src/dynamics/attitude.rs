@@ -0,0 +1,173 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Dynamics, DynamicsError, SpacecraftDynamics};
+use crate::cosmic::{SpacecraftAttitude, State, MAX_TANKS, TANKS_IDX};
+use crate::linalg::{Const, OVector, Vector3};
+use nalgebra::Quaternion;
+use std::fmt::{self, Write};
+use std::sync::Arc;
+
+/// The `TorqueModel` trait handles the external torques acting on the spacecraft.
+pub trait TorqueModel: Send + Sync + fmt::Display {
+    /// Returns the torque about the center of mass in the body frame, in N m.
+    fn torque(&self, ctx: &SpacecraftAttitude) -> Result<Vector3<f64>, DynamicsError>;
+}
+
+/// Gravity gradient torque due to the central body of the integration frame, modeled as a point mass.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GravityGradient;
+
+impl TorqueModel for GravityGradient {
+    fn torque(&self, ctx: &SpacecraftAttitude) -> Result<Vector3<f64>, DynamicsError> {
+        let osc = &ctx.sc.orbit;
+        let rmag = osc.rmag_km();
+        // Direction of the spacecraft from the central body, in the body frame
+        let r_body = ctx.attitude.q.inverse() * (osc.radius() / rmag);
+        // NOTE: The gravitational parameter and the radius are both in km, so their ratio is in s^-2
+        Ok(3.0 * osc.frame.gm() / rmag.powi(3) * r_body.cross(&(ctx.inertia_kg_m2 * r_body)))
+    }
+}
+
+impl fmt::Display for GravityGradient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "gravity gradient torque")
+    }
+}
+
+/// Rigid body attitude dynamics, propagated together with the translational dynamics of the spacecraft.
+///
+/// The attitude follows Euler's equations with the inertia tensor of the state, and the quaternion kinematics.
+/// The attitude does not affect the translational dynamics, so the guidance law of the spacecraft dynamics still
+/// sets the thrust direction: compare it with the body axis of the thruster to analyze the pointing during a burn.
+/// The STM is not supported.
+#[derive(Clone)]
+pub struct AttitudeDynamics {
+    pub sc_dyn: SpacecraftDynamics,
+    pub torque_models: Vec<Arc<dyn TorqueModel>>,
+}
+
+impl AttitudeDynamics {
+    /// Initializes torque free attitude dynamics with the provided spacecraft dynamics.
+    pub fn new(sc_dyn: SpacecraftDynamics) -> Self {
+        Self {
+            sc_dyn,
+            torque_models: Vec::new(),
+        }
+    }
+
+    /// Initializes attitude dynamics with the provided spacecraft dynamics and torque models.
+    pub fn from_torque_models(
+        sc_dyn: SpacecraftDynamics,
+        torque_models: Vec<Arc<dyn TorqueModel>>,
+    ) -> Self {
+        Self {
+            sc_dyn,
+            torque_models,
+        }
+    }
+
+    /// Initializes attitude dynamics with the provided spacecraft dynamics and the gravity gradient torque.
+    pub fn with_gravity_gradient(sc_dyn: SpacecraftDynamics) -> Self {
+        Self::from_torque_models(sc_dyn, vec![Arc::new(GravityGradient)])
+    }
+
+    /// Add a torque model to these dynamics
+    pub fn add_torque_model(&mut self, torque_model: Arc<dyn TorqueModel>) {
+        self.torque_models.push(torque_model);
+    }
+}
+
+impl fmt::Display for AttitudeDynamics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let torque_models: String = if self.torque_models.is_empty() {
+            "torque free;".to_string()
+        } else {
+            self.torque_models
+                .iter()
+                .fold(String::new(), |mut output, x| {
+                    let _ = write!(output, "{x}; ");
+                    output
+                })
+        };
+        write!(f, "Attitude dynamics: {} {}", torque_models, self.sc_dyn)
+    }
+}
+
+impl Dynamics for AttitudeDynamics {
+    type HyperdualSize = Const<17>;
+    type StateType = SpacecraftAttitude;
+
+    fn finally(&self, next_state: Self::StateType) -> Result<Self::StateType, DynamicsError> {
+        let mut state = next_state;
+        state.sc = self.sc_dyn.finally(next_state.sc)?;
+        Ok(state)
+    }
+
+    fn eom(
+        &self,
+        delta_t: f64,
+        state: &OVector<f64, Const<20>>,
+        ctx: &Self::StateType,
+    ) -> Result<OVector<f64, Const<20>>, DynamicsError> {
+        let osc = ctx.set_with_delta_seconds(delta_t, state);
+        let mut d_x = OVector::<f64, Const<20>>::zeros();
+
+        // Translational dynamics, without the STM
+        let mut sc_ctx = ctx.sc;
+        sc_ctx.unset_stm();
+        sc_ctx.orbit.unset_stm();
+        let mut sc_vec = OVector::<f64, Const<94>>::zeros();
+        for i in 0..9 {
+            sc_vec[i] = state[i];
+        }
+        for i in 0..MAX_TANKS {
+            sc_vec[i + TANKS_IDX] = state[i + 16];
+        }
+        let d_sc = self.sc_dyn.eom(delta_t, &sc_vec, &sc_ctx)?;
+        for i in 0..9 {
+            d_x[i] = d_sc[i];
+        }
+        for i in 0..MAX_TANKS {
+            d_x[i + 16] = d_sc[i + TANKS_IDX];
+        }
+
+        // Quaternion kinematics, with the angular velocity in the body frame
+        let omega = osc.attitude.omega_rad_s;
+        let q_dot = osc.attitude.q.into_inner() * Quaternion::from_imag(omega) * 0.5;
+        for (i, val) in q_dot.coords.iter().enumerate() {
+            d_x[i + 9] = *val;
+        }
+
+        // Euler's equations
+        let mut torque = Vector3::zeros();
+        for model in &self.torque_models {
+            torque += model.torque(&osc)?;
+        }
+        let inertia = osc.inertia_kg_m2;
+        let inertia_inv = inertia
+            .try_inverse()
+            .ok_or(DynamicsError::SingularInertia)?;
+        let omega_dot = inertia_inv * (torque - omega.cross(&(inertia * omega)));
+        for i in 0..3 {
+            d_x[i + 13] = omega_dot[i];
+        }
+
+        Ok(d_x)
+    }
+}
@@ -16,9 +16,11 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use super::plates::PlateModel;
 use super::{DynamicsError, ForceModel};
-use crate::cosmic::{AstroError, Cosm, Frame, Spacecraft};
-use crate::linalg::{Matrix3, Vector3};
+use crate::cosmic::{Bodies, Cosm, Frame, LightTimeCalc, Orbit, Spacecraft, CD_IDX};
+use crate::linalg::{Const, Matrix3, Matrix3x6, Vector3, Vector6};
+use hyperdual::{hyperspace_from_vector, linalg::norm, Float, OHyperdual};
 use std::fmt;
 use std::sync::Arc;
 
@@ -30,17 +32,146 @@ pub enum AtmDensity {
     StdAtm { max_alt_m: f64 },
 }
 
-/// `ConstantDrag` implements a constant drag model as defined in Vallado, 4th ed., page 551, with an important caveat.
+/// A wind model returns the velocity of the atmosphere with respect to the co-rotating atmosphere.
+pub trait WindModel: Send + Sync {
+    /// Returns the wind velocity in km/s, expressed in the body fixed frame of the drag, at the provided body fixed state.
+    fn wind_velocity(&self, osc: &Orbit) -> Vector3<f64>;
+}
+
+/// State of the spacecraft with respect to the atmosphere, expressed in the integration frame.
+struct AtmosphereRelative {
+    /// Radius vector from the center of the drag body, in km
+    radius: Vector3<f64>,
+    /// Inertial velocity with respect to the center of the drag body, in km/s
+    velocity: Vector3<f64>,
+    /// Angular velocity of the co-rotating atmosphere, in rad/s
+    omega: Vector3<f64>,
+    /// Wind velocity, in km/s
+    wind: Vector3<f64>,
+}
+
+impl AtmosphereRelative {
+    /// Returns the state relative to the atmosphere in the integration frame, and the state in the body fixed drag frame.
+    fn new(
+        ctx: &Spacecraft,
+        drag_frame: Frame,
+        wind: Option<&Arc<dyn WindModel>>,
+        cosm: &Cosm,
+    ) -> Result<(Self, Orbit), DynamicsError> {
+        let integration_frame = ctx.orbit.frame;
+        let osc = cosm.frame_chg(&ctx.orbit, drag_frame);
+        // State of the center of the drag body in the integration frame, which is null if both share the same center.
+        let center = cosm.celestial_state(
+            &drag_frame.ephem_path(),
+            ctx.orbit.epoch,
+            integration_frame,
+            LightTimeCalc::None,
+        );
+        // The atmosphere rotates about the Z axis of the body fixed frame.
+        let dcm = cosm
+            .try_position_dcm_from_to(&drag_frame, &integration_frame, ctx.orbit.epoch)
+            .map_err(|e| DynamicsError::FrameRotation { msg: e.to_string() })?;
+        let omega = dcm * Vector3::new(0.0, 0.0, drag_frame.angular_velocity());
+        let wind = match wind {
+            Some(wind) => dcm * wind.wind_velocity(&osc),
+            None => Vector3::zeros(),
+        };
+
+        Ok((
+            Self {
+                radius: ctx.orbit.radius() - center.radius(),
+                velocity: ctx.orbit.velocity() - center.velocity(),
+                omega,
+                wind,
+            },
+            osc,
+        ))
+    }
+
+    /// Velocity of the spacecraft with respect to the atmosphere, in km/s
+    fn relative_velocity(&self) -> Vector3<f64> {
+        self.velocity - self.omega.cross(&self.radius) - self.wind
+    }
+
+    /// Returns the drag force, where the density is in kg/m^3 and the area in m^2.
+    /// Note the 1e3 factor, which converts the velocity squared from km^2/s^2 to m^2/s^2 and the force from N to kN.
+    fn force(&self, rho: f64, cd_area_m2: f64) -> Vector3<f64> {
+        let velocity = self.relative_velocity();
+        -0.5e3 * rho * cd_area_m2 * velocity.norm() * velocity
+    }
+
+    /// Returns the drag force and its partials with respect to the position and velocity.
+    /// The density is modeled as locally exponential with respect to the radius magnitude, with the logarithmic derivative `dln_rho_dr` in 1/km.
+    fn dual_force(
+        &self,
+        rho: f64,
+        dln_rho_dr: f64,
+        cd_area_m2: f64,
+    ) -> (Vector3<f64>, Matrix3x6<f64>) {
+        let state: Vector6<OHyperdual<f64, Const<7>>> = hyperspace_from_vector(&Vector6::new(
+            self.radius[0],
+            self.radius[1],
+            self.radius[2],
+            self.velocity[0],
+            self.velocity[1],
+            self.velocity[2],
+        ));
+
+        let radius = state.fixed_rows::<3>(0).into_owned();
+        let velocity = state.fixed_rows::<3>(3).into_owned();
+
+        let omega: Vector3<OHyperdual<f64, Const<7>>> = Vector3::new(
+            OHyperdual::from_real(self.omega[0]),
+            OHyperdual::from_real(self.omega[1]),
+            OHyperdual::from_real(self.omega[2]),
+        );
+
+        // Velocity relative to the atmosphere, i.e. v - ω × r - wind
+        let mut v_rel: Vector3<OHyperdual<f64, Const<7>>> = Vector3::zeros();
+        v_rel[0] = velocity[0]
+            - (omega[1] * radius[2] - omega[2] * radius[1])
+            - OHyperdual::from_real(self.wind[0]);
+        v_rel[1] = velocity[1]
+            - (omega[2] * radius[0] - omega[0] * radius[2])
+            - OHyperdual::from_real(self.wind[1]);
+        v_rel[2] = velocity[2]
+            - (omega[0] * radius[1] - omega[1] * radius[0])
+            - OHyperdual::from_real(self.wind[2]);
+
+        let rmag = norm(&radius);
+        let rho_d = OHyperdual::<f64, Const<7>>::from_real(rho)
+            * (OHyperdual::<f64, Const<7>>::from_real(dln_rho_dr)
+                * (rmag - OHyperdual::from_real(rmag.real())))
+            .exp();
+
+        let force = v_rel
+            * (OHyperdual::<f64, Const<7>>::from_real(-0.5e3 * cd_area_m2) * rho_d * norm(&v_rel));
+
+        // Extract result into Vector3 and Matrix3x6
+        let mut fx = Vector3::zeros();
+        let mut grad = Matrix3x6::zeros();
+        for i in 0..3 {
+            fx[i] = force[i].real();
+            for j in 0..6 {
+                grad[(i, j)] = force[i][j + 1];
+            }
+        }
+
+        (fx, grad)
+    }
+}
+
+/// `ConstantDrag` implements a constant drag model as defined in Vallado, 4th ed., page 551.
 ///
-/// **WARNING:** This basic model assumes that the velocity of the spacecraft is identical to the velocity of the upper atmosphere,
-/// This is a **bad** assumption and **should not** be used for high fidelity simulations.
-/// This will be resolved after https://gitlab.com/chrisrabotin/nyx/issues/93 is implemented.
+/// The drag is computed from the velocity of the spacecraft relative to the co-rotating atmosphere, optionally corrected by a wind model.
 #[derive(Clone)]
 pub struct ConstantDrag {
     /// atmospheric density in kg/m^3
     pub rho: f64,
     /// Geoid causing the drag
     pub drag_frame: Frame,
+    /// Optional wind model, added to the co-rotation of the atmosphere
+    pub wind: Option<Arc<dyn WindModel>>,
     /// a Cosm reference is needed to convert to the state around the correct planet
     pub cosm: Arc<Cosm>,
 }
@@ -57,28 +188,45 @@ impl fmt::Display for ConstantDrag {
 
 impl ForceModel for ConstantDrag {
     fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, DynamicsError> {
-        let osc = self.cosm.frame_chg(&ctx.orbit, self.drag_frame);
-        let velocity = osc.velocity();
-        Ok(-0.5 * self.rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+        let (atm, _) =
+            AtmosphereRelative::new(ctx, self.drag_frame, self.wind.as_ref(), &self.cosm)?;
+        Ok(atm.force(self.rho, ctx.drag.cd * ctx.drag.area_m2))
+    }
+
+    fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), DynamicsError> {
+        let (force, grad) = self.dual_eom_pos_vel(ctx)?;
+        Ok((force, grad.fixed_view::<3, 3>(0, 0).into_owned()))
     }
 
-    fn dual_eom(
+    /// The drag depends on the velocity relative to the atmosphere
+    fn dual_eom_pos_vel(
         &self,
-        _osc_ctx: &Spacecraft,
-    ) -> Result<(Vector3<f64>, Matrix3<f64>), DynamicsError> {
-        Err(DynamicsError::DynamicsAstro {
-            source: AstroError::PartialsUndefined,
-        })
+        ctx: &Spacecraft,
+    ) -> Result<(Vector3<f64>, Matrix3x6<f64>), DynamicsError> {
+        let (atm, _) =
+            AtmosphereRelative::new(ctx, self.drag_frame, self.wind.as_ref(), &self.cosm)?;
+        Ok(atm.dual_force(self.rho, 0.0, ctx.drag.cd * ctx.drag.area_m2))
+    }
+
+    /// The drag force is proportional to the coefficient of drag
+    fn estimation_index(&self) -> Option<usize> {
+        Some(CD_IDX)
     }
 }
 
-/// `Drag` implements all three drag models.
+/// `Drag` implements all of the atmospheric density models.
+///
+/// The drag is computed from the velocity of the spacecraft relative to the co-rotating atmosphere, optionally corrected by a wind model.
 #[derive(Clone)]
 pub struct Drag {
     /// Density computation method
     pub density: AtmDensity,
     /// Frame to compute the drag in
     pub drag_frame: Frame,
+    /// Optional wind model, added to the co-rotation of the atmosphere
+    pub wind: Option<Arc<dyn WindModel>>,
+    /// Optional plate model of the spacecraft, which replaces the drag area and coefficient of drag of the spacecraft
+    pub plates: Option<Arc<PlateModel>>,
     /// a Cosm reference is needed to convert to the state around the correct planet
     pub cosm: Arc<Cosm>,
 }
@@ -93,6 +241,8 @@ impl Drag {
                 ref_alt_m: 88_667.0,
             },
             drag_frame: cosm.frame("IAU Earth"),
+            wind: None,
+            plates: None,
             cosm,
         })
     }
@@ -104,44 +254,49 @@ impl Drag {
                 max_alt_m: 1_000_000.0,
             },
             drag_frame: cosm.frame("IAU Earth"),
+            wind: None,
+            plates: None,
             cosm,
         })
     }
-}
 
-impl fmt::Display for Drag {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "\tDrag density {:?} in frame {}",
-            self.density, self.drag_frame
-        )
+    /// Returns a copy of this model which computes the drag force from the provided plate model instead of the spacecraft drag configuration.
+    pub fn with_plates(&self, plates: PlateModel) -> Arc<Self> {
+        let mut me = self.clone();
+        me.plates = Some(Arc::new(plates));
+        Arc::new(me)
     }
-}
 
-impl ForceModel for Drag {
-    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, DynamicsError> {
-        let integration_frame = ctx.orbit.frame;
-        let osc = self.cosm.frame_chg(&ctx.orbit, self.drag_frame);
-        match self.density {
-            AtmDensity::Constant(rho) => {
-                let velocity = osc.velocity();
-                Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+    /// Returns the product of the coefficient of drag and the area exposed to the flow, in m^2.
+    fn cd_area_m2(&self, ctx: &Spacecraft, atm: &AtmosphereRelative) -> f64 {
+        match &self.plates {
+            Some(plates) => {
+                let sun = self.cosm.celestial_state(
+                    Bodies::Sun.ephem_path(),
+                    ctx.orbit.epoch,
+                    ctx.orbit.frame,
+                    LightTimeCalc::None,
+                );
+                let sun_unit = (sun.radius() - ctx.orbit.radius()).normalize();
+                let velocity_unit = atm.relative_velocity().normalize();
+                plates.cd_area_m2(&ctx.orbit, &velocity_unit, &sun_unit)
             }
+            None => ctx.drag.cd * ctx.drag.area_m2,
+        }
+    }
+
+    /// Returns the atmospheric density in kg/m^3 at the provided state in the body fixed drag frame.
+    pub fn density_kg_m3(&self, osc: &Orbit) -> Result<f64, DynamicsError> {
+        match &self.density {
+            AtmDensity::Constant(rho) => Ok(*rho),
 
             AtmDensity::Exponential {
                 rho0,
                 r0,
                 ref_alt_m,
             } => {
-                let rho = rho0
-                    * (-(osc.rmag_km() - (r0 + self.drag_frame.equatorial_radius())) / ref_alt_m)
-                        .exp();
-
-                let velocity_integr_frame = self.cosm.frame_chg(&osc, integration_frame).velocity();
-
-                let velocity = velocity_integr_frame - osc.velocity();
-                Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
+                let altitude_m = (osc.rmag_km() - self.drag_frame.equatorial_radius()) * 1e3;
+                Ok(rho0 * (-(altitude_m - r0) / ref_alt_m).exp())
             }
 
             AtmDensity::StdAtm { max_alt_m } => {
@@ -163,21 +318,71 @@ impl ForceModel for Drag {
                     /* Calculating density by raising 10 to the log of density */
                     10.0_f64.powf(logdensity)
                 };
+                Ok(rho)
+            }
+        }
+    }
 
-                let velocity_integr_frame = self.cosm.frame_chg(&osc, integration_frame).velocity();
+    /// Returns the derivative of the logarithm of the density with respect to the radius magnitude (in 1/km), computed by finite differencing.
+    fn density_log_gradient(&self, osc: &Orbit, rho: f64) -> Result<f64, DynamicsError> {
+        if let AtmDensity::Constant(_) = self.density {
+            return Ok(0.0);
+        }
+        let step_km = 1.0;
+        let scale = 1.0 + step_km / osc.rmag_km();
+        let mut raised = *osc;
+        raised.x_km *= scale;
+        raised.y_km *= scale;
+        raised.z_km *= scale;
+        Ok((self.density_kg_m3(&raised)? / rho).ln() / step_km)
+    }
+}
 
-                let velocity = velocity_integr_frame - osc.velocity();
-                Ok(-0.5 * rho * ctx.drag.cd * ctx.drag.area_m2 * velocity.norm() * velocity)
-            }
+impl fmt::Display for Drag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "\tDrag density {:?} in frame {}",
+            self.density, self.drag_frame
+        )?;
+        if let Some(plates) = &self.plates {
+            write!(f, " on {plates}")?;
         }
+        Ok(())
+    }
+}
+
+impl ForceModel for Drag {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, DynamicsError> {
+        let (atm, osc) =
+            AtmosphereRelative::new(ctx, self.drag_frame, self.wind.as_ref(), &self.cosm)?;
+        let rho = self.density_kg_m3(&osc)?;
+        Ok(atm.force(rho, self.cd_area_m2(ctx, &atm)))
+    }
+
+    fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), DynamicsError> {
+        let (force, grad) = self.dual_eom_pos_vel(ctx)?;
+        Ok((force, grad.fixed_view::<3, 3>(0, 0).into_owned()))
     }
 
-    fn dual_eom(
+    /// The drag depends on the velocity relative to the atmosphere
+    fn dual_eom_pos_vel(
         &self,
-        _osc_ctx: &Spacecraft,
-    ) -> Result<(Vector3<f64>, Matrix3<f64>), DynamicsError> {
-        Err(DynamicsError::DynamicsAstro {
-            source: AstroError::PartialsUndefined,
-        })
+        ctx: &Spacecraft,
+    ) -> Result<(Vector3<f64>, Matrix3x6<f64>), DynamicsError> {
+        let (atm, osc) =
+            AtmosphereRelative::new(ctx, self.drag_frame, self.wind.as_ref(), &self.cosm)?;
+        let rho = self.density_kg_m3(&osc)?;
+        let dln_rho_dr = self.density_log_gradient(&osc, rho)?;
+        // NOTE: The exposed area of a plate model is held fixed in the partials
+        Ok(atm.dual_force(rho, dln_rho_dr, self.cd_area_m2(ctx, &atm)))
+    }
+
+    /// The drag force is proportional to the coefficient of drag, unless a plate model is used
+    fn estimation_index(&self) -> Option<usize> {
+        match self.plates {
+            Some(_) => None,
+            None => Some(CD_IDX),
+        }
     }
 }
@@ -0,0 +1,288 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{DynamicsError, ForceModel};
+use crate::cosmic::eclipse::{eclipse_state, EclipseLocator};
+use crate::cosmic::{Cosm, Frame, LightTimeCalc, Orbit, Spacecraft, AU, CR_IDX, SPEED_OF_LIGHT};
+use crate::io::earth_radiation::EarthRadiationMap;
+use crate::linalg::{Const, Matrix3, Vector3};
+use hyperdual::{hyperspace_from_vector, linalg::norm, Float, OHyperdual};
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+use std::sync::Arc;
+
+/// Radiation pressure due to the sunlight reflected by the Earth (albedo) and to the infrared emission of the Earth.
+///
+/// The visible cap of the Earth is discretized into a central element and `rings` concentric rings of 6, 12, 18... elements (Knocke et al., 1988).
+/// Each element reflects the sunlight it receives as a Lambertian surface and emits a uniform infrared flux of a quarter of the solar flux,
+/// scaled by the albedo and emissivity of the map at its location. The elements are only lit if they face the Sun and are not shadowed
+/// by the shadow bodies of the eclipse locator, other than the central body itself.
+///
+/// Like the solar radiation pressure, the force is proportional to the coefficient of reflectivity and the SRP area of the spacecraft.
+#[derive(Clone)]
+pub struct EarthRadiationPressure {
+    /// solar flux at 1 AU, in W/m^2
+    pub phi: f64,
+    pub e_loc: EclipseLocator,
+    /// Body fixed frame of the Earth, in which the map is defined
+    pub body_fixed_frame: Frame,
+    pub map: Arc<EarthRadiationMap>,
+    /// Number of rings of elements around the central element of the visible cap
+    pub rings: usize,
+    /// Set to false to ignore the reflected sunlight
+    pub albedo: bool,
+    /// Set to false to ignore the infrared emission
+    pub infrared: bool,
+}
+
+/// An element of the visible cap of the Earth
+struct CapElement {
+    /// Position of the element in the integration frame, in km
+    position_km: Vector3<f64>,
+    /// Unit normal of the element
+    normal: Vector3<f64>,
+    /// Radiant exitance times the area of the element over π, in W/m^2 * km^2
+    intensity: f64,
+}
+
+impl EarthRadiationPressure {
+    /// Albedo and infrared from the IAU Earth frame with three rings, shadows of the Moon, and a solar flux at 1 AU of: Phi = 1367.0
+    pub fn default_raw(map: EarthRadiationMap, cosm: Arc<Cosm>) -> Self {
+        Self {
+            phi: 1367.0,
+            body_fixed_frame: cosm.frame("IAU Earth"),
+            e_loc: EclipseLocator::cislunar(cosm),
+            map: Arc::new(map),
+            rings: 3,
+            albedo: true,
+            infrared: true,
+        }
+    }
+
+    /// Albedo and infrared from the IAU Earth frame with three rings, shadows of the Moon, and a solar flux at 1 AU of: Phi = 1367.0
+    pub fn default(map: EarthRadiationMap, cosm: Arc<Cosm>) -> Arc<Self> {
+        Arc::new(Self::default_raw(map, cosm))
+    }
+
+    /// Returns the fraction of the light source seen from the provided point of the surface, ignoring the central body
+    /// whose shadowing is already accounted for by the incidence angle.
+    fn illumination(&self, surface: &Orbit) -> f64 {
+        let central_body = surface.frame.ephem_path();
+        self.e_loc
+            .shadow_bodies
+            .iter()
+            .filter(|body| body.ephem_path() != central_body)
+            .map(|body| {
+                let k: f64 =
+                    eclipse_state(surface, self.e_loc.light_source, *body, &self.e_loc.cosm).into();
+                k
+            })
+            .fold(1.0, f64::min)
+    }
+
+    /// Discretizes the cap of the Earth visible from the spacecraft, skipping the elements which do not radiate.
+    ///
+    /// The cap is built around the origin of the integration frame, so the latter must be centered on the Earth.
+    fn cap_elements(&self, osc: &Orbit) -> Result<Vec<CapElement>, DynamicsError> {
+        if osc.frame.ephem_path() != self.body_fixed_frame.ephem_path() {
+            return Err(DynamicsError::IntegrationFrameCenter {
+                model: "Earth radiation pressure".to_string(),
+                center: format!("{}", self.body_fixed_frame),
+                frame: format!("{}", osc.frame),
+            });
+        }
+
+        let radius_km = self.body_fixed_frame.equatorial_radius();
+        let rmag = osc.rmag_km();
+        if rmag <= radius_km {
+            return Ok(Vec::new());
+        }
+
+        let cosm = &self.e_loc.cosm;
+        let sun = cosm
+            .try_celestial_state(
+                &self.e_loc.light_source.ephem_path(),
+                osc.epoch,
+                osc.frame,
+                LightTimeCalc::None,
+            )
+            .map_err(|e| DynamicsError::FrameRotation { msg: e.to_string() })?;
+        let sun_unit = sun.radius() / sun.rmag_km();
+        let flux = self.phi * (AU / sun.rmag_km()).powi(2);
+
+        let dcm = cosm
+            .try_position_dcm_from_to(&osc.frame, &self.body_fixed_frame, osc.epoch)
+            .map_err(|e| DynamicsError::FrameRotation { msg: e.to_string() })?;
+
+        // Orthonormal basis around the sub-satellite point
+        let u = osc.radius() / rmag;
+        let helper = if u[2].abs() < 0.9 {
+            Vector3::z()
+        } else {
+            Vector3::x()
+        };
+        let e1 = u.cross(&helper).normalize();
+        let e2 = u.cross(&e1);
+
+        let half_angle = (radius_km / rmag).acos();
+        let step = half_angle / (self.rings as f64 + 0.5);
+
+        let mut elements = Vec::with_capacity(1 + 3 * self.rings * (self.rings + 1));
+        for ring in 0..=self.rings {
+            let num_elements = (6 * ring).max(1);
+            let (inner, outer) = if ring == 0 {
+                (0.0, 0.5 * step)
+            } else {
+                ((ring as f64 - 0.5) * step, (ring as f64 + 0.5) * step)
+            };
+            let area_km2 =
+                TAU * radius_km.powi(2) * (inner.cos() - outer.cos()) / (num_elements as f64);
+            let beta = ring as f64 * step;
+
+            for idx in 0..num_elements {
+                let azimuth = TAU * (idx as f64) / (num_elements as f64);
+                let normal =
+                    beta.cos() * u + beta.sin() * (azimuth.cos() * e1 + azimuth.sin() * e2);
+                let position_km = radius_km * normal;
+
+                let mut exitance = 0.0;
+                let normal_fixed = dcm * normal;
+                let (albedo, emissivity) = self.map.at(
+                    normal_fixed[2].clamp(-1.0, 1.0).asin().to_degrees(),
+                    normal_fixed[1].atan2(normal_fixed[0]).to_degrees(),
+                );
+
+                if self.infrared {
+                    exitance += emissivity * flux / 4.0;
+                }
+
+                let cos_sun = normal.dot(&sun_unit);
+                if self.albedo && cos_sun > 0.0 {
+                    let surface = Orbit::cartesian(
+                        position_km[0],
+                        position_km[1],
+                        position_km[2],
+                        0.0,
+                        0.0,
+                        0.0,
+                        osc.epoch,
+                        osc.frame,
+                    );
+                    exitance += albedo * flux * cos_sun * self.illumination(&surface);
+                }
+
+                if exitance > 0.0 {
+                    elements.push(CapElement {
+                        position_km,
+                        normal,
+                        intensity: exitance * area_km2 / PI,
+                    });
+                }
+            }
+        }
+
+        Ok(elements)
+    }
+}
+
+impl ForceModel for EarthRadiationPressure {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, DynamicsError> {
+        let osc = &ctx.orbit;
+        // Irradiance at the spacecraft in W/m^2, in the direction of the incoming radiation
+        let mut irradiance = Vector3::zeros();
+        for element in self.cap_elements(osc)? {
+            let rho = osc.radius() - element.position_km;
+            let rho_mag = rho.norm();
+            let cos_out = element.normal.dot(&rho) / rho_mag;
+            if cos_out > 0.0 {
+                irradiance += (element.intensity * cos_out / rho_mag.powi(3)) * rho;
+            }
+        }
+
+        // Note the 1e-3 is to convert the force from m/s^2 to km/s^2
+        Ok(1e-3 * ctx.srp.cr * ctx.srp.area_m2 / SPEED_OF_LIGHT * irradiance)
+    }
+
+    fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), DynamicsError> {
+        let osc = &ctx.orbit;
+        let radius: Vector3<OHyperdual<f64, Const<4>>> = hyperspace_from_vector(&osc.radius());
+
+        // NOTE: The elements of the cap are held fixed: the partials only account for the relative position of the spacecraft.
+        let mut irradiance: Vector3<OHyperdual<f64, Const<4>>> = Vector3::zeros();
+        for element in self.cap_elements(osc)? {
+            let rho = radius
+                - element
+                    .position_km
+                    .map(OHyperdual::<f64, Const<4>>::from_real);
+            let rho_mag = norm(&rho);
+            let cos_out = (rho[0] * element.normal[0]
+                + rho[1] * element.normal[1]
+                + rho[2] * element.normal[2])
+                / rho_mag;
+            if cos_out.real() > 0.0 {
+                let scalar = OHyperdual::<f64, Const<4>>::from_real(element.intensity) * cos_out
+                    / rho_mag.powi(3);
+                for i in 0..3 {
+                    irradiance[i] += scalar * rho[i];
+                }
+            }
+        }
+
+        let scale = OHyperdual::<f64, Const<4>>::from_real(
+            1e-3 * ctx.srp.cr * ctx.srp.area_m2 / SPEED_OF_LIGHT,
+        );
+
+        let mut dx = Vector3::zeros();
+        let mut grad = Matrix3::zeros();
+        for i in 0..3 {
+            let force = scale * irradiance[i];
+            dx[i] = force.real();
+            for j in 0..3 {
+                grad[(i, j)] = force[j + 1];
+            }
+        }
+
+        Ok((dx, grad))
+    }
+
+    /// The Earth radiation pressure force is proportional to the coefficient of reflectivity
+    fn estimation_index(&self) -> Option<usize> {
+        Some(CR_IDX)
+    }
+}
+
+impl fmt::Display for EarthRadiationPressure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut sources = Vec::new();
+        if self.albedo {
+            sources.push("albedo");
+        }
+        if self.infrared {
+            sources.push("infrared");
+        }
+        write!(
+            f,
+            "Earth radiation pressure ({}) of {} with {} rings, φ = {} W/m^2 and eclipse {}",
+            sources.join(", "),
+            self.body_fixed_frame,
+            self.rings,
+            self.phi,
+            self.e_loc
+        )
+    }
+}
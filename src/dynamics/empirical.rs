@@ -0,0 +1,265 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::relativity::{cross, dot};
+use super::{DynamicsError, ForceModel};
+use crate::cosmic::{Frame, Orbit, Spacecraft};
+use crate::io::ConfigError;
+use crate::linalg::{Const, Matrix3, Matrix3x6, OMatrix, Vector3, Vector6};
+use crate::time::Epoch;
+use hyperdual::linalg::norm;
+use hyperdual::{hyperspace_from_vector, Float, OHyperdual};
+use std::fmt;
+use std::sync::Arc;
+
+/// Coefficients of the empirical acceleration along each axis of the local frame, all in km/s^2.
+///
+/// The acceleration is `constant + cos_1rev * cos(u) + sin_1rev * sin(u)`, where u is the argument of latitude.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct EmpiricalCoefficients {
+    pub constant: Vector3<f64>,
+    pub cos_1rev: Vector3<f64>,
+    pub sin_1rev: Vector3<f64>,
+}
+
+impl EmpiricalCoefficients {
+    /// Initializes a constant acceleration in the local frame, in km/s^2.
+    pub fn constant(accel_km_s2: Vector3<f64>) -> Self {
+        Self {
+            constant: accel_km_s2,
+            ..Default::default()
+        }
+    }
+
+    /// Initializes a once-per-revolution acceleration in the local frame from its cosine and sine amplitudes, in km/s^2.
+    pub fn once_per_rev(cos_km_s2: Vector3<f64>, sin_km_s2: Vector3<f64>) -> Self {
+        Self {
+            constant: Vector3::zeros(),
+            cos_1rev: cos_km_s2,
+            sin_1rev: sin_km_s2,
+        }
+    }
+
+    /// Returns the acceleration in the local frame at the provided argument of latitude, in radians.
+    pub fn accel(&self, aol_rad: f64) -> Vector3<f64> {
+        self.constant + self.cos_1rev * aol_rad.cos() + self.sin_1rev * aol_rad.sin()
+    }
+
+    /// Returns the coefficients as a vector of [constant, cos_1rev, sin_1rev], in km/s^2.
+    pub fn as_vector(&self) -> OMatrix<f64, Const<9>, Const<1>> {
+        let mut vector = OMatrix::<f64, Const<9>, Const<1>>::zeros();
+        for i in 0..3 {
+            vector[i] = self.constant[i];
+            vector[i + 3] = self.cos_1rev[i];
+            vector[i + 6] = self.sin_1rev[i];
+        }
+        vector
+    }
+
+    /// Initializes the coefficients from a vector of [constant, cos_1rev, sin_1rev], in km/s^2.
+    pub fn from_vector(vector: &OMatrix<f64, Const<9>, Const<1>>) -> Self {
+        Self {
+            constant: vector.fixed_rows::<3>(0).into_owned(),
+            cos_1rev: vector.fixed_rows::<3>(3).into_owned(),
+            sin_1rev: vector.fixed_rows::<3>(6).into_owned(),
+        }
+    }
+}
+
+/// Empirical accelerations, used to absorb the unmodeled forces in orbit determination.
+///
+/// The acceleration is constant and once-per-revolution in a local frame of the orbit (RCN, RIC or VNC), and its coefficients are
+/// piecewise constant: the initial coefficients apply until the start of the first segment, and each segment applies until the start of the next one.
+///
+/// The partials of the acceleration with respect to the nine coefficients of the active segment are provided by `coefficient_partials`.
+/// However, the coefficients are not part of the spacecraft state, so the filters cannot estimate them yet.
+#[derive(Clone, Debug)]
+pub struct EmpiricalAccel {
+    /// Local frame of the coefficients, one of RCN, RIC or VNC
+    pub frame: Frame,
+    /// Coefficients applied before the first segment
+    pub initial: EmpiricalCoefficients,
+    /// Start epoch and coefficients of each segment, sorted by epoch
+    pub segments: Vec<(Epoch, EmpiricalCoefficients)>,
+}
+
+impl EmpiricalAccel {
+    /// Initializes empirical accelerations in the provided local frame with the same coefficients throughout.
+    /// Returns an error if the frame is not RCN, RIC or VNC.
+    pub fn new(frame: Frame, coefficients: EmpiricalCoefficients) -> Result<Self, ConfigError> {
+        if !matches!(frame, Frame::RCN | Frame::RIC | Frame::VNC) {
+            return Err(ConfigError::InvalidConfig {
+                msg: format!(
+                    "empirical accelerations must be defined in the RCN, RIC or VNC frame, got {frame}"
+                ),
+            });
+        }
+        Ok(Self {
+            frame,
+            initial: coefficients,
+            segments: Vec::new(),
+        })
+    }
+
+    /// Initializes a constant acceleration in the RCN frame, in km/s^2.
+    pub fn constant_rcn(accel_km_s2: Vector3<f64>) -> Arc<Self> {
+        Arc::new(Self {
+            frame: Frame::RCN,
+            initial: EmpiricalCoefficients::constant(accel_km_s2),
+            segments: Vec::new(),
+        })
+    }
+
+    /// Applies the provided coefficients from the provided epoch until the start of the next segment.
+    pub fn with_segment(mut self, start: Epoch, coefficients: EmpiricalCoefficients) -> Self {
+        let idx = self.segments.partition_point(|(epoch, _)| *epoch <= start);
+        self.segments.insert(idx, (start, coefficients));
+        self
+    }
+
+    /// Returns the index of the segment active at the provided epoch, if any segment has started.
+    pub fn segment_index(&self, epoch: Epoch) -> Option<usize> {
+        self.segments
+            .partition_point(|(start, _)| *start <= epoch)
+            .checked_sub(1)
+    }
+
+    /// Returns the coefficients active at the provided epoch.
+    pub fn coefficients_at(&self, epoch: Epoch) -> &EmpiricalCoefficients {
+        match self.segment_index(epoch) {
+            Some(idx) => &self.segments[idx].1,
+            None => &self.initial,
+        }
+    }
+
+    /// Returns the empirical acceleration in the integration frame, in km/s^2.
+    pub fn accel(&self, osc: &Orbit) -> Result<Vector3<f64>, DynamicsError> {
+        let dcm = osc
+            .dcm_from_traj_frame(self.frame)
+            .map_err(|source| DynamicsError::DynamicsAstro { source })?;
+        let aol_rad = osc.aol_deg().to_radians();
+        Ok(dcm * self.coefficients_at(osc.epoch).accel(aol_rad))
+    }
+
+    /// Returns the partials of the acceleration in the integration frame with respect to the coefficients of the active segment,
+    /// ordered as constant, cosine and sine terms along each local axis.
+    pub fn coefficient_partials(
+        &self,
+        osc: &Orbit,
+    ) -> Result<OMatrix<f64, Const<3>, Const<9>>, DynamicsError> {
+        let dcm = osc
+            .dcm_from_traj_frame(self.frame)
+            .map_err(|source| DynamicsError::DynamicsAstro { source })?;
+        let aol_rad = osc.aol_deg().to_radians();
+        let mut partials = OMatrix::<f64, Const<3>, Const<9>>::zeros();
+        partials.fixed_columns_mut::<3>(0).copy_from(&dcm);
+        partials
+            .fixed_columns_mut::<3>(3)
+            .copy_from(&(dcm * aol_rad.cos()));
+        partials
+            .fixed_columns_mut::<3>(6)
+            .copy_from(&(dcm * aol_rad.sin()));
+        Ok(partials)
+    }
+}
+
+impl ForceModel for EmpiricalAccel {
+    fn eom(&self, ctx: &Spacecraft) -> Result<Vector3<f64>, DynamicsError> {
+        // The acceleration is in km/s^2, so the force is in kN
+        Ok(self.accel(&ctx.orbit)? * ctx.mass_kg())
+    }
+
+    fn dual_eom(&self, ctx: &Spacecraft) -> Result<(Vector3<f64>, Matrix3<f64>), DynamicsError> {
+        let (force, grad) = self.dual_eom_pos_vel(ctx)?;
+        Ok((force, grad.fixed_view::<3, 3>(0, 0).into_owned()))
+    }
+
+    /// The local frame of the empirical accelerations depends on the velocity
+    fn dual_eom_pos_vel(
+        &self,
+        ctx: &Spacecraft,
+    ) -> Result<(Vector3<f64>, Matrix3x6<f64>), DynamicsError> {
+        let osc = &ctx.orbit;
+        let state: Vector6<OHyperdual<f64, Const<7>>> =
+            hyperspace_from_vector(&osc.to_cartesian_vec());
+        let radius = state.fixed_rows::<3>(0).into_owned();
+        let velocity = state.fixed_rows::<3>(3).into_owned();
+
+        let r_hat = radius / norm(&radius);
+        let h = cross(&radius, &velocity);
+        let h_hat = h / norm(&h);
+
+        // Local frame axes in the integration frame, matching `Orbit::dcm_from_traj_frame`
+        let axes = match self.frame {
+            Frame::VNC => {
+                let v_hat = velocity / norm(&velocity);
+                let c_hat = cross(&v_hat, &h_hat);
+                [v_hat, h_hat, c_hat]
+            }
+            _ => [r_hat, cross(&h_hat, &r_hat), h_hat],
+        };
+
+        // Argument of latitude, measured from the ascending node, or from the X axis for equatorial orbits
+        let node = Vector3::new(-h[1], h[0], OHyperdual::from_real(0.0));
+        let node_hat = if norm(&node).real() > 1e-9 * norm(&h).real() {
+            node / norm(&node)
+        } else {
+            Vector3::x().map(OHyperdual::<f64, Const<7>>::from_real)
+        };
+        let cos_u_geo = dot(&r_hat, &node_hat);
+        let sin_u_geo = dot(&r_hat, &cross(&h_hat, &node_hat));
+        // The value of the argument of latitude is that of `Orbit::aol_deg` used by the EOMs, which may only differ from the
+        // geometric angle by a constant offset in the degenerate cases (e.g. equatorial orbits): rotate the latter by that offset.
+        let offset = osc.aol_deg().to_radians() - sin_u_geo.real().atan2(cos_u_geo.real());
+        let (sin_offset, cos_offset) = offset.sin_cos();
+        let cos_u = cos_u_geo * cos_offset - sin_u_geo * sin_offset;
+        let sin_u = sin_u_geo * cos_offset + cos_u_geo * sin_offset;
+
+        let coeffs = self.coefficients_at(osc.epoch);
+        let mass = OHyperdual::<f64, Const<7>>::from_real(ctx.mass_kg());
+        let mut force: Vector3<OHyperdual<f64, Const<7>>> = Vector3::zeros();
+        for (k, axis) in axes.iter().enumerate() {
+            let local = OHyperdual::<f64, Const<7>>::from_real(coeffs.constant[k])
+                + cos_u * coeffs.cos_1rev[k]
+                + sin_u * coeffs.sin_1rev[k];
+            force += *axis * (local * mass);
+        }
+
+        let mut fx = Vector3::zeros();
+        let mut grad = Matrix3x6::zeros();
+        for i in 0..3 {
+            fx[i] = force[i].real();
+            for j in 0..6 {
+                grad[(i, j)] = force[i][j + 1];
+            }
+        }
+
+        Ok((fx, grad))
+    }
+}
+
+impl fmt::Display for EmpiricalAccel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Empirical accelerations in {} with {} segments",
+            self.frame,
+            self.segments.len() + 1
+        )
+    }
+}
@@ -0,0 +1,279 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{Dynamics, DynamicsError, OrbitalDynamics, SpacecraftDynamics};
+use crate::cosmic::{AstroError, Frame, Orbit, Spacecraft};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{Const, DefaultAllocator, DimName, OVector, Vector3, Vector6};
+use crate::time::Epoch;
+use crate::State;
+use std::fmt;
+
+/// The modified equinoctial elements are singular when one plus the Z component of the unit orbital momentum is below this threshold.
+const RETROGRADE_EQUATORIAL_THRESH: f64 = 1e-10;
+/// The modified equinoctial elements are undefined when the orbital momentum is below this threshold, relative to the product of the radius and velocity magnitudes.
+const RECTILINEAR_THRESH: f64 = 1e-12;
+
+/// `EquinoctialDynamics` wraps orbital or spacecraft dynamics to integrate the modified equinoctial elements of the orbit instead of its
+/// Cartesian state, with Gauss's variational equations.
+///
+/// The accelerations of the wrapped dynamics (i.e. those of all of its acceleration models and force models, and the thrust) are projected
+/// in the RCN frame and drive the rates of the elements. As these elements vary slowly, the integrator can take much larger steps than
+/// in Cartesian coordinates, e.g. for the low thrust propagations of the `Ruggiero` guidance law.
+///
+/// The elements are the semi-parameter `p` in km, the eccentricity vector components `f` and `g`, the node vector components `h` and `k`,
+/// and the true longitude `L` in radians (Walker, Ireland and Owens, 1985). They are singular for retrograde equatorial orbits only.
+/// The other components of the state vector (e.g. the fuel mass) are integrated as in the wrapped dynamics.
+///
+/// Note that the error control of adaptive integrators applies to the elements: `Propagator::equinoctial` uses an error control
+/// suited to them (`RSSEquinoctialState`). The STM cannot be propagated, the dense output of the propagator is not available,
+/// and the time transformation of the wrapped dynamics, if any, is not used.
+#[derive(Clone)]
+pub struct EquinoctialDynamics<D> {
+    pub inner: D,
+}
+
+impl<D> EquinoctialDynamics<D> {
+    /// Initializes the propagation of the modified equinoctial elements of the provided dynamics
+    pub fn new(inner: D) -> Self {
+        Self { inner }
+    }
+}
+
+impl<D: fmt::Display> fmt::Display for EquinoctialDynamics<D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Modified equinoctial elements of {}", self.inner)
+    }
+}
+
+impl Dynamics for EquinoctialDynamics<OrbitalDynamics> {
+    type HyperdualSize = Const<7>;
+    type StateType = Orbit;
+
+    fn eom(
+        &self,
+        delta_t_s: f64,
+        state: &OVector<f64, Const<42>>,
+        ctx: &Orbit,
+    ) -> Result<OVector<f64, Const<42>>, DynamicsError> {
+        if ctx.stm.is_some() {
+            return Err(DynamicsError::StateTransitionMatrixUnsupported);
+        }
+        let cartesian = from_equinoctial_vector(state, ctx.frame.gm());
+        let osc = ctx.set_with_delta_seconds(delta_t_s, &cartesian);
+        let d_x = self.inner.eom(delta_t_s, &cartesian, ctx)?;
+        equinoctial_rates(state, d_x, &osc)
+    }
+
+    fn finally(&self, next_state: Orbit) -> Result<Orbit, DynamicsError> {
+        self.inner.finally(next_state)
+    }
+
+    fn energy(&self, state: &Orbit) -> Option<f64> {
+        self.inner.energy(state)
+    }
+
+    fn integration_vector(
+        &self,
+        state: &Orbit,
+    ) -> Result<Option<OVector<f64, Const<42>>>, DynamicsError> {
+        to_equinoctial_vector(&state.as_vector(), state.frame.gm()).map(Some)
+    }
+
+    fn set_integration_vector(
+        &self,
+        state: &mut Orbit,
+        epoch: Epoch,
+        vector: &OVector<f64, Const<42>>,
+    ) -> Result<(), DynamicsError> {
+        let gm = state.frame.gm();
+        state.set(epoch, &from_equinoctial_vector(vector, gm));
+        Ok(())
+    }
+}
+
+impl Dynamics for EquinoctialDynamics<SpacecraftDynamics> {
+    type HyperdualSize = Const<9>;
+    type StateType = Spacecraft;
+
+    fn eom(
+        &self,
+        delta_t_s: f64,
+        state: &OVector<f64, Const<94>>,
+        ctx: &Spacecraft,
+    ) -> Result<OVector<f64, Const<94>>, DynamicsError> {
+        if ctx.orbit.stm.is_some() {
+            return Err(DynamicsError::StateTransitionMatrixUnsupported);
+        }
+        let cartesian = from_equinoctial_vector(state, ctx.orbit.frame.gm());
+        let osc = ctx.set_with_delta_seconds(delta_t_s, &cartesian).orbit;
+        let d_x = self.inner.eom(delta_t_s, &cartesian, ctx)?;
+        equinoctial_rates(state, d_x, &osc)
+    }
+
+    fn finally(&self, next_state: Spacecraft) -> Result<Spacecraft, DynamicsError> {
+        self.inner.finally(next_state)
+    }
+
+    fn energy(&self, state: &Spacecraft) -> Option<f64> {
+        self.inner.energy(state)
+    }
+
+    fn integration_vector(
+        &self,
+        state: &Spacecraft,
+    ) -> Result<Option<OVector<f64, Const<94>>>, DynamicsError> {
+        to_equinoctial_vector(&state.as_vector(), state.orbit.frame.gm()).map(Some)
+    }
+
+    fn set_integration_vector(
+        &self,
+        state: &mut Spacecraft,
+        epoch: Epoch,
+        vector: &OVector<f64, Const<94>>,
+    ) -> Result<(), DynamicsError> {
+        let gm = state.orbit.frame.gm();
+        state.set(epoch, &from_equinoctial_vector(vector, gm));
+        Ok(())
+    }
+}
+
+/// Returns the modified equinoctial elements `[p, f, g, h, k, L]` of the provided Cartesian state, for the provided gravitational parameter.
+pub fn cartesian_to_equinoctial(
+    cartesian: &Vector6<f64>,
+    gm: f64,
+) -> Result<Vector6<f64>, AstroError> {
+    let radius = cartesian.fixed_rows::<3>(0).into_owned();
+    let velocity = cartesian.fixed_rows::<3>(3).into_owned();
+    let hvec = radius.cross(&velocity);
+    let hmag = hvec.norm();
+    if hmag <= RECTILINEAR_THRESH * radius.norm() * velocity.norm() {
+        return Err(AstroError::RectilinearOrbit);
+    }
+    let h_hat = hvec / hmag;
+    if 1.0 + h_hat[2] < RETROGRADE_EQUATORIAL_THRESH {
+        return Err(AstroError::RetrogradeEquatorial);
+    }
+    let ecc_vec = velocity.cross(&hvec) / gm - radius / radius.norm();
+    let h = -h_hat[1] / (1.0 + h_hat[2]);
+    let k = h_hat[0] / (1.0 + h_hat[2]);
+    let s2 = 1.0 + h.powi(2) + k.powi(2);
+    // Unit vectors of the equinoctial frame
+    let f_hat = Vector3::new(1.0 + h.powi(2) - k.powi(2), 2.0 * h * k, -2.0 * k) / s2;
+    let g_hat = Vector3::new(2.0 * h * k, 1.0 - h.powi(2) + k.powi(2), 2.0 * h) / s2;
+    Ok(Vector6::new(
+        hmag.powi(2) / gm,
+        ecc_vec.dot(&f_hat),
+        ecc_vec.dot(&g_hat),
+        h,
+        k,
+        radius.dot(&g_hat).atan2(radius.dot(&f_hat)),
+    ))
+}
+
+/// Returns the Cartesian state of the provided modified equinoctial elements `[p, f, g, h, k, L]`, for the provided gravitational parameter.
+pub fn equinoctial_to_cartesian(mee: &Vector6<f64>, gm: f64) -> Vector6<f64> {
+    let (p, f, g, h, k) = (mee[0], mee[1], mee[2], mee[3], mee[4]);
+    let (sin_l, cos_l) = mee[5].sin_cos();
+    let alpha2 = h.powi(2) - k.powi(2);
+    let s2 = 1.0 + h.powi(2) + k.powi(2);
+    let rmag = p / (1.0 + f * cos_l + g * sin_l);
+    let vscale = (gm / p).sqrt() / s2;
+    Vector6::new(
+        rmag / s2 * (cos_l + alpha2 * cos_l + 2.0 * h * k * sin_l),
+        rmag / s2 * (sin_l - alpha2 * sin_l + 2.0 * h * k * cos_l),
+        2.0 * rmag / s2 * (h * sin_l - k * cos_l),
+        -vscale * (sin_l + alpha2 * sin_l - 2.0 * h * k * cos_l + g - 2.0 * f * h * k + alpha2 * g),
+        -vscale
+            * (-cos_l + alpha2 * cos_l + 2.0 * h * k * sin_l - f + 2.0 * g * h * k + alpha2 * f),
+        2.0 * vscale * (h * cos_l + k * sin_l + f * h + g * k),
+    )
+}
+
+/// Replaces the Cartesian state in the first six components of the provided state vector by its modified equinoctial elements.
+fn to_equinoctial_vector<N: DimName>(
+    vector: &OVector<f64, N>,
+    gm: f64,
+) -> Result<OVector<f64, N>, DynamicsError>
+where
+    DefaultAllocator: Allocator<f64, N>,
+{
+    let mee = cartesian_to_equinoctial(&vector.fixed_rows::<6>(0).into_owned(), gm)
+        .map_err(|source| DynamicsError::DynamicsAstro { source })?;
+    let mut mee_vector = vector.clone();
+    mee_vector.fixed_rows_mut::<6>(0).copy_from(&mee);
+    Ok(mee_vector)
+}
+
+/// Replaces the modified equinoctial elements in the first six components of the provided state vector by the Cartesian state.
+fn from_equinoctial_vector<N: DimName>(vector: &OVector<f64, N>, gm: f64) -> OVector<f64, N>
+where
+    DefaultAllocator: Allocator<f64, N>,
+{
+    let cartesian = equinoctial_to_cartesian(&vector.fixed_rows::<6>(0).into_owned(), gm);
+    let mut cartesian_vector = vector.clone();
+    cartesian_vector
+        .fixed_rows_mut::<6>(0)
+        .copy_from(&cartesian);
+    cartesian_vector
+}
+
+/// Computes the rates of the modified equinoctial elements in the provided state vector with Gauss's variational equations,
+/// from the derivative of the Cartesian state vector `d_x` computed by the wrapped dynamics at the osculating orbit `osc`.
+/// The other components of the derivative are returned as is.
+fn equinoctial_rates<N: DimName>(
+    vector: &OVector<f64, N>,
+    mut d_x: OVector<f64, N>,
+    osc: &Orbit,
+) -> Result<OVector<f64, N>, DynamicsError>
+where
+    DefaultAllocator: Allocator<f64, N>,
+{
+    let gm = osc.frame.gm();
+    // The perturbation is the acceleration of the wrapped dynamics without the two body acceleration
+    let two_body_accel = (-gm / osc.rmag_km().powi(3)) * osc.radius();
+    let perturbation = d_x.fixed_rows::<3>(3) - two_body_accel;
+    let dcm = osc
+        .dcm_from_traj_frame(Frame::RCN)
+        .map_err(|source| DynamicsError::DynamicsAstro { source })?;
+    let accel_rcn = dcm.transpose() * perturbation;
+    let (radial, transverse, normal) = (accel_rcn[0], accel_rcn[1], accel_rcn[2]);
+
+    let (p, f, g, h, k) = (vector[0], vector[1], vector[2], vector[3], vector[4]);
+    let (sin_l, cos_l) = vector[5].sin_cos();
+    let q = 1.0 + f * cos_l + g * sin_l;
+    let s2 = 1.0 + h.powi(2) + k.powi(2);
+    let sqrt_p_gm = (p / gm).sqrt();
+    let hk_term = h * sin_l - k * cos_l;
+
+    let rates = Vector6::new(
+        2.0 * p / q * sqrt_p_gm * transverse,
+        sqrt_p_gm
+            * (radial * sin_l + ((q + 1.0) * cos_l + f) * transverse / q
+                - hk_term * g * normal / q),
+        sqrt_p_gm
+            * (-radial * cos_l
+                + ((q + 1.0) * sin_l + g) * transverse / q
+                + hk_term * f * normal / q),
+        sqrt_p_gm * s2 * cos_l * normal / (2.0 * q),
+        sqrt_p_gm * s2 * sin_l * normal / (2.0 * q),
+        (gm * p).sqrt() * (q / p).powi(2) + sqrt_p_gm * hk_term * normal / q,
+    );
+    d_x.fixed_rows_mut::<6>(0).copy_from(&rates);
+    Ok(d_x)
+}
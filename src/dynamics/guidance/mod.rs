@@ -17,6 +17,7 @@
 */
 
 use crate::cosmic::{Frame, GuidanceMode, Orbit, Spacecraft, STD_GRAVITY};
+use crate::dynamics::propulsion::Propulsion;
 use crate::errors::NyxError;
 use crate::linalg::Vector3;
 use serde::{Deserialize, Serialize};
@@ -74,6 +75,12 @@ pub trait GuidanceLaw: fmt::Display + Send + Sync {
     /// For example, 0 means coasting, i.e. no thrusting, and 1 means maximum thrusting.
     fn throttle(&self, osc_state: &Spacecraft) -> f64;
 
+    /// Returns the throttle level of each engine of the provided propulsion subsystem, each between [0;1].
+    /// By default, all of the engines are set to the throttle level of this guidance law.
+    fn allocate(&self, osc_state: &Spacecraft, propulsion: &Propulsion) -> Vec<f64> {
+        vec![self.throttle(osc_state); propulsion.engines.len()]
+    }
+
     /// Updates the state of the BaseSpacecraft for the next maneuver, e.g. prepares the controller for the next maneuver
     fn next(&self, next_state: &mut Spacecraft);
 
@@ -119,6 +126,18 @@ pub enum GuidanceErrors {
     NoThrustersDefined,
     #[snafu(display("Throttle is not between 0.0 and 1.0: {ratio}"))]
     ThrottleRatio { ratio: f64 },
+    #[snafu(display(
+        "Thrust allocated to {got} engines but the propulsion subsystem has {expected}"
+    ))]
+    AllocationSize { expected: usize, got: usize },
+    #[snafu(display(
+        "Engine #{engine} is fed by tank #{tank} but at most {max} tanks are supported"
+    ))]
+    FeedTank {
+        engine: usize,
+        tank: usize,
+        max: usize,
+    },
     #[snafu(display("Invalid finite burn control direction u = [{x}, {y}, {z}] => i-plane = {in_plane_deg} deg, Delta = {out_of_plane_deg} deg",))]
     InvalidDirection {
         x: f64,
@@ -18,7 +18,8 @@
 
 use crate::cosmic::{AstroError, Orbit};
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName, Matrix3, OMatrix, OVector, Vector3};
+use crate::linalg::{DefaultAllocator, DimName, Matrix3, Matrix3x6, OMatrix, OVector, Vector3};
+use crate::time::Epoch;
 use crate::State;
 use hyperdual::{OHyperdual, Owned};
 use snafu::Snafu;
@@ -53,18 +54,58 @@ pub mod guidance;
 /// Defines some velocity change controllers.
 pub mod deltavctrl;
 
+/// Defines a propulsion subsystem of several engines.
+pub mod propulsion;
+pub use self::propulsion::*;
+
+/// Defines the solar array power model of solar electric propulsion.
+pub mod power;
+pub use self::power::*;
+
 /// Defines solar radiation pressure models
 pub mod solarpressure;
 pub use self::solarpressure::*;
 
+/// Define the Earth albedo and infrared radiation pressure model
+pub mod earth_radiation;
+pub use self::earth_radiation::*;
+
 /// Define drag models
 pub mod drag;
 pub use self::drag::*;
 
+/// Define the plate models of the spacecraft surface and the attitude laws orienting them, used by the SRP and drag models
+pub mod plates;
+pub use self::plates::*;
+
+/// Define the empirical accelerations
+pub mod empirical;
+pub use self::empirical::*;
+
 /// Define the spherical harmonic models.
 pub mod sph_harmonics;
 pub use self::sph_harmonics::*;
 
+/// Define the solid Earth and ocean tide corrections of the spherical harmonic models.
+pub mod tides;
+pub use self::tides::*;
+
+/// Define the spherical harmonic models of third bodies.
+pub mod third_body;
+pub use self::third_body::*;
+
+/// Define the post-Newtonian relativistic correction.
+pub mod relativity;
+pub use self::relativity::*;
+
+/// Define the rigid body attitude dynamics and the torque models.
+pub mod attitude;
+pub use self::attitude::*;
+
+/// Define the propagation of the modified equinoctial elements with Gauss's variational equations.
+pub mod equinoctial;
+pub use self::equinoctial::*;
+
 /// The `Dynamics` trait handles and stores any equation of motion *and* the state is integrated.
 ///
 /// Its design is such that several of the provided dynamics can be combined fairly easily. However,
@@ -126,6 +167,42 @@ where
     fn finally(&self, next_state: Self::StateType) -> Result<Self::StateType, DynamicsError> {
         Ok(next_state)
     }
+    /// Optionally returns the energy of the provided state in these dynamics, used as a diagnostic of the integration.
+    /// For example, the drift of the energy of conservative dynamics should remain bounded with a symplectic integrator.
+    /// Note that the orbital and spacecraft dynamics return the two body energy, which is only conserved without acceleration models.
+    fn energy(&self, _state: &Self::StateType) -> Option<f64> {
+        None
+    }
+
+    /// Optionally returns the derivative of the physical time with respect to a fictitious time at the provided state vector, e.g. from a Sundman transformation.
+    /// If defined, the propagator takes its steps in this fictitious time (except for the last step until the requested epoch), which regularizes them.
+    fn time_transformation(
+        &self,
+        _state_vec: &OVector<f64, <Self::StateType as State>::VecLength>,
+        _state_ctx: &Self::StateType,
+    ) -> Option<f64> {
+        None
+    }
+
+    /// Optionally returns the vector which is integrated for the provided state, if these dynamics do not integrate the state vector itself
+    /// (e.g. orbital elements instead of the Cartesian state). The equations of motion are then called with that vector.
+    fn integration_vector(
+        &self,
+        _state: &Self::StateType,
+    ) -> Result<Option<OVector<f64, <Self::StateType as State>::VecLength>>, DynamicsError> {
+        Ok(None)
+    }
+
+    /// Sets the provided state from the integrated vector at the provided epoch. This must be the inverse of `integration_vector`.
+    fn set_integration_vector(
+        &self,
+        state: &mut Self::StateType,
+        epoch: Epoch,
+        vector: &OVector<f64, <Self::StateType as State>::VecLength>,
+    ) -> Result<(), DynamicsError> {
+        state.set(epoch, vector);
+        Ok(())
+    }
 }
 
 /// The `ForceModel` trait handles immutable dynamics which return a force. Those will be divided by the mass of the spacecraft to compute the acceleration (F = ma).
@@ -139,6 +216,28 @@ pub trait ForceModel: Send + Sync + fmt::Display {
     /// computation of the STM. The `osc_ctx` is the osculating context, i.e. it changes for each sub-step of the integrator.
     fn dual_eom(&self, osc_ctx: &Spacecraft)
         -> Result<(Vector3<f64>, Matrix3<f64>), DynamicsError>;
+
+    /// Returns the force and its partials with respect to the position (first three columns) and the velocity (last three columns).
+    /// This is what the spacecraft dynamics use to compute the STM. By default, the position partials are those of `dual_eom` and
+    /// the velocity partials are zero, so the force models which depend on the velocity (e.g. drag) must implement it.
+    fn dual_eom_pos_vel(
+        &self,
+        osc_ctx: &Spacecraft,
+    ) -> Result<(Vector3<f64>, Matrix3x6<f64>), DynamicsError> {
+        let (force, grad) = self.dual_eom(osc_ctx)?;
+        let mut grad_pos_vel = Matrix3x6::zeros();
+        grad_pos_vel.fixed_view_mut::<3, 3>(0, 0).copy_from(&grad);
+        Ok((force, grad_pos_vel))
+    }
+
+    /// If the force of this model is proportional to a spacecraft coefficient which may be estimated (e.g. Cr for SRP and Cd for drag),
+    /// returns the index of that coefficient in the spacecraft state vector, i.e. `CR_IDX` or `CD_IDX`.
+    /// This allows the spacecraft dynamics to compute the partials of the acceleration with respect to that coefficient.
+    /// Only Cr and Cd are part of the spacecraft state: the coefficients of an empirical acceleration cannot be estimated yet,
+    /// and any other index is rejected by the spacecraft dynamics.
+    fn estimation_index(&self) -> Option<usize> {
+        None
+    }
 }
 
 /// The `AccelModel` trait handles immutable dynamics which return an acceleration. Those can be added directly to Orbital Dynamics for example.
@@ -165,4 +264,20 @@ pub enum DynamicsError {
     DynamicsAstro { source: AstroError },
     #[snafu(display("dynamical model encountered an issue with the guidance: {source}"))]
     DynamicsGuidance { source: GuidanceErrors },
+    #[snafu(display("dynamical model could not rotate between frames: {msg}"))]
+    FrameRotation { msg: String },
+    #[snafu(display(
+        "{model} requires an integration frame centered on {center}, but the state is in {frame}"
+    ))]
+    IntegrationFrameCenter {
+        model: String,
+        center: String,
+        frame: String,
+    },
+    #[snafu(display("state index {idx} is neither Cr nor Cd and cannot be estimated"))]
+    UnsupportedEstimationIndex { idx: usize },
+    #[snafu(display("inertia tensor is singular"))]
+    SingularInertia,
+    #[snafu(display("these dynamics do not support the propagation of the STM"))]
+    StateTransitionMatrixUnsupported,
 }
@@ -33,6 +33,8 @@ pub use super::sph_harmonics::Harmonics;
 
 pub struct OrbitalDynamics {
     pub accel_models: Vec<Arc<dyn AccelModel + Sync>>,
+    /// Optional Sundman time transformation, to integrate in a fictitious time instead of the physical time
+    pub sundman: Option<Sundman>,
 }
 
 impl OrbitalDynamics {
@@ -49,7 +51,10 @@ impl OrbitalDynamics {
 
     /// Initialize orbital dynamics with a list of acceleration models
     pub fn new(accel_models: Vec<Arc<dyn AccelModel + Sync>>) -> Self {
-        Self { accel_models }
+        Self {
+            accel_models,
+            sundman: None,
+        }
     }
 
     /// Initialize new orbital mechanics with the provided model.
@@ -69,12 +74,69 @@ impl OrbitalDynamics {
         me.add_model(accel_model);
         me
     }
+
+    /// Clone these dynamics and integrate them with the provided Sundman time transformation.
+    /// The STM cannot be propagated with a time transformation, and multistep propagators (e.g. `Propagator::abm8`) do not support it.
+    pub fn with_sundman(self, sundman: Sundman) -> Self {
+        let mut me = self;
+        me.sundman = Some(sundman);
+        me
+    }
+}
+
+/// The Sundman time transformation `dt = (r / r_ref)^α ds` regularizes the equations of motion with respect to the distance
+/// to the central body: with a constant step in the fictitious time `s`, the steps in physical time are small close to the
+/// central body and large far from it, which is well suited to highly eccentric orbits and close flybys.
+///
+/// The state remains the Cartesian state at the physical epoch, so events, trajectories and acceleration models are unaffected.
+/// The step sizes of the propagator are then in fictitious time, which is in seconds at the reference radius.
+///
+/// Note that the Kustaanheimo-Stiefel regularization, which also transforms the Cartesian coordinates, is not available.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sundman {
+    /// Exponent α of the transformation: 1 for a fictitious time proportional to the eccentric anomaly, 2 for the true anomaly
+    pub exponent: f64,
+    /// Radius at which the fictitious time and the physical time elapse at the same rate
+    pub reference_radius_km: f64,
+}
+
+impl Sundman {
+    pub fn new(exponent: f64, reference_radius_km: f64) -> Self {
+        Self {
+            exponent,
+            reference_radius_km,
+        }
+    }
+
+    /// The transformation with an exponent of 1, for which the fictitious time is proportional to the eccentric anomaly in two body dynamics.
+    pub fn eccentric_anomaly(reference_radius_km: f64) -> Self {
+        Self::new(1.0, reference_radius_km)
+    }
+
+    /// Returns the derivative of the physical time with respect to the fictitious time at the provided distance to the central body.
+    pub fn dt_ds(&self, rmag_km: f64) -> f64 {
+        (rmag_km / self.reference_radius_km).powf(self.exponent)
+    }
+}
+
+impl fmt::Display for Sundman {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Sundman transformation (r / {} km)^{}",
+            self.reference_radius_km, self.exponent
+        )
+    }
 }
 
 impl fmt::Display for OrbitalDynamics {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let models: Vec<String> = self.accel_models.iter().map(|x| format!("{x}")).collect();
-        write!(f, "Orbital dynamics: {}", models.join("; "))
+        write!(f, "Orbital dynamics: {}", models.join("; "))?;
+        if let Some(sundman) = &self.sundman {
+            write!(f, " (with {sundman})")?;
+        }
+        Ok(())
     }
 }
 
@@ -82,12 +144,34 @@ impl Dynamics for OrbitalDynamics {
     type HyperdualSize = Const<7>;
     type StateType = Orbit;
 
+    /// The two body specific mechanical energy of the state, in km^2/s^2, if its frame has a gravitational parameter.
+    /// Note that it is only conserved in two body dynamics: its drift otherwise includes the effect of the acceleration models.
+    fn energy(&self, state: &Orbit) -> Option<f64> {
+        match state.frame {
+            Frame::Celestial { .. } | Frame::Geoid { .. } => Some(state.energy_km2_s2()),
+            _ => None,
+        }
+    }
+
+    fn time_transformation(
+        &self,
+        state_vec: &OVector<f64, Const<42>>,
+        _ctx: &Orbit,
+    ) -> Option<f64> {
+        self.sundman
+            .map(|sundman| sundman.dt_ds(state_vec.fixed_rows::<3>(0).norm()))
+    }
+
     fn eom(
         &self,
         delta_t_s: f64,
         state: &OVector<f64, Const<42>>,
         ctx: &Orbit,
     ) -> Result<OVector<f64, Const<42>>, DynamicsError> {
+        if ctx.stm.is_some() && self.sundman.is_some() {
+            // The STM would be with respect to the fictitious time
+            return Err(DynamicsError::StateTransitionMatrixUnsupported);
+        }
         let osc = ctx.set_with_delta_seconds(delta_t_s, state);
         let (new_state, new_stm) = if ctx.stm.is_some() {
             let (state, grad) = self.dual_eom(delta_t_s, &osc)?;
@@ -0,0 +1,295 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::linalg::{Matrix3, Vector3};
+use std::fmt;
+use std::sync::Arc;
+
+/// The `AttitudeLaw` trait provides the orientation of the body frame of the spacecraft, as used by the plate models.
+pub trait AttitudeLaw: Send + Sync + fmt::Display {
+    /// Returns the DCM from the body frame of the spacecraft to the integration frame of the provided state.
+    fn dcm_to_inertial(&self, osc: &Orbit) -> Matrix3<f64>;
+}
+
+/// Nadir pointing attitude: the body Z axis points to the center of the integration frame,
+/// the body Y axis is opposite to the orbital angular momentum, and the body X axis is close to the velocity.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct NadirPointing;
+
+impl AttitudeLaw for NadirPointing {
+    fn dcm_to_inertial(&self, osc: &Orbit) -> Matrix3<f64> {
+        let z = -osc.radius() / osc.rmag_km();
+        let y = -osc.hvec() / osc.hvec().norm();
+        let x = y.cross(&z);
+        Matrix3::from_columns(&[x, y, z])
+    }
+}
+
+impl fmt::Display for NadirPointing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "nadir pointing")
+    }
+}
+
+/// Sun pointing attitude: the body Z axis points to the Sun and the body Y axis is perpendicular to both the Sun direction and the orbital angular momentum.
+#[derive(Clone)]
+pub struct SunPointing {
+    sun_frame: Frame,
+    cosm: Arc<Cosm>,
+}
+
+impl SunPointing {
+    pub fn new(cosm: Arc<Cosm>) -> Self {
+        Self {
+            sun_frame: cosm.frame("Sun J2000"),
+            cosm,
+        }
+    }
+}
+
+impl AttitudeLaw for SunPointing {
+    fn dcm_to_inertial(&self, osc: &Orbit) -> Matrix3<f64> {
+        // Position of the spacecraft as seen from the Sun
+        let r_sun = self.cosm.frame_chg(osc, self.sun_frame).radius();
+        let z = -r_sun / r_sun.norm();
+        let h = osc.hvec();
+        let mut y = z.cross(&h);
+        if y.norm() < f64::EPSILON * h.norm() {
+            // The Sun is along the orbital angular momentum, pick any perpendicular direction
+            y = z.cross(&Vector3::x());
+        }
+        let y = y.normalize();
+        let x = y.cross(&z);
+        Matrix3::from_columns(&[x, y, z])
+    }
+}
+
+impl fmt::Display for SunPointing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sun pointing")
+    }
+}
+
+/// Inertial attitude: the body frame is fixed with respect to the integration frame.
+#[derive(Copy, Clone, Debug)]
+pub struct InertialAttitude {
+    /// DCM from the body frame to the integration frame
+    pub dcm: Matrix3<f64>,
+}
+
+impl InertialAttitude {
+    /// Initializes an inertial attitude where the body frame is aligned with the integration frame.
+    pub fn aligned() -> Self {
+        Self {
+            dcm: Matrix3::identity(),
+        }
+    }
+}
+
+impl AttitudeLaw for InertialAttitude {
+    fn dcm_to_inertial(&self, _osc: &Orbit) -> Matrix3<f64> {
+        self.dcm
+    }
+}
+
+impl fmt::Display for InertialAttitude {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "inertial attitude")
+    }
+}
+
+/// A flat plate of the surface of the spacecraft.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plate {
+    /// Area of the plate, in m^2
+    pub area_m2: f64,
+    /// Outward unit normal of the plate in the body frame
+    pub normal: Vector3<f64>,
+    /// Fraction of the incoming radiation which is specularly reflected
+    pub specular: f64,
+    /// Fraction of the incoming radiation which is diffusely reflected, the remainder being absorbed
+    pub diffuse: f64,
+    /// Coefficient of drag of the plate
+    pub cd: f64,
+    /// Set to true if both faces of the plate are exposed, e.g. a solar array
+    pub two_sided: bool,
+    /// Rotation axis in the body frame, if the plate is articulated to track the Sun (e.g. solar array drive).
+    /// The normal is then rotated about that axis to be as close as possible to the Sun direction.
+    pub tracking_axis: Option<Vector3<f64>>,
+}
+
+impl Plate {
+    /// Initializes a one-sided, fixed plate with a coefficient of drag of 2.2.
+    pub fn new(area_m2: f64, normal: Vector3<f64>, specular: f64, diffuse: f64) -> Self {
+        Self {
+            area_m2,
+            normal: normal.normalize(),
+            specular,
+            diffuse,
+            cd: 2.2,
+            two_sided: false,
+            tracking_axis: None,
+        }
+    }
+
+    /// Sets the coefficient of drag of this plate.
+    pub fn with_cd(mut self, cd: f64) -> Self {
+        self.cd = cd;
+        self
+    }
+
+    /// Exposes both faces of this plate.
+    pub fn two_sided(mut self) -> Self {
+        self.two_sided = true;
+        self
+    }
+
+    /// Articulates this plate about the provided body axis so that it tracks the Sun.
+    pub fn with_sun_tracking(mut self, axis: Vector3<f64>) -> Self {
+        self.tracking_axis = Some(axis.normalize());
+        self
+    }
+
+    /// Returns the outward normal of this plate in the body frame, given the direction to the Sun in the body frame.
+    fn body_normal(&self, sun_body: &Vector3<f64>) -> Vector3<f64> {
+        match self.tracking_axis {
+            Some(axis) => {
+                let projected = sun_body - axis.dot(sun_body) * axis;
+                if projected.norm() > 1e-12 {
+                    projected.normalize()
+                } else {
+                    self.normal
+                }
+            }
+            None => self.normal,
+        }
+    }
+
+    /// Returns the normal of the face exposed to the provided direction and the cosine of the incidence angle.
+    pub(crate) fn facing(
+        &self,
+        normal: Vector3<f64>,
+        direction: &Vector3<f64>,
+    ) -> (Vector3<f64>, f64) {
+        let cos_theta = normal.dot(direction);
+        if self.two_sided && cos_theta < 0.0 {
+            (-normal, -cos_theta)
+        } else {
+            (normal, cos_theta)
+        }
+    }
+}
+
+/// A plate model of the spacecraft (e.g. box-wing), with the attitude law which orients it.
+///
+/// When used by `SolarPressure` or `Drag`, the plate model replaces the area and coefficient of the `SrpConfig` and `DragConfig` of the spacecraft.
+#[derive(Clone)]
+pub struct PlateModel {
+    pub plates: Vec<Plate>,
+    pub attitude: Arc<dyn AttitudeLaw>,
+}
+
+impl PlateModel {
+    pub fn new(plates: Vec<Plate>, attitude: Arc<dyn AttitudeLaw>) -> Self {
+        Self { plates, attitude }
+    }
+
+    /// Initializes a box-wing model: a box of the provided dimensions along the body X, Y and Z axes (in meters),
+    /// and a two-sided solar array of the provided area rotating about the body Y axis to track the Sun.
+    ///
+    /// The box is covered with a material reflecting 30% specularly and 30% diffusely, and the solar array reflects 10% specularly and 10% diffusely.
+    pub fn box_wing(
+        dims_m: Vector3<f64>,
+        wing_area_m2: f64,
+        attitude: Arc<dyn AttitudeLaw>,
+    ) -> Self {
+        let mut plates = Vec::with_capacity(7);
+        for axis in 0..3 {
+            let area_m2 = dims_m[(axis + 1) % 3] * dims_m[(axis + 2) % 3];
+            let mut normal = Vector3::zeros();
+            normal[axis] = 1.0;
+            plates.push(Plate::new(area_m2, normal, 0.3, 0.3));
+            plates.push(Plate::new(area_m2, -normal, 0.3, 0.3));
+        }
+        plates.push(
+            Plate::new(wing_area_m2, Vector3::z(), 0.1, 0.1)
+                .two_sided()
+                .with_sun_tracking(Vector3::y()),
+        );
+        Self::new(plates, attitude)
+    }
+
+    /// Returns the outward normals of the plates in the integration frame, where `sun_unit` is the direction from the spacecraft to the Sun in the integration frame.
+    pub fn inertial_normals(&self, osc: &Orbit, sun_unit: &Vector3<f64>) -> Vec<Vector3<f64>> {
+        let dcm = self.attitude.dcm_to_inertial(osc);
+        let sun_body = dcm.transpose() * sun_unit;
+        self.plates
+            .iter()
+            .map(|plate| dcm * plate.body_normal(&sun_body))
+            .collect()
+    }
+
+    /// Returns the solar radiation pressure force in N (Montenbruck and Gill, eq. 3.73), where `sun_unit` is the direction from the spacecraft to the Sun
+    /// in the integration frame and `pressure` is the solar radiation pressure in N/m^2.
+    pub fn srp_force(&self, osc: &Orbit, sun_unit: &Vector3<f64>, pressure: f64) -> Vector3<f64> {
+        let mut force = Vector3::zeros();
+        for (plate, normal) in self.plates.iter().zip(self.inertial_normals(osc, sun_unit)) {
+            let (normal, cos_theta) = plate.facing(normal, sun_unit);
+            if cos_theta > 0.0 {
+                force -= pressure
+                    * plate.area_m2
+                    * cos_theta
+                    * ((1.0 - plate.specular) * sun_unit
+                        + 2.0 * (plate.specular * cos_theta + plate.diffuse / 3.0) * normal);
+            }
+        }
+        force
+    }
+
+    /// Returns the product of the coefficient of drag and the area exposed to the flow, in m^2, where `velocity_unit` is the direction of the
+    /// velocity of the spacecraft relative to the atmosphere and `sun_unit` the direction to the Sun, both in the integration frame.
+    pub fn cd_area_m2(
+        &self,
+        osc: &Orbit,
+        velocity_unit: &Vector3<f64>,
+        sun_unit: &Vector3<f64>,
+    ) -> f64 {
+        self.plates
+            .iter()
+            .zip(self.inertial_normals(osc, sun_unit))
+            .map(|(plate, normal)| {
+                let (_, cos_theta) = plate.facing(normal, velocity_unit);
+                plate.cd * plate.area_m2 * cos_theta.max(0.0)
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for PlateModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let area_m2: f64 = self.plates.iter().map(|plate| plate.area_m2).sum();
+        write!(
+            f,
+            "{} plates of {:.3} m^2 with {}",
+            self.plates.len(),
+            area_m2,
+            self.attitude
+        )
+    }
+}
@@ -0,0 +1,247 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::guidance::GuidanceLaw;
+use super::propulsion::Propulsion;
+use crate::cosmic::eclipse::EclipseLocator;
+use crate::cosmic::{LightTimeCalc, Orbit, Spacecraft, AU};
+use crate::errors::NyxError;
+use crate::linalg::Vector3;
+use crate::time::{Epoch, Unit};
+use std::fmt;
+use std::sync::Arc;
+
+/// Solar array power model of a solar electric propulsion spacecraft.
+///
+/// The power generated at a distance r from the Sun (in AU) follows an extension of the model of the GMAT solar power system:
+///
+/// P = P0 / r^2 * (c0 + c1 / r + c2 / r^2 + c3 * r + c4 * r^2) / (1 + c5 * r + c6 * r^2)
+///
+/// where P0 is the power at 1 AU, degraded by a constant annual rate since the beginning of life, and scaled by the fraction of the Sun
+/// which is visible from the spacecraft. The bus power is reserved, and the remaining power is available to the thrusters.
+///
+/// The GMAT model only has five coefficients, (g0 + g1 / r + g2 / r^2) / (1 + g3 * r + g4 * r^2), which is this model with c3 = c4 = 0
+/// (refer to `with_gmat_fit`).
+#[allow(non_snake_case)]
+#[derive(Clone)]
+pub struct SolarArrayPower {
+    /// Power generated at 1 AU at the beginning of life, in kW
+    pub bol_power_kW: f64,
+    /// Epoch of the beginning of life
+    pub bol_epoch: Epoch,
+    /// Fraction of the power lost per year, e.g. 0.02 for 2% per year
+    pub degradation_per_year: f64,
+    /// Coefficients of the fit of the power with respect to the distance to the Sun
+    pub fit: [f64; 7],
+    /// Power reserved for the bus, in kW
+    pub bus_power_kW: f64,
+    /// Eclipse locator whose light source is the Sun
+    pub e_loc: EclipseLocator,
+}
+
+impl SolarArrayPower {
+    /// Initializes a solar array power model following the inverse square law, without degradation nor bus power.
+    #[allow(non_snake_case)]
+    pub fn new(bol_power_kW: f64, bol_epoch: Epoch, e_loc: EclipseLocator) -> Self {
+        Self {
+            bol_power_kW,
+            bol_epoch,
+            degradation_per_year: 0.0,
+            fit: [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            bus_power_kW: 0.0,
+            e_loc,
+        }
+    }
+
+    /// Sets the fraction of the power lost per year since the beginning of life.
+    pub fn with_degradation(mut self, degradation_per_year: f64) -> Self {
+        self.degradation_per_year = degradation_per_year;
+        self
+    }
+
+    /// Sets the coefficients of the fit of the power with respect to the distance to the Sun, ordered as in the model.
+    pub fn with_fit(mut self, fit: [f64; 7]) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Sets the five coefficients of the fit of the GMAT solar power system, ordered as in GMAT.
+    pub fn with_gmat_fit(mut self, gmat_fit: [f64; 5]) -> Self {
+        self.fit = [
+            gmat_fit[0],
+            gmat_fit[1],
+            gmat_fit[2],
+            0.0,
+            0.0,
+            gmat_fit[3],
+            gmat_fit[4],
+        ];
+        self
+    }
+
+    /// Reserves the provided power for the bus, in kW.
+    #[allow(non_snake_case)]
+    pub fn with_bus_power(mut self, bus_power_kW: f64) -> Self {
+        self.bus_power_kW = bus_power_kW;
+        self
+    }
+
+    /// Returns the power generated by the solar arrays at the provided state, in kW.
+    /// Errors if the state of the Sun is not available at the epoch of the provided state.
+    #[allow(non_snake_case)]
+    pub fn generated_power_kW(&self, osc: &Orbit) -> Result<f64, NyxError> {
+        let sun = self.e_loc.cosm.try_celestial_state(
+            &self.e_loc.light_source.ephem_path(),
+            osc.epoch,
+            osc.frame,
+            LightTimeCalc::None,
+        )?;
+        let r_au = (sun.radius() - osc.radius()).norm() / AU;
+
+        let years = (osc.epoch - self.bol_epoch).to_unit(Unit::Day) / 365.25;
+        let p0 = self.bol_power_kW * (1.0 - self.degradation_per_year).powf(years.max(0.0));
+
+        let c = &self.fit;
+        let relative =
+            (c[0] + c[1] / r_au + c[2] / r_au.powi(2) + c[3] * r_au + c[4] * r_au.powi(2))
+                / (r_au.powi(2) * (1.0 + c[5] * r_au + c[6] * r_au.powi(2)));
+
+        let illumination: f64 = self.e_loc.compute(osc).into();
+
+        Ok((p0 * relative * illumination).max(0.0))
+    }
+
+    /// Returns the power available to the thrusters at the provided state, in kW.
+    /// Errors if the state of the Sun is not available at the epoch of the provided state.
+    #[allow(non_snake_case)]
+    pub fn available_power_kW(&self, osc: &Orbit) -> Result<f64, NyxError> {
+        Ok((self.generated_power_kW(osc)? - self.bus_power_kW).max(0.0))
+    }
+
+    /// Returns the power available to the thrusters at the provided state, in kW, or zero (i.e. no thrust) if it cannot be computed.
+    #[allow(non_snake_case)]
+    fn available_power_or_none_kW(&self, osc: &Orbit) -> f64 {
+        match self.available_power_kW(osc) {
+            Ok(power) => power,
+            Err(e) => {
+                warn!(
+                    "no power available to the thrusters at {}: {}",
+                    osc.epoch, e
+                );
+                0.0
+            }
+        }
+    }
+}
+
+impl fmt::Display for SolarArrayPower {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "solar arrays of {:.3} kW at 1 AU (BOL {}, {:.2}%/yr degradation, {:.3} kW bus) with eclipse {}",
+            self.bol_power_kW,
+            self.bol_epoch,
+            self.degradation_per_year * 100.0,
+            self.bus_power_kW,
+            self.e_loc
+        )
+    }
+}
+
+/// Limits the thrust of a guidance law to the power available to the thrusters.
+///
+/// With the single thruster of the spacecraft, the throttle of the guidance law is capped to the ratio of the available power to the
+/// `thruster_power_kW` needed at full throttle, and the Isp of the thruster is unchanged. If that power is not positive, the thruster
+/// does not draw from the solar arrays and its throttle is not limited.
+/// With a propulsion subsystem, the throttle levels of the engines are scaled down so that their total input power does not exceed
+/// the available power, and the throttle tables of the engines map that power to thrust and Isp.
+/// If the available power cannot be computed (e.g. the state of the Sun is not available), the thrusters are shut down.
+#[allow(non_snake_case)]
+#[derive(Clone)]
+pub struct PowerLimited {
+    pub guid_law: Arc<dyn GuidanceLaw>,
+    pub power: Arc<SolarArrayPower>,
+    /// Input power of the single thruster of the spacecraft at full throttle, in kW
+    pub thruster_power_kW: f64,
+}
+
+impl PowerLimited {
+    /// Limits the provided guidance law with the provided power model, where the single thruster of the spacecraft (if used) requires
+    /// `thruster_power_kW` at full throttle.
+    #[allow(non_snake_case)]
+    pub fn new(
+        guid_law: Arc<dyn GuidanceLaw>,
+        power: SolarArrayPower,
+        thruster_power_kW: f64,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            guid_law,
+            power: Arc::new(power),
+            thruster_power_kW,
+        })
+    }
+}
+
+impl fmt::Display for PowerLimited {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} limited by {}", self.guid_law, self.power)
+    }
+}
+
+impl GuidanceLaw for PowerLimited {
+    fn direction(&self, osc_state: &Spacecraft) -> Vector3<f64> {
+        self.guid_law.direction(osc_state)
+    }
+
+    fn throttle(&self, osc_state: &Spacecraft) -> f64 {
+        let throttle = self.guid_law.throttle(osc_state);
+        if throttle > 0.0 && self.thruster_power_kW > 0.0 {
+            let available = self.power.available_power_or_none_kW(&osc_state.orbit);
+            throttle.min(available / self.thruster_power_kW)
+        } else {
+            throttle
+        }
+    }
+
+    fn allocate(&self, osc_state: &Spacecraft, propulsion: &Propulsion) -> Vec<f64> {
+        let mut throttles = self.guid_law.allocate(osc_state, propulsion);
+        let requested: f64 = throttles
+            .iter()
+            .zip(&propulsion.engines)
+            .map(|(throttle, engine)| throttle * engine.throttle_table.max_power_kW())
+            .sum();
+        if requested > 0.0 {
+            let available = self.power.available_power_or_none_kW(&osc_state.orbit);
+            if requested > available {
+                let scale = available / requested;
+                for throttle in throttles.iter_mut() {
+                    *throttle *= scale;
+                }
+            }
+        }
+        throttles
+    }
+
+    fn next(&self, next_state: &mut Spacecraft) {
+        self.guid_law.next(next_state)
+    }
+
+    fn achieved(&self, osc_state: &Spacecraft) -> Result<bool, NyxError> {
+        self.guid_law.achieved(osc_state)
+    }
+}
@@ -0,0 +1,587 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::guidance::{GuidanceErrors, Thruster};
+use crate::cosmic::{MAX_TANKS, STD_GRAVITY};
+use crate::io::ConfigError;
+use crate::linalg::{Matrix3, Vector3};
+use crate::time::Duration;
+use nalgebra::{Rotation3, Unit, UnitQuaternion};
+use std::fmt;
+
+/// A point of a throttle table: the thrust and Isp of an engine for a given input power.
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ThrottlePoint {
+    /// Input power of the engine, in kW
+    pub power_kW: f64,
+    /// Thrust at this input power, in Newtons
+    pub thrust_N: f64,
+    /// Isp at this input power, in seconds
+    pub isp_s: f64,
+}
+
+/// A throttle table mapping the input power of an engine to its thrust and Isp, linearly interpolated between the points.
+///
+/// The engine is off below the lowest power of the table, and the performance is capped at the highest power.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ThrottleTable {
+    /// Points of the table, sorted by increasing input power
+    points: Vec<ThrottlePoint>,
+}
+
+impl ThrottleTable {
+    /// Initializes a throttle table from the provided points, which are sorted by input power.
+    /// Returns an error if no point is provided or if a point is not finite.
+    pub fn new(mut points: Vec<ThrottlePoint>) -> Result<Self, ConfigError> {
+        if points.is_empty() {
+            return Err(ConfigError::InvalidConfig {
+                msg: "throttle table requires at least one point".to_string(),
+            });
+        }
+        if let Some(point) = points
+            .iter()
+            .find(|p| !(p.power_kW.is_finite() && p.thrust_N.is_finite() && p.isp_s.is_finite()))
+        {
+            return Err(ConfigError::InvalidConfig {
+                msg: format!("throttle table point must be finite, got {point:?}"),
+            });
+        }
+        points.sort_by(|a, b| a.power_kW.total_cmp(&b.power_kW));
+        Ok(Self { points })
+    }
+
+    /// Initializes the throttle table of an engine whose thrust is proportional to its input power and whose Isp is constant,
+    /// where an input power of 1 kW corresponds to the full thrust of the provided thruster.
+    pub fn proportional(thruster: Thruster) -> Self {
+        Self {
+            points: vec![
+                ThrottlePoint {
+                    power_kW: 0.0,
+                    thrust_N: 0.0,
+                    isp_s: thruster.isp_s,
+                },
+                ThrottlePoint {
+                    power_kW: 1.0,
+                    thrust_N: thruster.thrust_N,
+                    isp_s: thruster.isp_s,
+                },
+            ],
+        }
+    }
+
+    /// Returns the maximum input power of this table, in kW.
+    #[allow(non_snake_case)]
+    pub fn max_power_kW(&self) -> f64 {
+        self.points.last().unwrap().power_kW
+    }
+
+    /// Returns the minimum input power of this table, in kW, below which the engine is off.
+    #[allow(non_snake_case)]
+    pub fn min_power_kW(&self) -> f64 {
+        self.points[0].power_kW
+    }
+
+    /// Returns the thrust and Isp at the provided input power in kW, or None if the power is below the lowest power of the table.
+    #[allow(non_snake_case)]
+    pub fn at(&self, power_kW: f64) -> Option<Thruster> {
+        if power_kW < self.min_power_kW() || power_kW <= 0.0 {
+            return None;
+        }
+        let idx = self.points.partition_point(|p| p.power_kW <= power_kW);
+        if idx == self.points.len() {
+            let last = self.points.last().unwrap();
+            return Some(Thruster {
+                thrust_N: last.thrust_N,
+                isp_s: last.isp_s,
+            });
+        }
+        let (lo, hi) = (self.points[idx - 1], self.points[idx]);
+        let t = (power_kW - lo.power_kW) / (hi.power_kW - lo.power_kW);
+        Some(Thruster {
+            thrust_N: lo.thrust_N + t * (hi.thrust_N - lo.thrust_N),
+            isp_s: lo.isp_s + t * (hi.isp_s - lo.isp_s),
+        })
+    }
+}
+
+/// Blowdown model of a propellant tank pressurized by a fixed amount of gas, whose pressure drops as the propellant is consumed.
+///
+/// The ullage gas follows a polytropic expansion: P = P0 * (V0 / V)^n, where V is the volume of the ullage, i.e. the volume of the tank
+/// minus the volume of the propellant. Use a polytropic index of 1.0 for an isothermal expansion.
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Blowdown {
+    /// Pressure of the tank when it holds the initial propellant mass, in kPa
+    pub initial_pressure_kPa: f64,
+    /// Propellant mass at the initial pressure, in kg
+    pub initial_propellant_kg: f64,
+    /// Volume of the tank, in m^3
+    pub volume_m3: f64,
+    /// Density of the propellant, in kg/m^3
+    pub density_kg_m3: f64,
+    /// Polytropic index of the expansion of the pressurant
+    pub polytropic_index: f64,
+}
+
+impl Blowdown {
+    /// Returns the pressure of the tank when it holds the provided propellant mass, in kPa.
+    #[allow(non_snake_case)]
+    pub fn pressure_kPa(&self, propellant_kg: f64) -> f64 {
+        let initial_ullage_m3 = self.volume_m3 - self.initial_propellant_kg / self.density_kg_m3;
+        let ullage_m3 = self.volume_m3 - propellant_kg.max(0.0) / self.density_kg_m3;
+        self.initial_pressure_kPa * (initial_ullage_m3 / ullage_m3).powf(self.polytropic_index)
+    }
+}
+
+/// A propellant tank of the propulsion subsystem.
+///
+/// The propellant mass of each tank is stored in the spacecraft state, in the same order as the tanks of the propulsion subsystem.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tank {
+    pub name: String,
+    /// Blowdown model of the tank, or None if its pressure is regulated
+    pub blowdown: Option<Blowdown>,
+}
+
+impl Tank {
+    /// Initializes a tank whose pressure is regulated.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            blowdown: None,
+        }
+    }
+
+    /// Sets the blowdown model of this tank.
+    pub fn with_blowdown(mut self, blowdown: Blowdown) -> Self {
+        self.blowdown = Some(blowdown);
+        self
+    }
+
+    /// Returns the pressure of the tank when it holds the provided propellant mass in kPa, or None if its pressure is regulated.
+    #[allow(non_snake_case)]
+    pub fn pressure_kPa(&self, propellant_kg: f64) -> Option<f64> {
+        self.blowdown.map(|b| b.pressure_kPa(propellant_kg))
+    }
+}
+
+impl fmt::Display for Tank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.blowdown {
+            Some(b) => write!(
+                f,
+                "{} (blowdown from {:.1} kPa)",
+                self.name, b.initial_pressure_kPa
+            ),
+            None => write!(f, "{} (regulated)", self.name),
+        }
+    }
+}
+
+/// Sensitivity of the performance of an engine to its feed pressure.
+///
+/// The thrust and Isp of the throttle table are those at the reference pressure, and scale with the feed pressure P as
+/// thrust * (P / P_ref)^thrust_exponent and Isp * (P / P_ref)^isp_exponent.
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PressureSensitivity {
+    /// Feed pressure at which the throttle table is defined, in kPa
+    pub reference_pressure_kPa: f64,
+    /// Exponent of the pressure ratio scaling the thrust
+    pub thrust_exponent: f64,
+    /// Exponent of the pressure ratio scaling the Isp
+    pub isp_exponent: f64,
+}
+
+/// An engine of the propulsion subsystem.
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Engine {
+    pub name: String,
+    /// Direction of the thrust applied on the spacecraft when the engine is not canted, as a unit vector in the body frame
+    pub mount_direction: Vector3<f64>,
+    /// Cant angle of the engine, in degrees
+    pub cant_deg: f64,
+    /// Axis of the cant rotation in the body frame, usually perpendicular to the mount direction
+    pub cant_axis: Vector3<f64>,
+    /// Throttle table of this engine
+    pub throttle_table: ThrottleTable,
+    /// Minimum impulse bit of this engine, in N s, only used when the propulsion subsystem is pulse modulated
+    pub min_impulse_bit_N_s: f64,
+    /// Tanks feeding this engine, as the index of each tank and the fraction of the mass flow drawn from it
+    pub feed: Vec<(usize, f64)>,
+    /// Sensitivity of the thrust and Isp to the feed pressure, if any of the feeding tanks blows down
+    pub pressure_sensitivity: Option<PressureSensitivity>,
+}
+
+impl Engine {
+    /// Initializes an engine with the provided throttle table and mount direction in the body frame, without cant nor minimum impulse bit.
+    pub fn new(name: &str, throttle_table: ThrottleTable, mount_direction: Vector3<f64>) -> Self {
+        let mount_direction = mount_direction.normalize();
+        // Pick an arbitrary cant axis perpendicular to the mount direction
+        let helper = if mount_direction[0].abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::y()
+        };
+        Self {
+            name: name.to_string(),
+            mount_direction,
+            cant_deg: 0.0,
+            cant_axis: mount_direction.cross(&helper).normalize(),
+            throttle_table,
+            min_impulse_bit_N_s: 0.0,
+            feed: vec![(0, 1.0)],
+            pressure_sensitivity: None,
+        }
+    }
+
+    /// Initializes an engine of constant Isp whose thrust is proportional to the throttle, like the provided thruster.
+    pub fn from_thruster(name: &str, thruster: Thruster, mount_direction: Vector3<f64>) -> Self {
+        Self::new(name, ThrottleTable::proportional(thruster), mount_direction)
+    }
+
+    /// Cants this engine by the provided angle in degrees about the provided axis of the body frame.
+    pub fn with_cant(mut self, cant_deg: f64, cant_axis: Vector3<f64>) -> Self {
+        self.cant_deg = cant_deg;
+        self.cant_axis = cant_axis.normalize();
+        self
+    }
+
+    /// Sets the minimum impulse bit of this engine, in N s.
+    #[allow(non_snake_case)]
+    pub fn with_min_impulse_bit(mut self, min_impulse_bit_N_s: f64) -> Self {
+        self.min_impulse_bit_N_s = min_impulse_bit_N_s;
+        self
+    }
+
+    /// Feeds this engine from the tank at the provided index of the propulsion subsystem, which must be less than `MAX_TANKS`.
+    pub fn with_tank(mut self, tank: usize) -> Self {
+        self.feed = vec![(tank, 1.0)];
+        self
+    }
+
+    /// Feeds this bipropellant engine from the provided oxidizer and fuel tanks, with the mixture ratio being the ratio of the oxidizer mass flow to the fuel mass flow.
+    /// The tank indexes must be less than `MAX_TANKS`.
+    pub fn with_bipropellant(
+        mut self,
+        oxidizer_tank: usize,
+        fuel_tank: usize,
+        mixture_ratio: f64,
+    ) -> Self {
+        self.feed = vec![
+            (oxidizer_tank, mixture_ratio / (1.0 + mixture_ratio)),
+            (fuel_tank, 1.0 / (1.0 + mixture_ratio)),
+        ];
+        self
+    }
+
+    /// Sets the sensitivity of the thrust and Isp of this engine to its feed pressure.
+    #[allow(non_snake_case)]
+    pub fn with_pressure_sensitivity(
+        mut self,
+        reference_pressure_kPa: f64,
+        thrust_exponent: f64,
+        isp_exponent: f64,
+    ) -> Self {
+        self.pressure_sensitivity = Some(PressureSensitivity {
+            reference_pressure_kPa,
+            thrust_exponent,
+            isp_exponent,
+        });
+        self
+    }
+
+    /// Returns the direction of the thrust applied on the spacecraft, including the cant angle, as a unit vector in the body frame.
+    pub fn thrust_direction(&self) -> Vector3<f64> {
+        Rotation3::from_axis_angle(
+            &Unit::new_normalize(self.cant_axis),
+            self.cant_deg.to_radians(),
+        ) * self.mount_direction
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let full = self.throttle_table.at(self.throttle_table.max_power_kW());
+        write!(
+            f,
+            "{} ({:.3} N @ {:.1} s, canted by {:.1} deg)",
+            self.name,
+            full.map_or(0.0, |t| t.thrust_N),
+            full.map_or(0.0, |t| t.isp_s),
+            self.cant_deg
+        )
+    }
+}
+
+/// Thrust and mass flow rate of an engine of the propulsion subsystem.
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct EngineOutput {
+    /// Thrust, in Newtons
+    pub thrust_N: f64,
+    /// Mass flow rate of propellant, in kg/s
+    pub mass_flow_kg_s: f64,
+}
+
+/// A propulsion subsystem of several engines, used by `SpacecraftDynamics` instead of the single thruster of the spacecraft.
+///
+/// The guidance law sets the inertial direction of the nominal thrust axis of the subsystem, which defines the attitude of the body frame
+/// (with the smallest rotation from the body frame aligned with the integration frame), and allocates a throttle level to each engine.
+/// The throttle level of an engine is the fraction of the maximum input power of its throttle table.
+///
+/// If a duty cycle period is set, the engines are pulse modulated: each engine fires at its full power for a fraction of the period equal to its throttle level,
+/// and a pulse smaller than the minimum impulse bit of the engine is not fired. The thrust is averaged over the period.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Propulsion {
+    pub engines: Vec<Engine>,
+    /// Nominal thrust axis of the subsystem in the body frame, aligned with the direction of the guidance law
+    pub thrust_axis: Vector3<f64>,
+    /// Period of the pulse modulation, if the engines are not throttled continuously
+    pub duty_cycle: Option<Duration>,
+    /// Propellant tanks feeding the engines, in the same order as the tank masses of the spacecraft.
+    /// If there are no tanks, the propellant is only drawn from the fuel mass of the spacecraft.
+    pub tanks: Vec<Tank>,
+}
+
+impl Propulsion {
+    /// Initializes a continuously throttled propulsion subsystem with the provided engines and nominal thrust axis in the body frame.
+    pub fn new(engines: Vec<Engine>, thrust_axis: Vector3<f64>) -> Self {
+        Self {
+            engines,
+            thrust_axis: thrust_axis.normalize(),
+            duty_cycle: None,
+            tanks: Vec::new(),
+        }
+    }
+
+    /// Sets the propellant tanks feeding the engines, whose propellant masses are stored in the same order in the spacecraft state
+    /// (cf. `Spacecraft::with_tank_masses`).
+    /// Returns an error if more than `MAX_TANKS` tanks are provided, or if an engine is fed by a tank which is not provided.
+    pub fn with_tanks(mut self, tanks: Vec<Tank>) -> Result<Self, ConfigError> {
+        if tanks.len() > MAX_TANKS {
+            return Err(ConfigError::InvalidConfig {
+                msg: format!(
+                    "at most {MAX_TANKS} propellant tanks are supported, got {}",
+                    tanks.len()
+                ),
+            });
+        }
+        for engine in &self.engines {
+            for (tank, _) in &engine.feed {
+                if *tank >= tanks.len() {
+                    return Err(ConfigError::InvalidConfig {
+                        msg: format!(
+                            "engine {} is fed by tank {tank} but only {} tanks are defined",
+                            engine.name,
+                            tanks.len()
+                        ),
+                    });
+                }
+            }
+        }
+        self.tanks = tanks;
+        Ok(self)
+    }
+
+    /// Pulse modulates the engines with the provided duty cycle period.
+    pub fn with_duty_cycle(mut self, period: Duration) -> Self {
+        self.duty_cycle = Some(period);
+        self
+    }
+
+    /// Returns the DCM from the body frame to the integration frame when the nominal thrust axis is aligned with the provided inertial direction.
+    pub fn dcm_to_inertial(&self, direction: &Vector3<f64>) -> Matrix3<f64> {
+        let q =
+            UnitQuaternion::rotation_between(&self.thrust_axis, direction).unwrap_or_else(|| {
+                // The direction is opposite to the thrust axis: flip about any perpendicular axis
+                let helper = if self.thrust_axis[0].abs() < 0.9 {
+                    Vector3::x()
+                } else {
+                    Vector3::y()
+                };
+                UnitQuaternion::from_axis_angle(
+                    &Unit::new_normalize(self.thrust_axis.cross(&helper)),
+                    std::f64::consts::PI,
+                )
+            });
+        q.to_rotation_matrix().into_inner()
+    }
+
+    /// Returns the thrust and mass flow rate of each engine for the provided throttle levels, at the reference pressure of the engines.
+    pub fn outputs(&self, throttles: &[f64]) -> Result<Vec<EngineOutput>, GuidanceErrors> {
+        self.engine_outputs(throttles, None)
+    }
+
+    /// Returns the thrust and mass flow rate of each engine for the provided throttle levels, where the performance of the engines
+    /// depends on the pressure of the blowdown tanks holding the provided propellant masses, in kg.
+    pub fn outputs_with_tanks(
+        &self,
+        throttles: &[f64],
+        tank_masses_kg: &[f64],
+    ) -> Result<Vec<EngineOutput>, GuidanceErrors> {
+        self.engine_outputs(throttles, Some(tank_masses_kg))
+    }
+
+    /// Returns the feed pressure of the provided engine in kPa, as the average pressure of its blowdown tanks weighted by the mass flow drawn from each,
+    /// or None if all of its tanks are regulated.
+    #[allow(non_snake_case)]
+    pub fn feed_pressure_kPa(&self, engine: &Engine, tank_masses_kg: &[f64]) -> Option<f64> {
+        let mut pressure_kPa = 0.0;
+        let mut weight = 0.0;
+        for (tank, fraction) in &engine.feed {
+            if let Some(p) = self
+                .tanks
+                .get(*tank)
+                .and_then(|t| t.pressure_kPa(tank_masses_kg.get(*tank).copied().unwrap_or(0.0)))
+            {
+                pressure_kPa += fraction * p;
+                weight += fraction;
+            }
+        }
+        if weight > 0.0 {
+            Some(pressure_kPa / weight)
+        } else {
+            None
+        }
+    }
+
+    fn engine_outputs(
+        &self,
+        throttles: &[f64],
+        tank_masses_kg: Option<&[f64]>,
+    ) -> Result<Vec<EngineOutput>, GuidanceErrors> {
+        if throttles.len() != self.engines.len() {
+            return Err(GuidanceErrors::AllocationSize {
+                expected: self.engines.len(),
+                got: throttles.len(),
+            });
+        }
+
+        let mut outputs = Vec::with_capacity(self.engines.len());
+        for (engine_no, (engine, throttle)) in self.engines.iter().zip(throttles).enumerate() {
+            if let Some((tank, _)) = engine.feed.iter().find(|(tank, _)| *tank >= MAX_TANKS) {
+                return Err(GuidanceErrors::FeedTank {
+                    engine: engine_no,
+                    tank: *tank,
+                    max: MAX_TANKS,
+                });
+            }
+
+            let throttle = *throttle;
+            if !(0.0..=1.0).contains(&throttle) {
+                return Err(GuidanceErrors::ThrottleRatio { ratio: throttle });
+            }
+
+            // Scale the performance of the engine by its feed pressure
+            let (thrust_scale, isp_scale) = match (engine.pressure_sensitivity, tank_masses_kg) {
+                (Some(sensitivity), Some(tank_masses_kg)) => {
+                    match self.feed_pressure_kPa(engine, tank_masses_kg) {
+                        Some(pressure_kPa) => {
+                            let ratio = pressure_kPa / sensitivity.reference_pressure_kPa;
+                            (
+                                ratio.powf(sensitivity.thrust_exponent),
+                                ratio.powf(sensitivity.isp_exponent),
+                            )
+                        }
+                        None => (1.0, 1.0),
+                    }
+                }
+                _ => (1.0, 1.0),
+            };
+
+            let max_power_kw = engine.throttle_table.max_power_kW();
+            let output = match self.duty_cycle {
+                Some(period) => match engine.throttle_table.at(max_power_kw) {
+                    Some(full)
+                        if throttle * period.to_seconds() * full.thrust_N
+                            >= engine.min_impulse_bit_N_s =>
+                    {
+                        let thrust_N = throttle * full.thrust_N * thrust_scale;
+                        EngineOutput {
+                            thrust_N,
+                            mass_flow_kg_s: thrust_N / (full.isp_s * isp_scale * STD_GRAVITY),
+                        }
+                    }
+                    _ => EngineOutput::default(),
+                },
+                None => match engine.throttle_table.at(throttle * max_power_kw) {
+                    Some(perf) => {
+                        let thrust_N = perf.thrust_N * thrust_scale;
+                        EngineOutput {
+                            thrust_N,
+                            mass_flow_kg_s: thrust_N / (perf.isp_s * isp_scale * STD_GRAVITY),
+                        }
+                    }
+                    None => EngineOutput::default(),
+                },
+            };
+            outputs.push(output);
+        }
+        Ok(outputs)
+    }
+
+    /// Returns the total thrust in the body frame, in Newtons, and the total mass flow rate in kg/s, for the provided throttle levels.
+    pub fn thrust_body(&self, throttles: &[f64]) -> Result<(Vector3<f64>, f64), GuidanceErrors> {
+        Ok(self.net_thrust(&self.outputs(throttles)?))
+    }
+
+    /// Returns the total thrust in the body frame, in Newtons, and the total mass flow rate in kg/s, of the provided engine outputs.
+    pub fn net_thrust(&self, outputs: &[EngineOutput]) -> (Vector3<f64>, f64) {
+        let mut thrust = Vector3::zeros();
+        let mut mass_flow_kg_s = 0.0;
+        for (engine, output) in self.engines.iter().zip(outputs) {
+            thrust += engine.thrust_direction() * output.thrust_N;
+            mass_flow_kg_s += output.mass_flow_kg_s;
+        }
+        (thrust, mass_flow_kg_s)
+    }
+
+    /// Returns the mass flow rate drawn from each tank in kg/s, split across the tanks feeding each engine, so that the flow rates
+    /// sum to the total mass flow rate. Without tanks in the propulsion subsystem, the engines are fed by the first tank by default,
+    /// like the single thruster: the flows of the tanks which the spacecraft does not hold are ignored.
+    /// Feeds from a tank index of `MAX_TANKS` or more (rejected when computing the outputs) are ignored.
+    pub fn tank_flows(&self, outputs: &[EngineOutput]) -> [f64; MAX_TANKS] {
+        let mut flows = [0.0; MAX_TANKS];
+        for (engine, output) in self.engines.iter().zip(outputs) {
+            for (tank, fraction) in &engine.feed {
+                if let Some(flow) = flows.get_mut(*tank) {
+                    *flow += fraction * output.mass_flow_kg_s;
+                }
+            }
+        }
+        flows
+    }
+}
+
+impl fmt::Display for Propulsion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let engines: Vec<String> = self.engines.iter().map(|e| format!("{e}")).collect();
+        write!(f, "Propulsion [{}]", engines.join(", "))?;
+        if !self.tanks.is_empty() {
+            let tanks: Vec<String> = self.tanks.iter().map(|t| format!("{t}")).collect();
+            write!(f, " fed by [{}]", tanks.join(", "))?;
+        }
+        if let Some(period) = self.duty_cycle {
+            write!(f, " pulsed every {period}")?;
+        }
+        Ok(())
+    }
+}
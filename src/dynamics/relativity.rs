@@ -0,0 +1,208 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Cosm, Frame, LightTimeCalc, Orbit, SPEED_OF_LIGHT_KMS};
+use crate::dynamics::AccelModel;
+use crate::linalg::{Const, Matrix3, Vector3};
+use hyperdual::linalg::norm;
+use hyperdual::{extract_jacobian_and_result, hyperspace_from_vector, Float, OHyperdual};
+use std::fmt;
+use std::sync::Arc;
+
+use super::DynamicsError;
+
+/// Angular momentum per unit mass of the Earth, in km^2/s (IERS Conventions 2010, section 10.3)
+pub const EARTH_ANGULAR_MOMENTUM_KM2_S: f64 = 9.8e2;
+
+/// Post-Newtonian relativistic correction to the acceleration around the central body of the integration frame.
+///
+/// Implements equation 10.12 of the IERS Conventions (2010) in the parametrized post-Newtonian framework with β = γ = 1:
+/// the Schwarzschild term is always included, and the Lense-Thirring (frame dragging) and de Sitter (geodesic precession) terms are optional.
+/// The partials of the acceleration with respect to the velocity are neglected in `dual_eom`.
+#[derive(Clone)]
+pub struct Relativity {
+    cosm: Arc<Cosm>,
+    /// Body fixed frame of the central body, whose Z axis is the spin axis, and its angular momentum per unit mass in km^2/s
+    lense_thirring: Option<(Frame, f64)>,
+    /// Frame of the Sun, set to include the de Sitter term due to the motion of the central body around the Sun
+    de_sitter: Option<Frame>,
+}
+
+impl Relativity {
+    /// Initializes the Schwarzschild correction only, which is the dominant relativistic effect for Earth orbiters.
+    pub fn new(cosm: Arc<Cosm>) -> Self {
+        Self {
+            cosm,
+            lense_thirring: None,
+            de_sitter: None,
+        }
+    }
+
+    /// Initializes the Schwarzschild correction only.
+    pub fn schwarzschild(cosm: Arc<Cosm>) -> Arc<Self> {
+        Arc::new(Self::new(cosm))
+    }
+
+    /// Initializes the full IERS correction for Earth orbiters: Schwarzschild, Lense-Thirring and de Sitter terms.
+    pub fn iers_earth(cosm: Arc<Cosm>) -> Arc<Self> {
+        let iau_earth = cosm.frame("IAU Earth");
+        Arc::new(
+            Self::new(cosm)
+                .with_lense_thirring(iau_earth, EARTH_ANGULAR_MOMENTUM_KM2_S)
+                .with_de_sitter(),
+        )
+    }
+
+    /// Includes the Lense-Thirring term, where the spin axis of the central body is the Z axis of the provided body fixed frame
+    /// and `angular_momentum_km2_s` is the angular momentum per unit mass of the central body.
+    pub fn with_lense_thirring(
+        mut self,
+        body_fixed_frame: Frame,
+        angular_momentum_km2_s: f64,
+    ) -> Self {
+        self.lense_thirring = Some((body_fixed_frame, angular_momentum_km2_s));
+        self
+    }
+
+    /// Includes the de Sitter term due to the motion of the central body around the Sun.
+    pub fn with_de_sitter(mut self) -> Self {
+        self.de_sitter = Some(self.cosm.frame("Sun J2000"));
+        self
+    }
+
+    /// Returns the angular momentum per unit mass vector of the central body in the integration frame, if the Lense-Thirring term is enabled.
+    fn angular_momentum(&self, osc: &Orbit) -> Result<Option<Vector3<f64>>, DynamicsError> {
+        match self.lense_thirring {
+            Some((body_fixed_frame, j_km2_s)) => {
+                let dcm = self
+                    .cosm
+                    .try_position_dcm_from_to(&body_fixed_frame, &osc.frame, osc.epoch)
+                    .map_err(|e| DynamicsError::FrameRotation { msg: e.to_string() })?;
+                Ok(Some(dcm * Vector3::new(0.0, 0.0, j_km2_s)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the de Sitter acceleration, which does not depend on the position of the spacecraft.
+    fn de_sitter_accel(&self, osc: &Orbit) -> Result<Vector3<f64>, DynamicsError> {
+        let sun_frame = match self.de_sitter {
+            Some(sun_frame) if osc.frame.ephem_path() != sun_frame.ephem_path() => sun_frame,
+            _ => return Ok(Vector3::zeros()),
+        };
+        // State of the central body with respect to the Sun
+        let sun_state = self
+            .cosm
+            .try_celestial_state(
+                &sun_frame.ephem_path(),
+                osc.epoch,
+                osc.frame,
+                LightTimeCalc::None,
+            )
+            .map_err(|e| DynamicsError::FrameRotation { msg: e.to_string() })?;
+        let r_body = -sun_state.radius();
+        let v_body = -sun_state.velocity();
+        let gm_sun = sun_frame.gm();
+        let omega = v_body
+            .cross(&(-gm_sun / (SPEED_OF_LIGHT_KMS.powi(2) * r_body.norm().powi(3)) * r_body));
+        Ok(3.0 * omega.cross(&osc.velocity()))
+    }
+}
+
+impl fmt::Display for Relativity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut terms = vec!["Schwarzschild".to_string()];
+        if let Some((frame, _)) = self.lense_thirring {
+            terms.push(format!("Lense-Thirring ({frame})"));
+        }
+        if self.de_sitter.is_some() {
+            terms.push("de Sitter".to_string());
+        }
+        write!(f, "Relativity ({})", terms.join(", "))
+    }
+}
+
+impl AccelModel for Relativity {
+    fn eom(&self, osc: &Orbit) -> Result<Vector3<f64>, DynamicsError> {
+        let gm = osc.frame.gm();
+        let c2 = SPEED_OF_LIGHT_KMS.powi(2);
+        let r = osc.radius();
+        let v = osc.velocity();
+        let rmag = osc.rmag_km();
+        let factor = gm / (c2 * rmag.powi(3));
+
+        let mut accel = factor * ((4.0 * gm / rmag - v.norm_squared()) * r + 4.0 * r.dot(&v) * v);
+
+        if let Some(j) = self.angular_momentum(osc)? {
+            accel += 2.0 * factor * (3.0 / rmag.powi(2) * r.cross(&v) * r.dot(&j) + v.cross(&j));
+        }
+
+        Ok(accel + self.de_sitter_accel(osc)?)
+    }
+
+    fn dual_eom(&self, osc: &Orbit) -> Result<(Vector3<f64>, Matrix3<f64>), DynamicsError> {
+        // Build the hyperdual space of the radius vector, the velocity is a constant
+        let r: Vector3<OHyperdual<f64, Const<7>>> = hyperspace_from_vector(&osc.radius());
+        let v_real = osc.velocity();
+        let v = v_real.map(OHyperdual::<f64, Const<7>>::from_real);
+
+        let gm = OHyperdual::<f64, Const<7>>::from_real(osc.frame.gm());
+        let c2 = OHyperdual::<f64, Const<7>>::from_real(SPEED_OF_LIGHT_KMS.powi(2));
+        let rmag = norm(&r);
+        let factor = gm / (c2 * rmag.powi(3));
+
+        let r_dot_v = dot(&r, &v);
+        let v2 = OHyperdual::<f64, Const<7>>::from_real(v_real.norm_squared());
+        let four = OHyperdual::<f64, Const<7>>::from_real(4.0);
+
+        let mut accel = (r * (four * gm / rmag - v2) + v * (four * r_dot_v)) * factor;
+
+        if let Some(j) = self.angular_momentum(osc)? {
+            let j = j.map(OHyperdual::<f64, Const<7>>::from_real);
+            let three = OHyperdual::<f64, Const<7>>::from_real(3.0);
+            let two = OHyperdual::<f64, Const<7>>::from_real(2.0);
+            accel += (cross(&r, &v) * (three * dot(&r, &j) / rmag.powi(2)) + cross(&v, &j))
+                * (two * factor);
+        }
+
+        let (fx, grad) = extract_jacobian_and_result::<_, 3, 3, 7>(&accel);
+
+        // The de Sitter term does not depend on the position of the spacecraft, so it does not contribute to the gradient.
+        Ok((fx + self.de_sitter_accel(osc)?, grad))
+    }
+}
+
+/// Dot product of two hyperdual vectors
+pub(crate) fn dot(
+    a: &Vector3<OHyperdual<f64, Const<7>>>,
+    b: &Vector3<OHyperdual<f64, Const<7>>>,
+) -> OHyperdual<f64, Const<7>> {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Cross product of two hyperdual vectors
+pub(crate) fn cross(
+    a: &Vector3<OHyperdual<f64, Const<7>>>,
+    b: &Vector3<OHyperdual<f64, Const<7>>>,
+) -> Vector3<OHyperdual<f64, Const<7>>> {
+    Vector3::new(
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    )
+}
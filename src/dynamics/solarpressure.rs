@@ -16,9 +16,10 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+use super::plates::PlateModel;
 use super::{DynamicsError, ForceModel};
 use crate::cosmic::eclipse::EclipseLocator;
-use crate::cosmic::{Cosm, Frame, Spacecraft, AU, SPEED_OF_LIGHT};
+use crate::cosmic::{Cosm, Frame, Spacecraft, AU, CR_IDX, SPEED_OF_LIGHT};
 use crate::linalg::{Const, Matrix3, Vector3};
 use hyperdual::{hyperspace_from_vector, linalg::norm, Float, OHyperdual};
 use std::fmt;
@@ -30,6 +31,8 @@ pub struct SolarPressure {
     /// solar flux at 1 AU, in W/m^2
     pub phi: f64,
     pub e_loc: EclipseLocator,
+    /// Optional plate model of the spacecraft, which replaces the SRP area and coefficient of reflectivity of the spacecraft
+    pub plates: Option<Arc<PlateModel>>,
 }
 
 impl SolarPressure {
@@ -40,7 +43,11 @@ impl SolarPressure {
             shadow_bodies,
             cosm,
         };
-        Self { phi: 1367.0, e_loc }
+        Self {
+            phi: 1367.0,
+            e_loc,
+            plates: None,
+        }
     }
 
     /// Accounts for the shadowing of only one body and will set the solar flux at 1 AU to: Phi = 1367.0
@@ -54,6 +61,13 @@ impl SolarPressure {
         me.phi = flux_w_m2;
         Arc::new(me)
     }
+
+    /// Returns a copy of this model which computes the SRP force from the provided plate model instead of the spacecraft SRP configuration.
+    pub fn with_plates(&self, plates: PlateModel) -> Arc<Self> {
+        let mut me = self.clone();
+        me.plates = Some(Arc::new(plates));
+        Arc::new(me)
+    }
 }
 
 impl ForceModel for SolarPressure {
@@ -75,6 +89,11 @@ impl ForceModel for SolarPressure {
         // in N/(m^2)
         let flux_pressure = (k * self.phi / SPEED_OF_LIGHT) * (1.0 / r_sun_au).powi(2);
 
+        if let Some(plates) = &self.plates {
+            // Note the 1e-3 is to convert the SRP from m/s^2 to km/s^2
+            return Ok(1e-3 * plates.srp_force(osc, &-r_sun_unit, flux_pressure));
+        }
+
         // Note the 1e-3 is to convert the SRP from m/s^2 to km/s^2
         Ok(1e-3 * ctx.srp.cr * ctx.srp.area_m2 * flux_pressure * r_sun_unit)
     }
@@ -102,20 +121,48 @@ impl ForceModel for SolarPressure {
         let flux_pressure =
             OHyperdual::<f64, Const<9>>::from_real(k * self.phi / SPEED_OF_LIGHT) * inv_r_sun_au_p2;
 
-        // Note the 1e-3 is to convert the SRP from m/s^2 to km/s^2
-        let dual_force_scalar =
-            OHyperdual::<f64, Const<9>>::from_real(1e-3 * ctx.srp.cr * ctx.srp.area_m2);
         let mut dual_force: Vector3<OHyperdual<f64, Const<9>>> = Vector3::zeros();
-        dual_force[0] = dual_force_scalar * flux_pressure * r_sun_unit[0];
-        dual_force[1] = dual_force_scalar * flux_pressure * r_sun_unit[1];
-        dual_force[2] = dual_force_scalar * flux_pressure * r_sun_unit[2];
+        if let Some(plates) = &self.plates {
+            // NOTE: The orientation of the plates is held fixed: the partials only account for the direction and distance of the Sun.
+            let sun_unit = -r_sun / r_sun.norm();
+            for (plate, normal) in plates
+                .plates
+                .iter()
+                .zip(plates.inertial_normals(osc, &sun_unit))
+            {
+                let (normal, _) = plate.facing(normal, &sun_unit);
+                // The Sun direction is opposite to the unit vector from the Sun to the spacecraft
+                let cos_theta = -(r_sun_unit[0] * normal[0]
+                    + r_sun_unit[1] * normal[1]
+                    + r_sun_unit[2] * normal[2]);
+                if cos_theta.real() > 0.0 {
+                    // Note the 1e-3 is to convert the SRP from m/s^2 to km/s^2
+                    let scalar = OHyperdual::<f64, Const<9>>::from_real(1e-3 * plate.area_m2)
+                        * flux_pressure
+                        * cos_theta;
+                    let normal_scalar = OHyperdual::<f64, Const<9>>::from_real(2.0)
+                        * (cos_theta * plate.specular
+                            + OHyperdual::<f64, Const<9>>::from_real(plate.diffuse / 3.0));
+                    for i in 0..3 {
+                        dual_force[i] += scalar
+                            * (r_sun_unit[i] * (1.0 - plate.specular) - normal_scalar * normal[i]);
+                    }
+                }
+            }
+        } else {
+            // Note the 1e-3 is to convert the SRP from m/s^2 to km/s^2
+            let dual_force_scalar =
+                OHyperdual::<f64, Const<9>>::from_real(1e-3 * ctx.srp.cr * ctx.srp.area_m2);
+            dual_force[0] = dual_force_scalar * flux_pressure * r_sun_unit[0];
+            dual_force[1] = dual_force_scalar * flux_pressure * r_sun_unit[1];
+            dual_force[2] = dual_force_scalar * flux_pressure * r_sun_unit[2];
+        }
 
         // Extract result into Vector6 and Matrix6
         let mut dx = Vector3::zeros();
         let mut grad = Matrix3::zeros();
         for i in 0..3 {
             dx[i] += dual_force[i].real();
-            // NOTE: Although the hyperdual state is of size 7, we're only setting the values up to 3 (Matrix3)
             for j in 0..3 {
                 grad[(i, j)] += dual_force[i][j + 1];
             }
@@ -123,6 +170,14 @@ impl ForceModel for SolarPressure {
 
         Ok((dx, grad))
     }
+
+    /// The SRP force is proportional to the coefficient of reflectivity, unless a plate model is used
+    fn estimation_index(&self) -> Option<usize> {
+        match self.plates {
+            Some(_) => None,
+            None => Some(CR_IDX),
+        }
+    }
 }
 
 impl fmt::Display for SolarPressure {
@@ -131,6 +186,10 @@ impl fmt::Display for SolarPressure {
             f,
             "SRP with φ = {} W/m^2 and eclipse {}",
             self.phi, self.e_loc
-        )
+        )?;
+        if let Some(plates) = &self.plates {
+            write!(f, " on {plates}")?;
+        }
+        Ok(())
     }
 }
@@ -18,8 +18,10 @@
 
 use super::guidance::{ra_dec_from_unit_vector, GuidanceErrors, GuidanceLaw};
 use super::orbital::OrbitalDynamics;
+use super::propulsion::Propulsion;
 use super::{AccelModel, Dynamics, ForceModel};
 pub use crate::cosmic::{GuidanceMode, Spacecraft, STD_GRAVITY};
+use crate::cosmic::{CD_IDX, CR_IDX, MAX_TANKS, TANKS_IDX};
 use crate::dynamics::DynamicsError;
 use crate::errors::NyxError;
 use crate::io::dynamics::DynamicsSerde;
@@ -63,6 +65,8 @@ pub struct SpacecraftDynamics {
     pub force_models: Vec<Arc<dyn ForceModel>>,
     pub guid_law: Option<Arc<dyn GuidanceLaw>>,
     pub decrement_mass: bool,
+    /// Propulsion subsystem used by the guidance law, instead of the thruster of the spacecraft, if set
+    pub propulsion: Option<Arc<Propulsion>>,
 }
 
 impl SpacecraftDynamics {
@@ -74,6 +78,7 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: Vec::new(),
             decrement_mass: true,
+            propulsion: None,
         }
     }
 
@@ -88,6 +93,7 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: Vec::new(),
             decrement_mass: false,
+            propulsion: None,
         }
     }
 
@@ -98,6 +104,7 @@ impl SpacecraftDynamics {
             guid_law: None,
             force_models: Vec::new(),
             decrement_mass: true,
+            propulsion: None,
         }
     }
 
@@ -108,6 +115,7 @@ impl SpacecraftDynamics {
             guid_law: None,
             force_models: vec![force_model],
             decrement_mass: true,
+            propulsion: None,
         }
     }
 
@@ -148,6 +156,7 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: self.force_models.clone(),
             decrement_mass: self.decrement_mass,
+            propulsion: self.propulsion.clone(),
         }
     }
 
@@ -158,9 +167,17 @@ impl SpacecraftDynamics {
             guid_law: Some(guid_law),
             force_models: self.force_models.clone(),
             decrement_mass: false,
+            propulsion: self.propulsion.clone(),
         }
     }
 
+    /// Clone these spacecraft dynamics and use the provided propulsion subsystem instead of the thruster of the spacecraft.
+    pub fn with_propulsion(&self, propulsion: Propulsion) -> Self {
+        let mut me = self.clone();
+        me.propulsion = Some(Arc::new(propulsion));
+        me
+    }
+
     /// Clone these spacecraft dynamics and remove any control model
     pub fn without_guidance_law(&self) -> Self {
         Self {
@@ -168,6 +185,53 @@ impl SpacecraftDynamics {
             guid_law: None,
             force_models: self.force_models.clone(),
             decrement_mass: self.decrement_mass,
+            propulsion: self.propulsion.clone(),
+        }
+    }
+
+    /// Returns the thrust force in kN, the fuel rate in kg/s and the rate of each tank in kg/s of the propulsion subsystem,
+    /// with the throttle levels allocated by the guidance law.
+    fn propulsion_control(
+        &self,
+        guid_law: &dyn GuidanceLaw,
+        propulsion: &Propulsion,
+        osc_sc: &Spacecraft,
+    ) -> Result<(Vector3<f64>, f64, [f64; MAX_TANKS]), DynamicsError> {
+        let throttles = guid_law.allocate(osc_sc, propulsion);
+        if throttles.iter().all(|throttle| *throttle == 0.0) {
+            // Coasting, the direction may not be defined
+            return Ok((Vector3::zeros(), 0.0, [0.0; MAX_TANKS]));
+        }
+
+        let thrust_inertial = guid_law.direction(osc_sc);
+        if (thrust_inertial.norm() - 1.0).abs() > NORM_ERR {
+            let (alpha, delta) = ra_dec_from_unit_vector(thrust_inertial);
+            return Err(DynamicsError::DynamicsGuidance {
+                source: GuidanceErrors::InvalidDirection {
+                    x: thrust_inertial[0],
+                    y: thrust_inertial[1],
+                    z: thrust_inertial[2],
+                    in_plane_deg: alpha.to_degrees(),
+                    out_of_plane_deg: delta.to_degrees(),
+                },
+            });
+        }
+
+        let outputs = propulsion
+            .outputs_with_tanks(&throttles, &osc_sc.tank_masses())
+            .map_err(|source| DynamicsError::DynamicsGuidance { source })?;
+        let (thrust_body, mass_flow_kg_s) = propulsion.net_thrust(&outputs);
+
+        // Convert the thrust from N to kN
+        let thrust_force = propulsion.dcm_to_inertial(&thrust_inertial) * thrust_body * 1e-3;
+        if self.decrement_mass {
+            Ok((
+                thrust_force,
+                -mass_flow_kg_s,
+                propulsion.tank_flows(&outputs).map(|flow| -flow),
+            ))
+        } else {
+            Ok((thrust_force, 0.0, [0.0; MAX_TANKS]))
         }
     }
 }
@@ -259,7 +323,11 @@ impl fmt::Display for SpacecraftDynamics {
             self.guid_law.is_some(),
             force_models,
             self.orbital_dyn
-        )
+        )?;
+        if let Some(propulsion) = &self.propulsion {
+            write!(f, " {propulsion}")?;
+        }
+        Ok(())
     }
 }
 
@@ -268,7 +336,12 @@ impl Dynamics for SpacecraftDynamics {
     type StateType = Spacecraft;
 
     fn finally(&self, next_state: Self::StateType) -> Result<Self::StateType, DynamicsError> {
-        if next_state.fuel_mass_kg < 0.0 {
+        if next_state.fuel_mass_kg < 0.0
+            || next_state
+                .tank_masses_kg
+                .iter()
+                .any(|mass_kg| mass_kg.map_or(false, |mass_kg| mass_kg < 0.0))
+        {
             error!("negative fuel mass at {}", next_state.epoch());
             return Err(DynamicsError::FuelExhausted {
                 sc: Box::new(next_state),
@@ -285,17 +358,37 @@ impl Dynamics for SpacecraftDynamics {
         }
     }
 
+    /// The two body specific mechanical energy of the orbit of the spacecraft, cf. `OrbitalDynamics`.
+    fn energy(&self, state: &Self::StateType) -> Option<f64> {
+        self.orbital_dyn.energy(&state.orbit)
+    }
+
+    /// The Sundman transformation of the orbital dynamics, if any.
+    fn time_transformation(
+        &self,
+        state_vec: &OVector<f64, Const<94>>,
+        _ctx: &Self::StateType,
+    ) -> Option<f64> {
+        self.orbital_dyn
+            .sundman
+            .map(|sundman| sundman.dt_ds(state_vec.fixed_rows::<3>(0).norm()))
+    }
+
     fn eom(
         &self,
         delta_t: f64,
-        state: &OVector<f64, Const<90>>,
+        state: &OVector<f64, Const<94>>,
         ctx: &Self::StateType,
-    ) -> Result<OVector<f64, Const<90>>, DynamicsError> {
+    ) -> Result<OVector<f64, Const<94>>, DynamicsError> {
         // Rebuild the osculating state for the EOM context.
         let osc_sc = ctx.set_with_delta_seconds(delta_t, state);
-        let mut d_x = OVector::<f64, Const<90>>::zeros();
+        let mut d_x = OVector::<f64, Const<94>>::zeros();
 
         if ctx.orbit.stm.is_some() {
+            if self.orbital_dyn.sundman.is_some() {
+                // The STM would be with respect to the fictitious time
+                return Err(DynamicsError::StateTransitionMatrixUnsupported);
+            }
             // Call the gradient (also called the dual EOM function of the force models)
             let (state, grad) = self.dual_eom(delta_t, &osc_sc)?;
 
@@ -334,7 +427,9 @@ impl Dynamics for SpacecraftDynamics {
 
         // Now include the control as needed.
         if let Some(guid_law) = &self.guid_law {
-            let (thrust_force, fuel_rate) = {
+            let (thrust_force, fuel_rate, tank_rates) = if let Some(propulsion) = &self.propulsion {
+                self.propulsion_control(guid_law.as_ref(), propulsion, &osc_sc)?
+            } else {
                 if osc_sc.thruster.is_none() {
                     return Err(DynamicsError::DynamicsGuidance {
                         source: GuidanceErrors::NoThrustersDefined,
@@ -365,25 +460,26 @@ impl Dynamics for SpacecraftDynamics {
                     } else if thrust_inertial.norm().is_normal() {
                         // Compute the thrust in Newtons and Isp
                         let total_thrust = (thrust_throttle_lvl * thruster.thrust_N) * 1e-3; // Convert m/s^-2 to km/s^-2
-                        (
-                            thrust_inertial * total_thrust,
-                            if self.decrement_mass {
-                                let fuel_usage = thrust_throttle_lvl * thruster.thrust_N
-                                    / (thruster.isp_s * STD_GRAVITY);
-                                -fuel_usage
-                            } else {
-                                0.0
-                            },
-                        )
+                        let fuel_rate = if self.decrement_mass {
+                            let fuel_usage = thrust_throttle_lvl * thruster.thrust_N
+                                / (thruster.isp_s * STD_GRAVITY);
+                            -fuel_usage
+                        } else {
+                            0.0
+                        };
+                        // The single thruster draws from the first tank, if the spacecraft has tanks
+                        let mut tank_rates = [0.0; MAX_TANKS];
+                        tank_rates[0] = fuel_rate;
+                        (thrust_inertial * total_thrust, fuel_rate, tank_rates)
                     } else {
                         warn!(
                             "Abnormal thrust direction vector\t|u| = {}",
                             thrust_inertial.norm()
                         );
-                        (Vector3::zeros(), 0.0)
+                        (Vector3::zeros(), 0.0, [0.0; MAX_TANKS])
                     }
                 } else {
-                    (Vector3::zeros(), 0.0)
+                    (Vector3::zeros(), 0.0, [0.0; MAX_TANKS])
                 }
             };
 
@@ -391,6 +487,9 @@ impl Dynamics for SpacecraftDynamics {
                 d_x[i + 3] += thrust_force[i] / osc_sc.mass_kg();
             }
             d_x[8] += fuel_rate;
+            for (i, rate) in tank_rates.iter().enumerate() {
+                d_x[i + TANKS_IDX] += rate;
+            }
         }
         Ok(d_x)
     }
@@ -427,13 +526,27 @@ impl Dynamics for SpacecraftDynamics {
         // Call the EOMs
         let total_mass = ctx.mass_kg();
         for model in &self.force_models {
-            let (model_frc, model_grad) = model.dual_eom(ctx)?;
+            let (model_frc, model_grad) = model.dual_eom_pos_vel(ctx)?;
             for i in 0..3 {
                 // Add the velocity changes
                 d_x[i + 3] += model_frc[i] / total_mass;
-                // Add the velocity partials
-                for j in 1..4 {
-                    grad[(i + 3, j - 1)] += model_grad[(i, j - 1)] / total_mass;
+                // Add the position and velocity partials
+                for j in 0..6 {
+                    grad[(i + 3, j)] += model_grad[(i, j)] / total_mass;
+                }
+            }
+
+            if let Some(idx) = model.estimation_index() {
+                // The force is proportional to this coefficient, so its partial is the force divided by the coefficient
+                let coeff = match idx {
+                    CR_IDX => ctx.srp.cr,
+                    CD_IDX => ctx.drag.cd,
+                    _ => return Err(DynamicsError::UnsupportedEstimationIndex { idx }),
+                };
+                if coeff.abs() > f64::EPSILON {
+                    for i in 0..3 {
+                        grad[(i + 3, idx)] += model_frc[i] / (coeff * total_mass);
+                    }
                 }
             }
         }
@@ -17,6 +17,7 @@
 */
 
 use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::dynamics::tides::Tides;
 use crate::dynamics::AccelModel;
 use crate::io::gravity::HarmonicsMem;
 use crate::linalg::{DMatrix, Matrix3, Vector3, U7};
@@ -33,6 +34,10 @@ pub struct Harmonics {
     cosm: Arc<Cosm>,
     compute_frame: Frame,
     stor: HarmonicsMem,
+    tides: Option<Tides>,
+    /// Degree (plus one) and order up to which the field is computed, including the tides
+    max_degree: usize,
+    max_order: usize,
     a_nm: DMatrix<f64>,
     b_nm: DMatrix<f64>,
     c_nm: DMatrix<f64>,
@@ -48,11 +53,40 @@ pub struct Harmonics {
 impl Harmonics {
     /// Create a new Harmonics dynamical model from the provided gravity potential storage instance.
     pub fn from_stor(compute_frame: Frame, stor: HarmonicsMem, cosm: Arc<Cosm>) -> Arc<Self> {
+        Self::build(compute_frame, stor, None, cosm)
+    }
+
+    /// Create a new Harmonics dynamical model from the provided gravity potential storage instance,
+    /// whose coefficients are corrected at each epoch for the provided tides.
+    ///
+    /// The tidal corrections are applied up to their own degree and order, even if the static field has a lower degree or order.
+    pub fn from_stor_with_tides(
+        compute_frame: Frame,
+        stor: HarmonicsMem,
+        tides: Tides,
+        cosm: Arc<Cosm>,
+    ) -> Arc<Self> {
+        Self::build(compute_frame, stor, Some(tides), cosm)
+    }
+
+    fn build(
+        compute_frame: Frame,
+        stor: HarmonicsMem,
+        tides: Option<Tides>,
+        cosm: Arc<Cosm>,
+    ) -> Arc<Self> {
         assert!(
             compute_frame.is_geoid(),
             "harmonics only work around geoids"
         );
-        let degree_np2 = stor.max_degree_n() + 2;
+        let (max_degree, max_order) = match &tides {
+            Some(tides) => (
+                stor.max_degree_n().max(tides.max_degree() + 1),
+                stor.max_order_m().max(tides.max_degree()),
+            ),
+            None => (stor.max_degree_n(), stor.max_order_m()),
+        };
+        let degree_np2 = max_degree + 2;
         let mut a_nm = DMatrix::from_element(degree_np2 + 1, degree_np2 + 1, 0.0);
         let mut b_nm = DMatrix::from_element(degree_np2, degree_np2, 0.0);
         let mut c_nm = DMatrix::from_element(degree_np2, degree_np2, 0.0);
@@ -122,6 +156,9 @@ impl Harmonics {
             cosm,
             compute_frame,
             stor,
+            tides,
+            max_degree,
+            max_order,
             a_nm,
             b_nm,
             c_nm,
@@ -136,6 +173,40 @@ impl Harmonics {
     }
 }
 
+impl Harmonics {
+    /// Returns the body fixed frame in which this gravity field is computed
+    pub fn compute_frame(&self) -> Frame {
+        self.compute_frame
+    }
+
+    /// Returns the tides applied to this gravity field, if any
+    pub fn tides(&self) -> Option<&Tides> {
+        self.tides.as_ref()
+    }
+
+    /// Returns the C_nm and S_nm of the static field, corrected by the tidal corrections if provided.
+    fn cs_nm(
+        &self,
+        n: usize,
+        m: usize,
+        delta_cs: Option<&(DMatrix<f64>, DMatrix<f64>)>,
+    ) -> (f64, f64) {
+        let (mut c_val, mut s_val) = if n < self.stor.max_degree_n() && m <= self.stor.max_order_m()
+        {
+            self.stor.cs_nm(n, m)
+        } else {
+            (0.0, 0.0)
+        };
+        if let Some((delta_c, delta_s)) = delta_cs {
+            if n < delta_c.nrows() && m < delta_c.ncols() {
+                c_val += delta_c[(n, m)];
+                s_val += delta_s[(n, m)];
+            }
+        }
+        (c_val, s_val)
+    }
+}
+
 impl fmt::Display for Harmonics {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -144,7 +215,11 @@ impl fmt::Display for Harmonics {
             self.compute_frame,
             self.stor.max_order_m(),
             self.stor.max_degree_n(),
-        )
+        )?;
+        if let Some(tides) = &self.tides {
+            write!(f, " with {tides}")?;
+        }
+        Ok(())
     }
 }
 
@@ -158,8 +233,15 @@ impl AccelModel for Harmonics {
         let s_ = state.x_km / r_;
         let t_ = state.y_km / r_;
         let u_ = state.z_km / r_;
-        let max_degree = self.stor.max_degree_n(); // In GMAT, the degree is NN
-        let max_order = self.stor.max_order_m(); // In GMAT, the order is MM
+        let max_degree = self.max_degree; // In GMAT, the degree is NN
+        let max_order = self.max_order; // In GMAT, the order is MM
+
+        // Time varying corrections of the coefficients
+        let delta_cs = self
+            .tides
+            .as_ref()
+            .map(|tides| tides.delta_cs(osc.epoch, self.compute_frame, &self.cosm))
+            .transpose()?;
 
         // Create the associated Legendre polynomials. Note that we add three items as per GMAT (this may be useful for the STM)
         let mut a_nm = self.a_nm.clone();
@@ -207,7 +289,7 @@ impl AccelModel for Harmonics {
             rho_np1 *= rho;
 
             for m in 0..=min(n, max_order) {
-                let (c_val, s_val) = self.stor.cs_nm(n, m);
+                let (c_val, s_val) = self.cs_nm(n, m, delta_cs.as_ref());
                 let d_ = (c_val * r_m[m] + s_val * i_m[m]) * 2.0.sqrt();
                 let e_ = if m == 0 {
                     0.0
@@ -253,8 +335,15 @@ impl AccelModel for Harmonics {
         let s_ = radius[0] / r_;
         let t_ = radius[1] / r_;
         let u_ = radius[2] / r_;
-        let max_degree = self.stor.max_degree_n(); // In GMAT, the order is NN
-        let max_order = self.stor.max_order_m(); // In GMAT, the order is MM
+        let max_degree = self.max_degree; // In GMAT, the order is NN
+        let max_order = self.max_order; // In GMAT, the order is MM
+
+        // Time varying corrections of the coefficients (they do not depend on the position)
+        let delta_cs = self
+            .tides
+            .as_ref()
+            .map(|tides| tides.delta_cs(osc.epoch, self.compute_frame, &self.cosm))
+            .transpose()?;
 
         // Create the associated Legendre polynomials. Note that we add three items as per GMAT (this may be useful for the STM)
         let mut a_nm = self.a_nm_h.clone();
@@ -305,7 +394,7 @@ impl AccelModel for Harmonics {
             rho_np1 *= rho;
 
             for m in 0..=min(n, max_order) {
-                let (c_valf64, s_valf64) = self.stor.cs_nm(n, m);
+                let (c_valf64, s_valf64) = self.cs_nm(n, m, delta_cs.as_ref());
                 let c_val = OHyperdual::<f64, U7>::from(c_valf64);
                 let s_val = OHyperdual::<f64, U7>::from(s_valf64);
 
@@ -0,0 +1,87 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::dynamics::{AccelModel, Harmonics};
+use crate::io::gravity::HarmonicsMem;
+use crate::linalg::{Matrix3, Vector3};
+use std::fmt;
+use std::sync::Arc;
+
+use super::DynamicsError;
+
+/// Non-spherical gravity field of a third body, e.g. the lunar harmonics while integrating around the Earth.
+///
+/// The perturbation is the difference between the acceleration of the spacecraft due to the field (direct term) and the
+/// acceleration of the central body of the integration frame due to the same field (indirect term), as the integration frame
+/// is not inertial. The point mass term of the third body is _not_ included: use `PointMasses` for it.
+/// If the integration frame is centered on the body of the gravity field, this model is identical to `Harmonics`.
+#[derive(Clone)]
+pub struct ThirdBodyHarmonics {
+    harmonics: Arc<Harmonics>,
+}
+
+impl ThirdBodyHarmonics {
+    /// Create a new third body harmonics model from the provided gravity potential storage instance, computed in the body fixed frame of the third body.
+    pub fn from_stor(body_fixed_frame: Frame, stor: HarmonicsMem, cosm: Arc<Cosm>) -> Arc<Self> {
+        Self::from_harmonics(Harmonics::from_stor(body_fixed_frame, stor, cosm))
+    }
+
+    /// Create a new third body harmonics model from the harmonics of the third body.
+    pub fn from_harmonics(harmonics: Arc<Harmonics>) -> Arc<Self> {
+        Arc::new(Self { harmonics })
+    }
+
+    /// Returns whether the integration frame of the provided state is centered on the third body
+    fn is_central_body(&self, osc: &Orbit) -> bool {
+        self.harmonics.compute_frame().ephem_path() == osc.frame.ephem_path()
+    }
+
+    /// Returns the acceleration of the central body of the integration frame due to the gravity field of the third body (indirect term).
+    fn indirect_accel(&self, osc: &Orbit) -> Result<Vector3<f64>, DynamicsError> {
+        let central_body = Orbit::cartesian(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, osc.epoch, osc.frame);
+        self.harmonics.eom(&central_body)
+    }
+}
+
+impl fmt::Display for ThirdBodyHarmonics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "third body {}", self.harmonics)
+    }
+}
+
+impl AccelModel for ThirdBodyHarmonics {
+    fn eom(&self, osc: &Orbit) -> Result<Vector3<f64>, DynamicsError> {
+        let direct = self.harmonics.eom(osc)?;
+        if self.is_central_body(osc) {
+            Ok(direct)
+        } else {
+            Ok(direct - self.indirect_accel(osc)?)
+        }
+    }
+
+    fn dual_eom(&self, osc: &Orbit) -> Result<(Vector3<f64>, Matrix3<f64>), DynamicsError> {
+        let (direct, grad) = self.harmonics.dual_eom(osc)?;
+        if self.is_central_body(osc) {
+            Ok((direct, grad))
+        } else {
+            // The indirect term does not depend on the position of the spacecraft, so it does not contribute to the gradient.
+            Ok((direct - self.indirect_accel(osc)?, grad))
+        }
+    }
+}
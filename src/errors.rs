@@ -68,6 +68,9 @@ pub enum NyxError {
     /// No thruster attached to spacecraft
     #[snafu(display("No thruster attached to spacecraft"))]
     NoThrusterAvail,
+    /// No propellant tank at this index on the spacecraft
+    #[snafu(display("No propellant tank {index} on spacecraft"))]
+    NoTankAvail { index: usize },
     /// Happens when trying to modify a polynomial's (error)-th error but the polynomial has less orders than that
     #[snafu(display("Happens when trying to modify a polynomial's (error)-th error but the polynomial has less orders than that"))]
     PolynomialOrderError { order: usize },
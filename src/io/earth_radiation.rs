@@ -0,0 +1,259 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::utils::between_pm_180;
+use crate::NyxError;
+use std::fs::File;
+use std::io::BufReader;
+use std::str::FromStr;
+
+/// `EarthRadiationMap` stores a regular latitude/longitude grid of the albedo (reflectivity) and emissivity of a celestial body.
+///
+/// Each value applies to the cell centered on its latitude and longitude: the lookup returns the value of the nearest cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EarthRadiationMap {
+    /// Latitudes of the cell centers, in degrees, sorted in increasing order
+    lat_deg: Vec<f64>,
+    /// Longitudes of the cell centers, in degrees between -180 and 180, sorted in increasing order
+    lon_deg: Vec<f64>,
+    /// Albedo of each cell, by latitude then longitude
+    albedo: Vec<f64>,
+    /// Emissivity of each cell, by latitude then longitude
+    emissivity: Vec<f64>,
+}
+
+impl EarthRadiationMap {
+    /// Initializes a map with the same albedo and emissivity everywhere.
+    pub fn uniform(albedo: f64, emissivity: f64) -> Self {
+        Self {
+            lat_deg: vec![0.0],
+            lon_deg: vec![0.0],
+            albedo: vec![albedo],
+            emissivity: vec![emissivity],
+        }
+    }
+
+    /// Initializes the annual mean zonal model of Knocke et al. (1988), on a one degree latitude grid.
+    ///
+    /// The albedo is 0.34 + 0.29 P2(sin φ) and the emissivity is 0.68 - 0.18 P2(sin φ), where P2 is the second degree Legendre polynomial.
+    pub fn knocke() -> Self {
+        let lat_deg: Vec<f64> = (0..180).map(|i| -89.5 + i as f64).collect();
+        let p2: Vec<f64> = lat_deg
+            .iter()
+            .map(|lat| 0.5 * (3.0 * lat.to_radians().sin().powi(2) - 1.0))
+            .collect();
+        Self {
+            albedo: p2.iter().map(|p2| 0.34 + 0.29 * p2).collect(),
+            emissivity: p2.iter().map(|p2| 0.68 - 0.18 * p2).collect(),
+            lat_deg,
+            lon_deg: vec![0.0],
+        }
+    }
+
+    /// Initializes a map from the latitudes and longitudes of the cell centers, in degrees, and the albedo and emissivity of each cell
+    /// ordered by latitude then longitude.
+    pub fn from_grid(
+        lat_deg: Vec<f64>,
+        lon_deg: Vec<f64>,
+        albedo: Vec<f64>,
+        emissivity: Vec<f64>,
+    ) -> Result<Self, NyxError> {
+        let num_cells = lat_deg.len() * lon_deg.len();
+        if num_cells == 0 || albedo.len() != num_cells || emissivity.len() != num_cells {
+            return Err(NyxError::FileUnreadable {
+                msg: format!(
+                    "radiation map: expected {num_cells} cells but got {} albedo and {} emissivity values",
+                    albedo.len(),
+                    emissivity.len()
+                ),
+            });
+        }
+
+        if lat_deg.windows(2).any(|w| w[0] >= w[1]) || lon_deg.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(NyxError::FileUnreadable {
+                msg: "radiation map: latitudes and longitudes must be strictly increasing"
+                    .to_string(),
+            });
+        }
+
+        Ok(Self {
+            lat_deg,
+            lon_deg: lon_deg.iter().map(|lon| between_pm_180(*lon)).collect(),
+            albedo,
+            emissivity,
+        })
+    }
+
+    /// Loads the map from a CSV file with the columns `LAT_DEG`, `LON_DEG`, `ALBEDO` and `EMISSIVITY`, found from the header.
+    ///
+    /// The rows may be in any order but must cover every latitude and longitude of the grid exactly once.
+    pub fn from_csv(filepath: &str) -> Result<Self, NyxError> {
+        let file = File::open(filepath).map_err(|_| NyxError::FileUnreadable {
+            msg: format!("File not found: {filepath}"),
+        })?;
+
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(BufReader::new(file));
+
+        let headers = rdr
+            .headers()
+            .map_err(|e| NyxError::FileUnreadable {
+                msg: format!("radiation map: could not read header: {e}"),
+            })?
+            .clone();
+
+        let col = |name: &str| -> Result<usize, NyxError> {
+            headers
+                .iter()
+                .position(|h| h.trim() == name)
+                .ok_or_else(|| NyxError::FileUnreadable {
+                    msg: format!("radiation map: missing column `{name}`"),
+                })
+        };
+
+        let cols = [
+            col("LAT_DEG")?,
+            col("LON_DEG")?,
+            col("ALBEDO")?,
+            col("EMISSIVITY")?,
+        ];
+
+        let mut rows = Vec::new();
+        for (lno, record) in rdr.records().enumerate() {
+            let record = record.map_err(|e| NyxError::FileUnreadable {
+                msg: format!("radiation map: could not read line {lno}: {e}"),
+            })?;
+
+            let mut row = [0.0; 4];
+            for (value, idx) in row.iter_mut().zip(cols) {
+                let item = record.get(idx).unwrap_or_default().trim();
+                *value = f64::from_str(item).map_err(|_| NyxError::FileUnreadable {
+                    msg: format!("radiation map: could not parse `{item}` on line {lno}"),
+                })?;
+            }
+            row[1] = between_pm_180(row[1]);
+            rows.push(row);
+        }
+
+        let unique = |idx: usize| -> Vec<f64> {
+            let mut values: Vec<f64> = rows.iter().map(|row| row[idx]).collect();
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            values.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+            values
+        };
+
+        let lat_deg = unique(0);
+        let lon_deg = unique(1);
+
+        let num_cells = lat_deg.len() * lon_deg.len();
+        if rows.len() != num_cells {
+            return Err(NyxError::FileUnreadable {
+                msg: format!(
+                    "radiation map: {} rows do not form a regular grid of {} latitudes and {} longitudes",
+                    rows.len(),
+                    lat_deg.len(),
+                    lon_deg.len()
+                ),
+            });
+        }
+
+        let mut albedo = vec![f64::NAN; num_cells];
+        let mut emissivity = vec![f64::NAN; num_cells];
+        for row in &rows {
+            let idx = nearest(&lat_deg, row[0]) * lon_deg.len() + nearest(&lon_deg, row[1]);
+            albedo[idx] = row[2];
+            emissivity[idx] = row[3];
+        }
+
+        if albedo.iter().any(|a| a.is_nan()) {
+            return Err(NyxError::FileUnreadable {
+                msg: format!("radiation map: duplicated cells in {filepath}"),
+            });
+        }
+
+        Self::from_grid(lat_deg, lon_deg, albedo, emissivity)
+    }
+
+    /// Returns the albedo and emissivity of the cell nearest to the provided latitude and longitude, in degrees.
+    pub fn at(&self, lat_deg: f64, lon_deg: f64) -> (f64, f64) {
+        let lat_idx = nearest(&self.lat_deg, lat_deg);
+        let lon_idx = nearest_wrapped(&self.lon_deg, between_pm_180(lon_deg));
+        let idx = lat_idx * self.lon_deg.len() + lon_idx;
+        (self.albedo[idx], self.emissivity[idx])
+    }
+}
+
+/// Returns the index of the value nearest to `x` in the sorted `values`
+fn nearest(values: &[f64], x: f64) -> usize {
+    let idx = values.partition_point(|v| *v < x);
+    if idx == 0 {
+        0
+    } else if idx == values.len() || x - values[idx - 1] <= values[idx] - x {
+        idx - 1
+    } else {
+        idx
+    }
+}
+
+/// Returns the index of the longitude nearest to `lon_deg` in the sorted `values`, accounting for the wrap around at ±180 degrees
+fn nearest_wrapped(values: &[f64], lon_deg: f64) -> usize {
+    let idx = nearest(values, lon_deg);
+    let last = values.len() - 1;
+    // Check whether the cell on the other side of the antimeridian is closer
+    let dist = |i: usize| between_pm_180(values[i] - lon_deg).abs();
+    if dist(last) < dist(idx) {
+        last
+    } else if dist(0) < dist(idx) {
+        0
+    } else {
+        idx
+    }
+}
+
+#[cfg(test)]
+mod ut_earth_radiation {
+    use super::*;
+
+    #[test]
+    fn map_lookup() {
+        let lat_deg = vec![-45.0, 45.0];
+        let lon_deg = vec![-135.0, -45.0, 45.0, 135.0];
+        let albedo = (0..8).map(|i| i as f64 / 10.0).collect();
+        let emissivity = vec![0.5; 8];
+        let map = EarthRadiationMap::from_grid(lat_deg, lon_deg, albedo, emissivity).unwrap();
+
+        assert_eq!(map.at(-80.0, -100.0), (0.0, 0.5));
+        assert_eq!(map.at(10.0, 50.0), (0.6, 0.5));
+        // Wrap around the antimeridian
+        assert_eq!(map.at(10.0, 179.0), (0.7, 0.5));
+        assert_eq!(map.at(10.0, -179.0), (0.4, 0.5));
+        assert_eq!(map.at(10.0, 200.0), (0.4, 0.5));
+
+        assert!(
+            EarthRadiationMap::from_grid(vec![0.0], vec![0.0, 1.0], vec![0.3], vec![0.6]).is_err()
+        );
+
+        let knocke = EarthRadiationMap::knocke();
+        let (albedo_pole, emissivity_pole) = knocke.at(90.0, 12.0);
+        let (albedo_eq, emissivity_eq) = knocke.at(0.0, -12.0);
+        assert!(albedo_pole > albedo_eq);
+        assert!(emissivity_pole < emissivity_eq);
+        assert!((albedo_eq - (0.34 - 0.145)).abs() < 1e-3);
+    }
+}
@@ -17,6 +17,7 @@
 */
 
 use crate::errors::NyxError;
+use crate::md::prelude::Traj;
 use crate::md::StateParameter;
 use crate::time::Epoch;
 use crate::Orbit;
@@ -48,6 +49,8 @@ use crate::cosmic::{Cosm, Frame};
 /// Handles writing to an XYZV file
 pub mod cosmo;
 pub mod dynamics;
+/// Handles loading of the albedo and emissivity maps used by the Earth radiation pressure model
+pub mod earth_radiation;
 pub mod estimate;
 /// Handles reading from frames defined in input files
 pub mod frame_serde;
@@ -354,6 +357,27 @@ where
     Ok(frames)
 }
 
+pub(crate) fn traj_to_states<S>(traj: &Traj<Orbit>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    traj.states.serialize(serializer)
+}
+
+pub(crate) fn traj_from_states<'de, D>(deserializer: D) -> Result<Traj<Orbit>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let states: Vec<Orbit> = Vec::deserialize(deserializer)?;
+    if states.is_empty() {
+        return Err(serde::de::Error::custom("trajectory has no states"));
+    }
+    let mut traj = Traj::new();
+    traj.states = states;
+    traj.finalize();
+    Ok(traj)
+}
+
 /// A deserializer from Epoch string
 pub(crate) fn orbit_from_str<'de, D>(deserializer: D) -> Result<Orbit, D::Error>
 where
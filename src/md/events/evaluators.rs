@@ -85,6 +85,17 @@ impl EventEvaluator<Spacecraft> for Event {
     fn eval(&self, state: &Spacecraft) -> f64 {
         match self.parameter {
             StateParameter::FuelMass => state.fuel_mass_kg - self.desired_value,
+            StateParameter::TankMass1
+            | StateParameter::TankMass2
+            | StateParameter::TankMass3
+            | StateParameter::TankMass4 => match state.value(self.parameter) {
+                Ok(mass_kg) => mass_kg - self.desired_value,
+                Err(e) => {
+                    // The event cannot be evaluated: it will never be found
+                    error!("{self}: {e}");
+                    f64::NAN
+                }
+            },
             _ => self.eval(&state.orbit),
         }
     }
@@ -34,6 +34,8 @@ where
         Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
 {
     /// Find the exact state where the request event happens. The event function is expected to be monotone in the provided interval because we find the event using a Brent solver.
+    /// If the trajectory stores the continuous extension of each integration step (cf. `PropInstance::with_dense_output`), then the event is evaluated
+    /// with the integrator's own interpolant instead of a Hermite interpolation of the states.
     #[allow(clippy::identity_op)]
     pub fn find_bracketed<E>(
         &self,
@@ -108,6 +108,14 @@ pub enum StateParameter {
     SMA,
     /// Semi minor axis (km)
     SemiMinorAxis,
+    /// Propellant mass in the first tank (kg)
+    TankMass1,
+    /// Propellant mass in the second tank (kg)
+    TankMass2,
+    /// Propellant mass in the third tank (kg)
+    TankMass3,
+    /// Propellant mass in the fourth tank (kg)
+    TankMass4,
     /// Thrust (Newtons)
     Thrust,
     /// True anomaly
@@ -182,7 +190,12 @@ impl StateParameter {
 
             // Special
             Self::Energy => 1e-3,
-            Self::DryMass | Self::FuelMass => 1e-3,
+            Self::DryMass
+            | Self::FuelMass
+            | Self::TankMass1
+            | Self::TankMass2
+            | Self::TankMass3
+            | Self::TankMass4 => 1e-3,
             Self::Period => 1e-1,
             _ => unimplemented!("{self} cannot be used for event finding"),
         }
@@ -209,9 +222,24 @@ impl StateParameter {
                 | Self::Isp
                 | Self::GuidanceMode
                 | Self::Thrust
+                | Self::TankMass1
+                | Self::TankMass2
+                | Self::TankMass3
+                | Self::TankMass4
         )
     }
 
+    /// Returns the index of the propellant tank of this parameter, if it is a tank mass
+    pub const fn tank_index(&self) -> Option<usize> {
+        match self {
+            Self::TankMass1 => Some(0),
+            Self::TankMass2 => Some(1),
+            Self::TankMass3 => Some(2),
+            Self::TankMass4 => Some(3),
+            _ => None,
+        }
+    }
+
     pub const fn unit(&self) -> &'static str {
         match self {
             // Angles
@@ -256,7 +284,12 @@ impl StateParameter {
 
             Self::C3 | Self::Energy => "km^2/s^2",
 
-            Self::DryMass | Self::FuelMass => "kg",
+            Self::DryMass
+            | Self::FuelMass
+            | Self::TankMass1
+            | Self::TankMass2
+            | Self::TankMass3
+            | Self::TankMass4 => "kg",
             Self::Isp => "isp",
             Self::Thrust => "N",
             _ => "",
@@ -348,6 +381,10 @@ impl FromStr for StateParameter {
             "sma" => Ok(Self::SMA),
             "ta" => Ok(Self::TrueAnomaly),
             "tlong" => Ok(Self::TrueLongitude),
+            "tank1_mass" => Ok(Self::TankMass1),
+            "tank2_mass" => Ok(Self::TankMass2),
+            "tank3_mass" => Ok(Self::TankMass3),
+            "tank4_mass" => Ok(Self::TankMass4),
             "thrust" => Ok(Self::Thrust),
             "vdeclin" => Ok(Self::VelocityDeclination),
             "vmag" => Ok(Self::Vmag),
@@ -406,6 +443,10 @@ impl fmt::Display for StateParameter {
             Self::SemiParameter => "semi_parameter",
             Self::SemiMinorAxis => "semi_minor",
             Self::SMA => "sma",
+            Self::TankMass1 => "tank1_mass",
+            Self::TankMass2 => "tank2_mass",
+            Self::TankMass3 => "tank3_mass",
+            Self::TankMass4 => "tank4_mass",
             Self::Thrust => "thrust",
             Self::TrueAnomaly => "ta",
             Self::TrueLongitude => "tlong",
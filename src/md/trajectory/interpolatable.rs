@@ -178,8 +178,21 @@ impl Interpolatable for Spacecraft {
                 - states.first().unwrap().epoch().to_tdb_seconds());
 
         let mut me = self.with_orbit(orbit);
-        me.fuel_mass_kg += fuel_kg_dt
-            * (epoch.to_tdb_seconds() - states.first().unwrap().epoch().to_tdb_seconds());
+        let elapsed_s = epoch.to_tdb_seconds() - states.first().unwrap().epoch().to_tdb_seconds();
+        me.fuel_mass_kg += fuel_kg_dt * elapsed_s;
+
+        // The propellant in each tank is also linearly interpolated
+        let duration_s = states.last().unwrap().epoch().to_tdb_seconds()
+            - states.first().unwrap().epoch().to_tdb_seconds();
+        let first_tanks = states.first().unwrap().tank_masses();
+        let last_tanks = states.last().unwrap().tank_masses();
+        for (i, tank) in me.tank_masses_kg.iter_mut().enumerate() {
+            if tank.is_some() {
+                *tank = Some(
+                    first_tanks[i] + (last_tanks[i] - first_tanks[i]) / duration_s * elapsed_s,
+                );
+            }
+        }
 
         me
     }
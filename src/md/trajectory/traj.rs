@@ -22,9 +22,10 @@ use super::{Interpolatable, TrajError};
 use crate::errors::NyxError;
 use crate::io::watermark::pq_writer;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::DefaultAllocator;
+use crate::linalg::{DefaultAllocator, OVector};
 use crate::md::prelude::{Frame, GuidanceMode, StateParameter};
 use crate::md::EventEvaluator;
+use crate::propagators::{evaluate_step, step_contains, StepInterpolant};
 use crate::time::{Duration, Epoch, TimeSeries, TimeUnits};
 use crate::utils::dcm_finite_differencing;
 use arrow::array::{Array, Float64Builder, StringBuilder};
@@ -51,6 +52,31 @@ where
     pub name: Option<String>,
     /// We use a vector because we know that the states are produced in a chronological manner (the direction does not matter).
     pub states: Vec<S>,
+    /// Continuous extension of each integration step, if the propagator generated them, which are used instead of the Hermite interpolation of the states.
+    dense: Vec<DenseStep<S>>,
+}
+
+/// Continuous extension of an integration step in a trajectory: its start state is one of the states of the trajectory,
+/// so only the polynomial coefficients are stored.
+#[derive(Clone, PartialEq)]
+struct DenseStep<S: Interpolatable>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    start_epoch: Epoch,
+    step: Duration,
+    coeffs: Vec<OVector<f64, S::VecLength>>,
+}
+
+impl<S: Interpolatable> DenseStep<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    fn end_epoch(&self) -> Epoch {
+        self.start_epoch + self.step
+    }
 }
 
 impl<S: Interpolatable> Traj<S>
@@ -62,17 +88,71 @@ where
         Self {
             name: None,
             states: Vec::new(),
+            dense: Vec::new(),
         }
     }
+
+    /// Initializes a trajectory from the provided states, which must be in chronological order (cf. `finalize` otherwise).
+    pub fn from_states(states: Vec<S>) -> Self {
+        let mut me = Self::new();
+        me.states = states;
+        me
+    }
+
+    /// Stores the continuous extension of an integration step, whose start state must be one of the states of this trajectory.
+    /// Each step stores as many vectors as the degree of its polynomial (e.g. four for `Dormand45`), in addition to the states.
+    pub fn push_step(&mut self, step: StepInterpolant<S>) {
+        self.dense.push(DenseStep {
+            start_epoch: step.start_epoch(),
+            step: step.step,
+            coeffs: step.coeffs,
+        });
+    }
+
+    /// Returns the number of integration steps whose continuous extension is stored in this trajectory.
+    pub fn num_steps(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Returns the continuous extension of the integration step of the provided index, in chronological order, if its start state is stored.
+    pub fn step(&self, idx: usize) -> Option<StepInterpolant<S>> {
+        let step = self.dense.get(idx)?;
+        let start = self.state_at(step.start_epoch)?;
+        Some(StepInterpolant {
+            start: *start,
+            step: step.step,
+            coeffs: step.coeffs.clone(),
+        })
+    }
+
+    /// Removes the continuous extension of the integration steps, so that this trajectory is only interpolated from its states.
+    pub fn without_steps(mut self) -> Self {
+        self.dense.clear();
+        self
+    }
+
+    /// Returns the state stored at exactly the provided epoch, if any
+    fn state_at(&self, epoch: Epoch) -> Option<&S> {
+        self.states
+            .binary_search_by(|state| state.epoch().cmp(&epoch))
+            .ok()
+            .map(|idx| &self.states[idx])
+    }
     /// Orders the states, can be used to store the states out of order
     pub fn finalize(&mut self) {
         // Remove duplicate epochs
         self.states.dedup_by(|a, b| a.epoch().eq(&b.epoch()));
         // And sort
         self.states.sort_by_key(|a| a.epoch());
+        // Same for the steps, which do not overlap, so they are ordered by either bound
+        self.dense
+            .sort_by_key(|step| step.start_epoch.min(step.end_epoch()));
+        self.dense
+            .dedup_by(|a, b| a.start_epoch.eq(&b.start_epoch) && a.step.eq(&b.step));
     }
 
     /// Evaluate the trajectory at this specific epoch.
+    /// This uses the continuous extension of the integration step which contains this epoch if available, and a Hermite interpolation of the nearby states otherwise.
     pub fn at(&self, epoch: Epoch) -> Result<S, TrajError> {
         if self.states.is_empty() || self.first().epoch() > epoch || self.last().epoch() < epoch {
             return Err(TrajError::NoInterpolationData { epoch });
@@ -86,6 +166,18 @@ where
                 Ok(self.states[idx])
             }
             Err(idx) => {
+                // Use the continuous extension of the integration step if available
+                let step_idx = self
+                    .dense
+                    .partition_point(|step| step.start_epoch.max(step.end_epoch()) < epoch);
+                if let Some(step) = self.dense.get(step_idx) {
+                    if step_contains(step.start_epoch, step.step, epoch) {
+                        if let Some(start) = self.state_at(step.start_epoch) {
+                            return evaluate_step(start, step.step, &step.coeffs, epoch);
+                        }
+                    }
+                }
+
                 if idx == 0 || idx >= self.states.len() {
                     // The binary search returns where we should insert the data, so if it's at either end of the list, then we're out of bounds.
                     // This condition should have been handled by the check at the start of this function.
@@ -628,6 +720,13 @@ where
             {
                 me.states.push(**state);
             }
+            for step in other
+                .dense
+                .iter()
+                .filter(|step| step.start_epoch.min(step.end_epoch()) >= self.last().epoch())
+            {
+                me.dense.push(step.clone());
+            }
             me.finalize();
 
             Ok(me)
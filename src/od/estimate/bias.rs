@@ -0,0 +1,304 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::hifitime::Epoch;
+use crate::linalg::{DMatrix, DVector};
+use crate::od::noise::GaussMarkov;
+use std::fmt;
+
+/// Kind of measurement bias of a tracking device
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BiasKind {
+    /// Offset of the first measurement component (e.g. the range), in the unit of that measurement
+    Range,
+    /// Offset of the second measurement component (e.g. the Doppler), in the unit of that measurement
+    Doppler,
+    /// Offset of the time tag of the measurement, in seconds.
+    /// Only supported for measurements where the second component is the rate of the first one (e.g. range and Doppler).
+    Timing,
+}
+
+impl fmt::Display for BiasKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Range => write!(f, "range"),
+            Self::Doppler => write!(f, "Doppler"),
+            Self::Timing => write!(f, "timing"),
+        }
+    }
+}
+
+/// A measurement bias of a tracking device, augmented to the filter state and modeled as a first order Gauss-Markov process.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MsrBias {
+    /// Name of the tracking device of this bias
+    pub device: String,
+    /// Kind of bias
+    pub kind: BiasKind,
+    /// The time constant τ and the steady state sigma are used for the time update of the bias, and the bias sigma is its initial standard deviation.
+    /// A white noise process (i.e. τ of more than a year) with a zero steady state sigma is a random constant, otherwise it is reset at each time step.
+    pub process: GaussMarkov,
+    /// If set, this bias is only _considered_: its uncertainty is accounted for in the covariance, but it is not estimated.
+    pub consider: bool,
+}
+
+impl MsrBias {
+    /// Initializes a measurement bias which will be estimated by the filter.
+    pub fn solve_for(device: &str, kind: BiasKind, process: GaussMarkov) -> Self {
+        Self {
+            device: device.to_string(),
+            kind,
+            process,
+            consider: false,
+        }
+    }
+
+    /// Initializes a measurement bias which will only be considered by the filter.
+    pub fn consider(device: &str, kind: BiasKind, process: GaussMarkov) -> Self {
+        Self {
+            device: device.to_string(),
+            kind,
+            process,
+            consider: true,
+        }
+    }
+
+    /// Returns the decay of this bias and the variance of its process noise over the provided time step.
+    fn decay_and_noise(&self, delta_t_s: f64) -> (f64, f64) {
+        if self.process.is_white() {
+            if self.process.steady_state_sigma > 0.0 && delta_t_s.abs() > 0.0 {
+                // A white noise bias is uncorrelated in time: it is reset to a zero mean with its steady state variance after any time step
+                return (0.0, self.process.steady_state_sigma.powi(2));
+            }
+            // Without a steady state sigma, the bias is a random constant of the bias sigma
+            return (1.0, 0.0);
+        }
+        let decay = (-delta_t_s.abs() / self.process.tau.to_seconds()).exp();
+        (
+            decay,
+            self.process.steady_state_sigma.powi(2) * (1.0 - decay.powi(2)),
+        )
+    }
+}
+
+impl fmt::Display for MsrBias {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} bias ({}) with {}",
+            self.device,
+            self.kind,
+            if self.consider {
+                "consider"
+            } else {
+                "solve-for"
+            },
+            self.process
+        )
+    }
+}
+
+/// Estimate of the measurement biases augmented to the filter state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BiasEstimate {
+    /// Epoch of this estimate, unset until the first update of the filter
+    pub epoch: Option<Epoch>,
+    /// Definition of each bias
+    pub biases: Vec<MsrBias>,
+    /// Estimated value of each bias
+    pub values: DVector<f64>,
+    /// Covariance of the biases
+    pub covar: DMatrix<f64>,
+    /// Cross covariance between the estimated state (rows) and the biases (columns)
+    pub cross_covar: DMatrix<f64>,
+}
+
+impl BiasEstimate {
+    /// Initializes the bias estimate of zero mean, with the variance of each bias set from its Gauss-Markov bias sigma.
+    pub fn new(biases: Vec<MsrBias>, state_size: usize) -> Self {
+        let num = biases.len();
+        let covar = DMatrix::from_diagonal(&DVector::from_iterator(
+            num,
+            biases.iter().map(|bias| bias.process.bias_sigma.powi(2)),
+        ));
+        Self {
+            epoch: None,
+            biases,
+            values: DVector::zeros(num),
+            covar,
+            cross_covar: DMatrix::zeros(state_size, num),
+        }
+    }
+
+    /// Returns the estimated value of the provided bias of the provided device, if it is augmented to the state.
+    pub fn value_of(&self, device: &str, kind: BiasKind) -> Option<f64> {
+        self.index_of(device, kind).map(|idx| self.values[idx])
+    }
+
+    /// Returns the standard deviation of the provided bias of the provided device, if it is augmented to the state.
+    pub fn sigma_of(&self, device: &str, kind: BiasKind) -> Option<f64> {
+        self.index_of(device, kind)
+            .map(|idx| self.covar[(idx, idx)].sqrt())
+    }
+
+    fn index_of(&self, device: &str, kind: BiasKind) -> Option<usize> {
+        self.biases
+            .iter()
+            .position(|bias| bias.device == device && bias.kind == kind)
+    }
+
+    /// Time update of the biases with the state transition matrix of the state over the provided time step.
+    pub(crate) fn predict(&mut self, epoch: Epoch, delta_t_s: f64, stm: &DMatrix<f64>) {
+        let num = self.biases.len();
+        let mut phi_b = DMatrix::<f64>::identity(num, num);
+        let mut q_b = DMatrix::<f64>::zeros(num, num);
+        for (idx, bias) in self.biases.iter().enumerate() {
+            let (decay, noise) = bias.decay_and_noise(delta_t_s);
+            phi_b[(idx, idx)] = decay;
+            q_b[(idx, idx)] = noise;
+        }
+
+        self.values = &phi_b * &self.values;
+        self.covar = &phi_b * &self.covar * phi_b.transpose() + q_b;
+        self.cross_covar = stm * &self.cross_covar * phi_b.transpose();
+        self.epoch = Some(epoch);
+    }
+
+    /// Returns the offset of the measurement of the provided device due to the biases, once they are decayed over the provided time step.
+    pub(crate) fn predicted_offset(
+        &self,
+        device: &str,
+        delta_t_s: f64,
+        msr_size: usize,
+        computed_obs: &DVector<f64>,
+    ) -> DVector<f64> {
+        let decayed = DVector::from_iterator(
+            self.biases.len(),
+            self.biases
+                .iter()
+                .zip(self.values.iter())
+                .map(|(bias, value)| bias.decay_and_noise(delta_t_s).0 * value),
+        );
+        self.sensitivity(device, msr_size, computed_obs) * decayed
+    }
+
+    /// Returns the sensitivity matrix of the measurement with respect to the biases of the provided device.
+    /// The rate of the first measurement component is needed for the timing biases.
+    pub(crate) fn sensitivity(
+        &self,
+        device: &str,
+        msr_size: usize,
+        computed_obs: &DVector<f64>,
+    ) -> DMatrix<f64> {
+        let mut h_b = DMatrix::<f64>::zeros(msr_size, self.biases.len());
+        for (idx, bias) in self.biases.iter().enumerate() {
+            if bias.device != device {
+                continue;
+            }
+            match bias.kind {
+                BiasKind::Range => h_b[(0, idx)] = 1.0,
+                BiasKind::Doppler => {
+                    if msr_size > 1 {
+                        h_b[(1, idx)] = 1.0
+                    }
+                }
+                BiasKind::Timing => {
+                    if msr_size > 1 {
+                        h_b[(0, idx)] = computed_obs[1]
+                    }
+                }
+            }
+        }
+        h_b
+    }
+}
+
+impl fmt::Display for BiasEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, bias) in self.biases.iter().enumerate() {
+            writeln!(
+                f,
+                "{} {} bias: {:.6e} ± {:.6e}",
+                bias.device,
+                bias.kind,
+                self.values[idx],
+                self.covar[(idx, idx)].sqrt()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod ut_bias {
+    use super::*;
+    use crate::time::Unit;
+
+    #[test]
+    fn bias_time_update() {
+        let process = GaussMarkov::new(Unit::Hour * 1, 1e-3, 1e-4).unwrap();
+        let mut est = BiasEstimate::new(
+            vec![
+                MsrBias::solve_for("DSS-65", BiasKind::Range, process),
+                MsrBias::consider("DSS-65", BiasKind::Doppler, GaussMarkov::ZERO),
+            ],
+            6,
+        );
+        est.values[0] = 1e-3;
+
+        assert!((est.sigma_of("DSS-65", BiasKind::Range).unwrap() - 1e-3).abs() < 1e-15);
+        assert_eq!(est.value_of("DSS-34", BiasKind::Range), None);
+
+        let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+        est.predict(epoch, 3600.0, &DMatrix::identity(6, 6));
+
+        // After one time constant, the bias decays by 1/e
+        let value = est.value_of("DSS-65", BiasKind::Range).unwrap();
+        assert!((value - 1e-3 * (-1.0_f64).exp()).abs() < 1e-15);
+        // And the variance tends to the steady state variance
+        let expected_var = 1e-6 * (-2.0_f64).exp() + 1e-8 * (1.0 - (-2.0_f64).exp());
+        assert!((est.covar[(0, 0)] - expected_var).abs() < 1e-18);
+        // The zero noise process remains zero
+        assert_eq!(est.covar[(1, 1)], 0.0);
+
+        // A white noise bias forgets its value at every time step
+        let mut white = BiasEstimate::new(
+            vec![MsrBias::solve_for(
+                "DSS-65",
+                BiasKind::Range,
+                GaussMarkov::white_noise(2e-3),
+            )],
+            6,
+        );
+        white.values[0] = 5e-3;
+        white.predict(epoch, 0.0, &DMatrix::identity(6, 6));
+        assert_eq!(white.values[0], 5e-3);
+        white.predict(epoch + Unit::Second * 10, 10.0, &DMatrix::identity(6, 6));
+        assert_eq!(white.values[0], 0.0);
+        assert!((white.covar[(0, 0)] - 4e-6).abs() < 1e-18);
+
+        let h_b = est.sensitivity("DSS-65", 2, &DVector::from_vec(vec![1000.0, 2.5]));
+        assert_eq!(h_b[(0, 0)], 1.0);
+        assert_eq!(h_b[(1, 1)], 1.0);
+        assert_eq!(
+            est.sensitivity("DSS-34", 2, &DVector::from_vec(vec![1000.0, 2.5]))
+                .norm(),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,287 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::hifitime::Epoch;
+use crate::io::ConfigError;
+use crate::linalg::{DMatrix, DVector, Matrix3, Vector3};
+use std::fmt;
+
+/// Kind of parameter which is not estimated but whose uncertainty is considered
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsiderKind {
+    /// Gravitational parameter of the central body of the estimated orbit, in km^3/s^2
+    GravParam,
+    /// Component of the estimated state vector which is not solved for, e.g. index 6 for the Cr of a Spacecraft.
+    /// The a priori variance of this component must be zero in the filter so that it is not estimated.
+    StateComponent(usize),
+    /// Position of the tracking device along the provided axis (0, 1 or 2) of the frame in which it is fixed, in km
+    DeviceLocation { device: String, axis: usize },
+}
+
+impl fmt::Display for ConsiderKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::GravParam => write!(f, "GM"),
+            Self::StateComponent(idx) => write!(f, "state component #{idx}"),
+            Self::DeviceLocation { device, axis } => match ["X", "Y", "Z"].get(*axis) {
+                Some(name) => write!(f, "{device} location {name}"),
+                None => write!(f, "{device} location axis #{axis}"),
+            },
+        }
+    }
+}
+
+/// A consider parameter: its uncertainty is accounted for in the consider covariance, but it is not estimated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsiderParam {
+    /// Kind of consider parameter
+    pub kind: ConsiderKind,
+    /// A priori standard deviation of this parameter, in the unit of the parameter
+    pub sigma: f64,
+}
+
+impl ConsiderParam {
+    /// Considers the gravitational parameter of the central body, with the provided sigma in km^3/s^2
+    pub fn grav_param(sigma_km3_s2: f64) -> Self {
+        Self {
+            kind: ConsiderKind::GravParam,
+            sigma: sigma_km3_s2,
+        }
+    }
+
+    /// Considers a component of the estimated state vector, e.g. the Cr of a Spacecraft
+    pub fn state_component(index: usize, sigma: f64) -> Self {
+        Self {
+            kind: ConsiderKind::StateComponent(index),
+            sigma,
+        }
+    }
+
+    /// Considers the location of the provided tracking device along the provided axis (0, 1 or 2) of the frame in which it is fixed
+    pub fn device_location(device: &str, axis: usize, sigma_km: f64) -> Result<Self, ConfigError> {
+        if axis > 2 {
+            return Err(ConfigError::InvalidConfig {
+                msg: format!("device location axis must be 0, 1 or 2 but got {axis}"),
+            });
+        }
+        Ok(Self {
+            kind: ConsiderKind::DeviceLocation {
+                device: device.to_string(),
+                axis,
+            },
+            sigma: sigma_km,
+        })
+    }
+}
+
+impl fmt::Display for ConsiderParam {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (consider, σ = {:.6e})", self.kind, self.sigma)
+    }
+}
+
+/// Consider covariance analysis of the filter.
+///
+/// The filter estimate is computed ignoring the consider parameters, so its covariance only accounts for the measurement and
+/// process noises. This structure tracks the sensitivity S = ∂x̂/∂c of the estimated state to the consider parameters, from which
+/// the consider covariance P + S⋅Pcc⋅S^T and the cross covariance S⋅Pcc between the state and the consider parameters are computed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsiderEstimate {
+    /// Epoch of this estimate, unset until the first update of the filter
+    pub epoch: Option<Epoch>,
+    /// Definition of each consider parameter
+    pub params: Vec<ConsiderParam>,
+    /// Covariance of the consider parameters, which is not affected by the measurements
+    pub covar: DMatrix<f64>,
+    /// Sensitivity of the estimated state (rows) to the consider parameters (columns)
+    pub sensitivity: DMatrix<f64>,
+}
+
+impl ConsiderEstimate {
+    /// Initializes the consider analysis, with the variance of each parameter set from its sigma.
+    pub fn new(params: Vec<ConsiderParam>, state_size: usize) -> Self {
+        let num = params.len();
+        let covar = DMatrix::from_diagonal(&DVector::from_iterator(
+            num,
+            params.iter().map(|param| param.sigma.powi(2)),
+        ));
+        let mut me = Self {
+            epoch: None,
+            params,
+            covar,
+            sensitivity: DMatrix::zeros(state_size, num),
+        };
+        me.reset();
+        me
+    }
+
+    /// Resets the sensitivity to its initial value, where only the considered state components are sensitive to their parameter.
+    pub(crate) fn reset(&mut self) {
+        self.sensitivity.fill(0.0);
+        for (idx, param) in self.params.iter().enumerate() {
+            if let ConsiderKind::StateComponent(state_idx) = param.kind {
+                self.sensitivity[(state_idx, idx)] = 1.0;
+            }
+        }
+        self.epoch = None;
+    }
+
+    /// Returns the cross covariance between the estimated state (rows) and the consider parameters (columns)
+    pub fn cross_covar(&self) -> DMatrix<f64> {
+        &self.sensitivity * &self.covar
+    }
+
+    /// Returns the consider covariance from the noise-only covariance of the estimate
+    pub fn consider_covar(&self, covar: &DMatrix<f64>) -> DMatrix<f64> {
+        let inflation = &self.sensitivity * &self.covar * self.sensitivity.transpose();
+        // Enforce the symmetry of the inflated covariance
+        covar + (&inflation + inflation.transpose()) * 0.5
+    }
+
+    /// Time update of the sensitivity with the state transition matrix over the provided time step.
+    ///
+    /// The positions of the previous and current states (in km) are needed to map the gravitational parameter to the state.
+    /// The mapping integrates Φ⋅B, where B = [0; -r/|r|^3] is the partial of the state derivative with respect to GM, using the trapezoidal rule.
+    pub(crate) fn predict(
+        &mut self,
+        epoch: Epoch,
+        delta_t_s: f64,
+        stm: &DMatrix<f64>,
+        positions: Option<(Vector3<f64>, Vector3<f64>)>,
+    ) {
+        self.sensitivity = stm * &self.sensitivity;
+
+        if let Some((prev_r, cur_r)) = positions {
+            let n = stm.nrows();
+            let mut prev_b = DVector::<f64>::zeros(n);
+            let mut cur_b = DVector::<f64>::zeros(n);
+            for i in 0..3 {
+                prev_b[i + 3] = -prev_r[i] / prev_r.norm().powi(3);
+                cur_b[i + 3] = -cur_r[i] / cur_r.norm().powi(3);
+            }
+            let theta = (stm * prev_b + cur_b) * (0.5 * delta_t_s);
+
+            for (idx, param) in self.params.iter().enumerate() {
+                if param.kind == ConsiderKind::GravParam {
+                    let mapped = self.sensitivity.column(idx) + &theta;
+                    self.sensitivity.set_column(idx, &mapped);
+                }
+            }
+        }
+
+        self.epoch = Some(epoch);
+    }
+
+    /// Returns the sensitivity of the measurement to the consider parameters.
+    ///
+    /// The sensitivity to the location of the tracking device is the opposite of the sensitivity to the position of the
+    /// spacecraft (`h_position`, first three columns of H tilde), rotated from the frame in which the device is fixed with the provided DCM.
+    /// The change in the device velocity due to a change in its location is neglected.
+    pub fn msr_sensitivity(
+        &self,
+        device: &str,
+        h_position: &DMatrix<f64>,
+        dcm: &Matrix3<f64>,
+    ) -> DMatrix<f64> {
+        let mut h_c = DMatrix::<f64>::zeros(h_position.nrows(), self.params.len());
+        for (idx, param) in self.params.iter().enumerate() {
+            if let ConsiderKind::DeviceLocation {
+                device: param_device,
+                axis,
+            } = &param.kind
+            {
+                // Axes other than 0, 1 and 2 are rejected by `ConsiderParam::device_location`
+                if param_device == device && *axis < 3 {
+                    let axis_vec = DVector::from_column_slice(dcm.column(*axis).as_slice());
+                    h_c.set_column(idx, &(-h_position * axis_vec));
+                }
+            }
+        }
+        h_c
+    }
+
+    /// Measurement update of the sensitivity with the gain of the filter, the sensitivity of the measurement to the
+    /// state `h_tilde` and to the consider parameters `h_c`: S = (I - K⋅H)⋅S - K⋅Hc.
+    pub(crate) fn update(
+        &mut self,
+        epoch: Epoch,
+        gain: &DMatrix<f64>,
+        h_tilde: &DMatrix<f64>,
+        h_c: &DMatrix<f64>,
+    ) {
+        self.sensitivity -= gain * (h_tilde * &self.sensitivity + h_c);
+        self.epoch = Some(epoch);
+    }
+}
+
+impl fmt::Display for ConsiderEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for param in &self.params {
+            writeln!(f, "{param}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod ut_consider {
+    use super::*;
+
+    #[test]
+    fn consider_sensitivity() {
+        let mut est = ConsiderEstimate::new(
+            vec![
+                ConsiderParam::grav_param(1.0),
+                ConsiderParam::state_component(6, 0.1),
+                ConsiderParam::device_location("DSS-65", 2, 1e-3).unwrap(),
+            ],
+            9,
+        );
+
+        assert_eq!(est.sensitivity[(6, 1)], 1.0);
+        let covar = DMatrix::<f64>::identity(9, 9) * 1e-6;
+        let consider_covar = est.consider_covar(&covar);
+        assert!((consider_covar[(6, 6)] - (1e-6 + 1e-2)).abs() < 1e-15);
+        assert_eq!(consider_covar[(0, 0)], 1e-6);
+        assert_eq!(est.cross_covar()[(6, 1)], 1e-2);
+
+        // With an identity STM, the GM only affects the velocity (and the position through the STM in a real propagation)
+        let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+        let r = Vector3::new(7000.0, 0.0, 0.0);
+        est.predict(epoch, 10.0, &DMatrix::identity(9, 9), Some((r, r)));
+        assert!((est.sensitivity[(3, 0)] + 10.0 / 7000.0_f64.powi(2)).abs() < 1e-18);
+        assert_eq!(est.sensitivity[(0, 0)], 0.0);
+
+        // Range sensitivity to the station location is opposite to the spacecraft position
+        let mut h_position = DMatrix::<f64>::zeros(2, 3);
+        h_position[(0, 2)] = 1.0;
+        let h_c = est.msr_sensitivity("DSS-65", &h_position, &Matrix3::identity());
+        assert_eq!(h_c[(0, 2)], -1.0);
+        assert_eq!(
+            est.msr_sensitivity("DSS-34", &h_position, &Matrix3::identity())
+                .norm(),
+            0.0
+        );
+
+        assert!(ConsiderParam::device_location("DSS-65", 3, 1e-3).is_err());
+
+        est.reset();
+        assert_eq!(est.sensitivity[(3, 0)], 0.0);
+        assert_eq!(est.sensitivity[(6, 1)], 1.0);
+    }
+}
@@ -52,6 +52,13 @@ where
     pub predicted: bool,
     /// The STM used to compute this Estimate
     pub stm: OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+    /// The UD factors of the covariance carried by the filter, i.e. the unit upper triangular U and the diagonal D such that P = U⋅D⋅U^T.
+    /// Only set if the filter uses the UD formulation of the covariance.
+    #[allow(clippy::type_complexity)]
+    pub ud_factors: Option<(
+        OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+        OVector<f64, <T as State>::Size>,
+    )>,
 }
 
 impl<T: State> KfEstimate<T>
@@ -76,6 +83,7 @@ where
             covar_bar: covar,
             predicted: true,
             stm: OMatrix::<f64, <T as State>::Size, <T as State>::Size>::identity(),
+            ud_factors: None,
         }
     }
 
@@ -89,6 +97,7 @@ where
             covar_bar: covar,
             predicted: true,
             stm: OMatrix::<f64, <T as State>::Size, <T as State>::Size>::identity(),
+            ud_factors: None,
         }
     }
 }
@@ -136,6 +145,7 @@ impl KfEstimate<Orbit> {
             covar_bar: covar,
             predicted: true,
             stm: OMatrix::<f64, U6, U6>::identity(),
+            ud_factors: None,
         }
     }
 }
@@ -158,6 +168,7 @@ where
             covar_bar: OMatrix::<f64, <T as State>::Size, <T as State>::Size>::zeros(),
             predicted: true,
             stm: OMatrix::<f64, <T as State>::Size, <T as State>::Size>::identity(),
+            ud_factors: None,
         }
     }
 
@@ -186,8 +197,10 @@ where
     fn set_state_deviation(&mut self, new_state: OVector<f64, <T as State>::Size>) {
         self.state_deviation = new_state;
     }
+    /// Sets the covariance, which clears the UD factors since they no longer match it
     fn set_covar(&mut self, new_covar: OMatrix<f64, <T as State>::Size, <T as State>::Size>) {
         self.covar = new_covar;
+        self.ud_factors = None;
     }
 }
 
@@ -28,6 +28,10 @@ pub mod residual;
 pub use residual::Residual;
 pub mod kfestimate;
 pub use kfestimate::KfEstimate;
+pub mod bias;
+pub use bias::{BiasEstimate, BiasKind, MsrBias};
+pub mod consider;
+pub use consider::{ConsiderEstimate, ConsiderKind, ConsiderParam};
 
 /// Stores an Estimate, as the result of a `time_update` or `measurement_update`.
 pub trait Estimate<T: State>
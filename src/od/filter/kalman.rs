@@ -18,13 +18,33 @@
 
 pub use crate::errors::NyxError;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector, U3};
-pub use crate::od::estimate::{Estimate, KfEstimate, Residual};
+use crate::linalg::{DMatrix, DVector, DefaultAllocator, DimName, OMatrix, OVector, Vector3, U3};
+use crate::md::StateParameter;
+pub use crate::od::estimate::{
+    BiasEstimate, ConsiderEstimate, ConsiderParam, Estimate, KfEstimate, MsrBias, Residual,
+};
+use crate::od::filter::ud::{bierman_update, mwgs, ud_factorize, ud_to_covar};
 pub use crate::od::snc::SNC;
 use crate::od::{Filter, ODDynamicsSnafu, ODError, State};
 pub use crate::time::{Epoch, Unit};
 use snafu::prelude::*;
 
+/// Formulation of the covariance of the Kalman filter
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CovarFormulation {
+    /// Conventional covariance, updated with the Joseph form after each measurement
+    Joseph,
+    /// UD factorized covariance (P = U⋅D⋅U^T), with Thornton's time update and Bierman's measurement update.
+    /// This formulation guarantees that the covariance remains symmetric positive definite, e.g. over long arcs with very precise Doppler.
+    UD,
+}
+
+impl Default for CovarFormulation {
+    fn default() -> Self {
+        Self::Joseph
+    }
+}
+
 /// Defines both a Classical and an Extended Kalman filter (CKF and EKF)
 /// T: Type of state
 /// A: Acceleration size (for SNC)
@@ -60,9 +80,19 @@ where
     /// Determines whether this KF should operate as a Conventional/Classical Kalman filter or an Extended Kalman Filter.
     /// Recall that one should switch to an Extended KF only once the estimate is good (i.e. after a few good measurement updates on a CKF).
     pub ekf: bool,
+    /// Measurement biases of the tracking devices augmented to the estimated state, if any
+    pub msr_biases: Option<BiasEstimate>,
+    /// Formulation of the covariance used in the time and measurement updates
+    pub covar_formulation: CovarFormulation,
+    /// Consider covariance analysis, if the filter has consider parameters
+    pub consider: Option<ConsiderEstimate>,
     h_tilde: OMatrix<f64, M, <T as State>::Size>,
     h_tilde_updated: bool,
     prev_used_snc: usize,
+    tracking_device: Option<String>,
+    h_consider: Option<DMatrix<f64>>,
+    /// A priori measurement biases, restored when the filter is restarted from a new estimate
+    msr_biases_apriori: Option<BiasEstimate>,
 }
 
 impl<T, A, M> KF<T, A, M>
@@ -107,9 +137,15 @@ where
             measurement_noise,
             process_noise: vec![process_noise],
             ekf: false,
+            msr_biases: None,
+            covar_formulation: CovarFormulation::default(),
+            consider: None,
             h_tilde: OMatrix::<f64, M, <T as State>::Size>::zeros(),
             h_tilde_updated: false,
             prev_used_snc: 0,
+            tracking_device: None,
+            h_consider: None,
+            msr_biases_apriori: None,
         }
     }
 
@@ -137,11 +173,257 @@ where
             measurement_noise,
             process_noise: process_noises,
             ekf: false,
+            msr_biases: None,
+            covar_formulation: CovarFormulation::default(),
+            consider: None,
             h_tilde: OMatrix::<f64, M, <T as State>::Size>::zeros(),
             h_tilde_updated: false,
             prev_used_snc: 0,
+            tracking_device: None,
+            h_consider: None,
+            msr_biases_apriori: None,
+        }
+    }
+
+    /// Augments the estimated state of this KF with the provided measurement biases of the tracking devices.
+    ///
+    /// Each bias starts with a zero mean and the bias sigma of its Gauss-Markov process as its standard deviation.
+    /// Solve-for biases are estimated with the state, whereas consider biases only inflate the covariance.
+    pub fn with_msr_biases(mut self, biases: Vec<MsrBias>) -> Self {
+        self.msr_biases = if biases.is_empty() {
+            None
+        } else {
+            Some(BiasEstimate::new(biases, <T as State>::Size::dim()))
+        };
+        self.msr_biases_apriori = self.msr_biases.clone();
+        self
+    }
+
+    /// Sets the formulation of the covariance used by this KF.
+    pub fn with_covar_formulation(mut self, covar_formulation: CovarFormulation) -> Self {
+        self.covar_formulation = covar_formulation;
+        self.prev_estimate.ud_factors = None;
+        self
+    }
+
+    /// Sets the parameters which are not estimated but whose uncertainty is considered in the consider covariance.
+    pub fn with_consider_params(mut self, params: Vec<ConsiderParam>) -> Self {
+        self.consider = Some(ConsiderEstimate::new(params, <T as State>::Size::dim()));
+        self
+    }
+
+    /// Time update of the sensitivity of the estimate to the consider parameters, if any.
+    fn consider_predict(
+        &mut self,
+        nominal_state: &T,
+        stm: &OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+    ) {
+        let prev_state = self.prev_estimate.nominal_state;
+        if let Some(consider) = self.consider.as_mut() {
+            let n = <T as State>::Size::dim();
+            let position = |state: &T| -> Option<Vector3<f64>> {
+                Some(Vector3::new(
+                    state.value(StateParameter::X).ok()?,
+                    state.value(StateParameter::Y).ok()?,
+                    state.value(StateParameter::Z).ok()?,
+                ))
+            };
+            let positions = match (position(&prev_state), position(nominal_state)) {
+                (Some(prev_r), Some(cur_r)) => Some((prev_r, cur_r)),
+                _ => None,
+            };
+            consider.predict(
+                nominal_state.epoch(),
+                (nominal_state.epoch() - prev_state.epoch()).to_seconds(),
+                &DMatrix::from_column_slice(n, n, stm.as_slice()),
+                positions,
+            );
         }
     }
+
+    /// Measurement update of the sensitivity of the estimate to the consider parameters with the gain of the filter, if any.
+    fn consider_update(&mut self, epoch: Epoch, gain: &DMatrix<f64>) {
+        let h_c = self.h_consider.take();
+        if let Some(consider) = self.consider.as_mut() {
+            let (n, m) = (<T as State>::Size::dim(), M::dim());
+            let h_c = h_c.unwrap_or_else(|| DMatrix::zeros(m, consider.params.len()));
+            consider.update(
+                epoch,
+                gain,
+                &DMatrix::from_column_slice(m, n, self.h_tilde.as_slice()),
+                &h_c,
+            );
+        }
+    }
+
+    /// Returns the UD factors of the covariance mapped with the provided STM and including the process noise, if any.
+    /// The factors of the previous estimate are computed from its covariance if they are not already known.
+    #[allow(clippy::type_complexity)]
+    fn ud_predict(
+        &self,
+        stm: &OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+        snc_terms: Option<(OMatrix<f64, <T as State>::Size, A>, OMatrix<f64, A, A>)>,
+    ) -> (
+        OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+        OVector<f64, <T as State>::Size>,
+    ) {
+        let n = <T as State>::Size::dim();
+        let (u, d) = match self.prev_estimate.ud_factors {
+            Some((u, d)) => (u, d),
+            None => ud_factorize(&self.prev_estimate.covar),
+        };
+
+        // The predicted covariance is W⋅diag(D, Dq)⋅W^T, where W = [Φ⋅U | Γ⋅Uq]
+        let num_q = if snc_terms.is_some() { A::dim() } else { 0 };
+        let mut w = DMatrix::<f64>::zeros(n, n + num_q);
+        let mut dw = DVector::<f64>::zeros(n + num_q);
+        w.view_mut((0, 0), (n, n))
+            .copy_from(&DMatrix::from_column_slice(n, n, (stm * u).as_slice()));
+        dw.rows_mut(0, n).copy_from_slice(d.as_slice());
+
+        if let Some((gamma, snc_matrix)) = snc_terms {
+            let (u_q, d_q) = ud_factorize(&snc_matrix);
+            w.view_mut((0, n), (n, num_q))
+                .copy_from(&DMatrix::from_column_slice(
+                    n,
+                    num_q,
+                    (gamma * u_q).as_slice(),
+                ));
+            dw.rows_mut(n, num_q).copy_from_slice(d_q.as_slice());
+        }
+
+        let (u_bar, d_bar) = mwgs(&w, &dw);
+        (
+            OMatrix::<f64, <T as State>::Size, <T as State>::Size>::from_iterator(
+                u_bar.iter().copied(),
+            ),
+            OVector::<f64, <T as State>::Size>::from_iterator(d_bar.iter().copied()),
+        )
+    }
+
+    /// Measurement update of the state augmented with the measurement biases.
+    #[allow(clippy::too_many_arguments)]
+    fn augmented_measurement_update(
+        &mut self,
+        mut biases: BiasEstimate,
+        nominal_state: T,
+        stm: OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+        covar_bar: OMatrix<f64, <T as State>::Size, <T as State>::Size>,
+        prefit: OVector<f64, M>,
+        computed_obs: &OVector<f64, M>,
+        ratio: f64,
+    ) -> Result<(KfEstimate<T>, Residual<M>), ODError> {
+        let epoch = nominal_state.epoch();
+        let n = <T as State>::Size::dim();
+        let m = M::dim();
+        let nb = biases.biases.len();
+
+        // Propagate the biases and their cross covariance to this epoch
+        let stm_d = DMatrix::from_column_slice(n, n, stm.as_slice());
+        biases.predict(
+            epoch,
+            (epoch - self.prev_estimate.epoch()).to_seconds(),
+            &stm_d,
+        );
+
+        // Build the augmented covariance, sensitivity matrix and a priori state
+        let mut covar_aug = DMatrix::<f64>::zeros(n + nb, n + nb);
+        covar_aug
+            .view_mut((0, 0), (n, n))
+            .copy_from(&DMatrix::from_column_slice(n, n, covar_bar.as_slice()));
+        covar_aug
+            .view_mut((0, n), (n, nb))
+            .copy_from(&biases.cross_covar);
+        covar_aug
+            .view_mut((n, 0), (nb, n))
+            .copy_from(&biases.cross_covar.transpose());
+        covar_aug
+            .view_mut((n, n), (nb, nb))
+            .copy_from(&biases.covar);
+
+        let computed_d = DVector::from_column_slice(computed_obs.as_slice());
+        let device = self.tracking_device.clone().unwrap_or_default();
+        let mut h_aug = DMatrix::<f64>::zeros(m, n + nb);
+        h_aug
+            .view_mut((0, 0), (m, n))
+            .copy_from(&DMatrix::from_column_slice(m, n, self.h_tilde.as_slice()));
+        h_aug
+            .view_mut((0, n), (m, nb))
+            .copy_from(&biases.sensitivity(&device, m, &computed_d));
+
+        let mut state_bar = DVector::<f64>::zeros(n + nb);
+        if !self.ekf {
+            let dev_bar = stm * self.prev_estimate.state_deviation;
+            state_bar.rows_mut(0, n).copy_from_slice(dev_bar.as_slice());
+        }
+        state_bar.rows_mut(n, nb).copy_from(&biases.values);
+
+        // Compute the Kalman gain, where the rows of the consider biases are zeroed out
+        let prefit_d = DVector::from_column_slice(prefit.as_slice());
+        let r_d = DMatrix::from_column_slice(m, m, self.measurement_noise.as_slice());
+        let invertible_part = match (&h_aug * &covar_aug * h_aug.transpose() + &r_d).try_inverse() {
+            Some(inv) => inv,
+            None => return Err(ODError::SingularKalmanGain),
+        };
+        let mut gain = &covar_aug * h_aug.transpose() * invertible_part;
+        for (idx, bias) in biases.biases.iter().enumerate() {
+            if bias.consider {
+                gain.row_mut(n + idx).fill(0.0);
+            }
+        }
+
+        let innovation = &prefit_d - &h_aug * &state_bar;
+        let state_hat = &state_bar + &gain * &innovation;
+        let postfit_d = if self.ekf {
+            &prefit_d - &h_aug * &state_hat
+        } else {
+            innovation
+        };
+
+        // Compute covariance (Joseph update), valid for any gain including the consider one
+        let first_term = DMatrix::<f64>::identity(n + nb, n + nb) - &gain * &h_aug;
+        let covar =
+            &first_term * &covar_aug * first_term.transpose() + &gain * &r_d * gain.transpose();
+
+        // Split the augmented estimate
+        biases.values = state_hat.rows(n, nb).into_owned();
+        biases.covar = covar.view((n, n), (nb, nb)).into_owned();
+        biases.cross_covar = covar.view((0, n), (n, nb)).into_owned();
+
+        let covar = OMatrix::<f64, <T as State>::Size, <T as State>::Size>::from_iterator(
+            covar.view((0, 0), (n, n)).iter().copied(),
+        );
+        // The Joseph update of the augmented covariance does not update the UD factors, so those of the updated covariance are carried on
+        let ud_factors = if self.covar_formulation == CovarFormulation::UD {
+            Some(ud_factorize(&covar))
+        } else {
+            None
+        };
+
+        let estimate = KfEstimate {
+            nominal_state,
+            state_deviation: OVector::<f64, <T as State>::Size>::from_iterator(
+                state_hat.rows(0, n).iter().copied(),
+            ),
+            covar,
+            covar_bar,
+            stm,
+            predicted: false,
+            ud_factors,
+        };
+
+        let postfit = OVector::<f64, M>::from_iterator(postfit_d.iter().copied());
+
+        self.consider_update(epoch, &gain.rows(0, n).into_owned());
+        self.msr_biases = Some(biases);
+        self.h_tilde_updated = false;
+        self.prev_estimate = estimate;
+        // Update the prev epoch for all SNCs
+        for snc in &mut self.process_noise {
+            snc.prev_epoch = Some(self.prev_estimate.epoch());
+        }
+        Ok((estimate, Residual::new(epoch, prefit, postfit, ratio)))
+    }
 }
 
 impl<T, M> KF<T, U3, M>
@@ -170,9 +452,15 @@ where
             measurement_noise,
             process_noise: Vec::new(),
             ekf: false,
+            msr_biases: None,
+            covar_formulation: CovarFormulation::default(),
+            consider: None,
             h_tilde: OMatrix::<f64, M, <T as State>::Size>::zeros(),
             h_tilde_updated: false,
             prev_used_snc: 0,
+            tracking_device: None,
+            h_consider: None,
+            msr_biases_apriori: None,
         }
     }
 }
@@ -212,6 +500,13 @@ where
 
     fn set_previous_estimate(&mut self, est: &Self::Estimate) {
         self.prev_estimate = *est;
+        if let Some(consider) = self.consider.as_mut() {
+            consider.reset();
+        }
+        // The biases restart from their a priori values and covariance, uncorrelated with the new estimate
+        if let Some(biases) = self.msr_biases_apriori.as_ref() {
+            self.msr_biases = Some(biases.clone());
+        }
     }
 
     /// Update the sensitivity matrix (or "H tilde"). This function **must** be called prior to each
@@ -227,6 +522,7 @@ where
     fn time_update(&mut self, nominal_state: T) -> Result<Self::Estimate, ODError> {
         let stm = nominal_state.stm().with_context(|_| ODDynamicsSnafu)?;
         let mut covar_bar = stm * self.prev_estimate.covar * stm.transpose();
+        let mut snc_terms = None;
 
         // Try to apply an SNC, if applicable
         for (i, snc) in self.process_noise.iter().enumerate().rev() {
@@ -265,17 +561,38 @@ where
                     }
                 }
                 // Let's add the process noise
-                covar_bar += &gamma * snc_matrix * &gamma.transpose();
+                covar_bar += &gamma * &snc_matrix * gamma.transpose();
+                snc_terms = Some((gamma, snc_matrix));
                 // And break so we don't add any more process noise
                 break;
             }
         }
 
+        let ud_factors = if self.covar_formulation == CovarFormulation::UD {
+            let (u, d) = self.ud_predict(&stm, snc_terms);
+            covar_bar = ud_to_covar(&u, &d);
+            Some((u, d))
+        } else {
+            None
+        };
+
         let state_bar = if self.ekf {
             OVector::<f64, <T as State>::Size>::zeros()
         } else {
             stm * self.prev_estimate.state_deviation
         };
+
+        self.consider_predict(&nominal_state, &stm);
+
+        if let Some(biases) = self.msr_biases.as_mut() {
+            let n = <T as State>::Size::dim();
+            biases.predict(
+                nominal_state.epoch(),
+                (nominal_state.epoch() - self.prev_estimate.epoch()).to_seconds(),
+                &DMatrix::from_column_slice(n, n, stm.as_slice()),
+            );
+        }
+
         let estimate = KfEstimate {
             nominal_state,
             state_deviation: state_bar,
@@ -283,6 +600,7 @@ where
             covar_bar,
             stm,
             predicted: true,
+            ud_factors,
         };
         self.prev_estimate = estimate;
         // Update the prev epoch for all SNCs
@@ -311,6 +629,7 @@ where
         let epoch = nominal_state.epoch();
 
         let mut covar_bar = stm * self.prev_estimate.covar * stm.transpose();
+        let mut snc_terms = None;
         let mut snc_used = false;
         // Try to apply an SNC, if applicable
         for (i, snc) in self.process_noise.iter().enumerate().rev() {
@@ -349,7 +668,8 @@ where
                     }
                 }
                 // Let's add the process noise
-                covar_bar += &gamma * snc_matrix * &gamma.transpose();
+                covar_bar += &gamma * &snc_matrix * gamma.transpose();
+                snc_terms = Some((gamma, snc_matrix));
                 snc_used = true;
                 // And break so we don't add any more process noise
                 break;
@@ -360,14 +680,34 @@ where
             debug!("@{} No SNC", epoch);
         }
 
+        let ud_bar = if self.covar_formulation == CovarFormulation::UD {
+            let (u, d) = self.ud_predict(&stm, snc_terms);
+            covar_bar = ud_to_covar(&u, &d);
+            Some((u, d))
+        } else {
+            None
+        };
+
         let h_tilde_t = &self.h_tilde.transpose();
         let h_p_ht = &self.h_tilde * covar_bar * h_tilde_t;
 
         // Compute observation deviation (usually marked as y_i)
         let prefit = real_obs - computed_obs;
 
-        // Compute the prefit ratio
-        let ratio_mat = prefit.transpose() * &h_p_ht * &prefit;
+        // Compute the prefit ratio, once the estimated measurement biases are removed from the prefit residual
+        let ratio_mat = match self.msr_biases.as_ref() {
+            Some(biases) => {
+                let offset = biases.predicted_offset(
+                    self.tracking_device.as_deref().unwrap_or_default(),
+                    (epoch - self.prev_estimate.epoch()).to_seconds(),
+                    M::dim(),
+                    &DVector::from_column_slice(computed_obs.as_slice()),
+                );
+                let unbiased = &prefit - OVector::<f64, M>::from_column_slice(offset.as_slice());
+                unbiased.transpose() * &h_p_ht * &unbiased
+            }
+            None => prefit.transpose() * &h_p_ht * &prefit,
+        };
         let ratio = ratio_mat[0];
 
         if let Some(ratio_thresh) = resid_ratio_check {
@@ -381,6 +721,82 @@ where
             }
         }
 
+        self.consider_predict(&nominal_state, &stm);
+
+        if let Some(biases) = self.msr_biases.take() {
+            return self.augmented_measurement_update(
+                biases,
+                nominal_state,
+                stm,
+                covar_bar,
+                prefit,
+                computed_obs,
+                ratio,
+            );
+        }
+
+        if let Some((mut u, mut d)) = ud_bar {
+            // Whiten the measurements so that they are processed as uncorrelated scalar measurements of unit variance
+            let mut whitening = match self.measurement_noise.clone().cholesky() {
+                Some(cholesky) => cholesky.l(),
+                None => return Err(ODError::SingularKalmanGain),
+            };
+            if !whitening.try_inverse_mut() {
+                return Err(ODError::SingularKalmanGain);
+            }
+            let h_white = &whitening * &self.h_tilde;
+            let prefit_white = &whitening * &prefit;
+
+            let state_bar = if self.ekf {
+                OVector::<f64, <T as State>::Size>::zeros()
+            } else {
+                stm * self.prev_estimate.state_deviation
+            };
+
+            let mut state_hat = state_bar;
+            for i in 0..M::dim() {
+                let h_row = h_white.row(i).transpose();
+                let gain = bierman_update(&mut u, &mut d, &h_row, 1.0);
+                let innovation = prefit_white[i] - h_row.dot(&state_hat);
+                state_hat += gain * innovation;
+            }
+
+            let postfit = if self.ekf {
+                &prefit - (&self.h_tilde * state_hat)
+            } else {
+                &prefit - (&self.h_tilde * state_bar)
+            };
+
+            let covar = ud_to_covar(&u, &d);
+            if self.consider.is_some() {
+                // The gain of the full measurement is P⋅H^T⋅R^-1, where R^-1 = W^T⋅W
+                let gain = covar * h_white.transpose() * &whitening;
+                let n = <T as State>::Size::dim();
+                self.consider_update(
+                    epoch,
+                    &DMatrix::from_column_slice(n, M::dim(), gain.as_slice()),
+                );
+            }
+
+            let estimate = KfEstimate {
+                nominal_state,
+                state_deviation: state_hat,
+                covar,
+                covar_bar,
+                stm,
+                predicted: false,
+                ud_factors: Some((u, d)),
+            };
+
+            self.h_tilde_updated = false;
+            self.prev_estimate = estimate;
+            // Update the prev epoch for all SNCs
+            for snc in &mut self.process_noise {
+                snc.prev_epoch = Some(self.prev_estimate.epoch());
+            }
+            return Ok((estimate, Residual::new(epoch, prefit, postfit, ratio)));
+        }
+
         // Compute the Kalman gain but first adding the measurement noise to H⋅P⋅H^T
         let mut invertible_part = h_p_ht + &self.measurement_noise;
         if !invertible_part.try_inverse_mut() {
@@ -410,6 +826,14 @@ where
         let covar = first_term * covar_bar * first_term.transpose()
             + &gain * &self.measurement_noise * &gain.transpose();
 
+        if self.consider.is_some() {
+            let n = <T as State>::Size::dim();
+            self.consider_update(
+                epoch,
+                &DMatrix::from_column_slice(n, M::dim(), gain.as_slice()),
+            );
+        }
+
         // And wrap up
         let estimate = KfEstimate {
             nominal_state,
@@ -418,6 +842,7 @@ where
             covar_bar,
             stm,
             predicted: false,
+            ud_factors: None,
         };
 
         self.h_tilde_updated = false;
@@ -441,4 +866,20 @@ where
     fn set_process_noise(&mut self, snc: SNC<A>) {
         self.process_noise = vec![snc];
     }
+
+    fn set_tracking_device(&mut self, device: &str) {
+        self.tracking_device = Some(device.to_string());
+    }
+
+    fn bias_estimate(&self) -> Option<&BiasEstimate> {
+        self.msr_biases.as_ref()
+    }
+
+    fn consider_estimate(&self) -> Option<&ConsiderEstimate> {
+        self.consider.as_ref()
+    }
+
+    fn update_consider_sensitivity(&mut self, h_c: DMatrix<f64>) {
+        self.h_consider = Some(h_c);
+    }
 }
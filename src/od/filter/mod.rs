@@ -18,15 +18,17 @@
 
 use self::kalman::Residual;
 
-use super::estimate::Estimate;
+use super::estimate::{BiasEstimate, ConsiderEstimate, Estimate};
 use super::snc::SNC;
 use super::ODError;
 pub use crate::dynamics::Dynamics;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector};
+use crate::linalg::{DMatrix, DefaultAllocator, DimName, OMatrix, OVector};
 use crate::time::Epoch;
 pub use crate::{cosmic::Cosm, State, TimeTagged};
 pub mod kalman;
+pub mod ud;
+pub mod unscented;
 
 /// Defines a Filter trait where S is the size of the estimated state, A the number of acceleration components of the EOMs (used for process noise matrix size), M the size of the measurements.
 pub trait Filter<T, A, M>
@@ -95,4 +97,29 @@ where
 
     /// Returns the measurement noise used at this given epoch
     fn measurement_noise(&self, epoch: Epoch) -> &OMatrix<f64, M, M>;
+
+    /// Sets the name of the tracking device of the next measurement update, used to select its measurement biases.
+    fn set_tracking_device(&mut self, _device: &str) {}
+
+    /// Returns the estimate of the measurement biases augmented to the state, if any.
+    fn bias_estimate(&self) -> Option<&BiasEstimate> {
+        None
+    }
+
+    /// Returns the consider covariance analysis of the filter, if it has consider parameters.
+    fn consider_estimate(&self) -> Option<&ConsiderEstimate> {
+        None
+    }
+
+    /// Sets the sensitivity of the next measurement to the consider parameters, which is zero if not called before the measurement update.
+    fn update_consider_sensitivity(&mut self, _h_c: DMatrix<f64>) {}
+
+    /// Returns the sigma points at the epoch of the provided nominal state, if this filter relies on them (e.g. an unscented filter).
+    fn sigma_points(&mut self, _nominal_state: T) -> Result<Option<Vec<T>>, ODError> {
+        Ok(None)
+    }
+
+    /// Sets the computed observation of each sigma point (None if that sigma point is not visible). This should be called prior to each
+    /// call to `measurement_update` of filters which rely on sigma points.
+    fn update_sigma_observations(&mut self, _obs: Vec<Option<OVector<f64, M>>>) {}
 }
@@ -0,0 +1,195 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+//! UD factorization of the covariance, P = U⋅D⋅U^T where U is unit upper triangular and D is diagonal.
+//!
+//! The time update uses Thornton's modified weighted Gram-Schmidt orthogonalization and the measurement update
+//! uses Bierman's scalar update, cf. Bierman, "Factorization Methods for Discrete Sequential Estimation" (1977).
+//! Both guarantee that the diagonal D remains non-negative, so the covariance remains symmetric positive semi-definite.
+
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DMatrix, DVector, DefaultAllocator, DimName, OMatrix, OVector};
+
+/// Computes the UD factors of the provided symmetric covariance.
+/// Rows and columns with zero variance lead to a zero entry in D and the corresponding column of U is the identity.
+pub fn ud_factorize<N: DimName>(covar: &OMatrix<f64, N, N>) -> (OMatrix<f64, N, N>, OVector<f64, N>)
+where
+    DefaultAllocator: Allocator<f64, N> + Allocator<f64, N, N>,
+{
+    let n = N::dim();
+    let mut u = OMatrix::<f64, N, N>::identity();
+    let mut d = OVector::<f64, N>::zeros();
+
+    for j in (0..n).rev() {
+        let mut d_j = covar[(j, j)];
+        for k in j + 1..n {
+            d_j -= d[k] * u[(j, k)].powi(2);
+        }
+        d[j] = d_j.max(0.0);
+
+        if d[j] > 0.0 {
+            for i in 0..j {
+                let mut p_ij = covar[(i, j)];
+                for k in j + 1..n {
+                    p_ij -= d[k] * u[(i, k)] * u[(j, k)];
+                }
+                u[(i, j)] = p_ij / d[j];
+            }
+        }
+    }
+
+    (u, d)
+}
+
+/// Returns the covariance U⋅D⋅U^T of the provided factors, which is symmetric by construction.
+pub fn ud_to_covar<N: DimName>(u: &OMatrix<f64, N, N>, d: &OVector<f64, N>) -> OMatrix<f64, N, N>
+where
+    DefaultAllocator: Allocator<f64, N> + Allocator<f64, N, N>,
+{
+    let n = N::dim();
+    let mut covar = OMatrix::<f64, N, N>::zeros();
+    for i in 0..n {
+        for j in i..n {
+            // U is upper triangular, so only the columns k >= max(i, j) contribute
+            let mut p_ij = 0.0;
+            for k in j..n {
+                p_ij += u[(i, k)] * d[k] * u[(j, k)];
+            }
+            covar[(i, j)] = p_ij;
+            covar[(j, i)] = p_ij;
+        }
+    }
+    covar
+}
+
+/// Thornton's modified weighted Gram-Schmidt orthogonalization: returns the UD factors of W⋅diag(dw)⋅W^T.
+pub(crate) fn mwgs(w: &DMatrix<f64>, dw: &DVector<f64>) -> (DMatrix<f64>, DVector<f64>) {
+    let n = w.nrows();
+    let mut w = w.clone();
+    let mut u = DMatrix::<f64>::identity(n, n);
+    let mut d = DVector::<f64>::zeros(n);
+
+    for j in (0..n).rev() {
+        let d_j = w
+            .row(j)
+            .iter()
+            .zip(dw.iter())
+            .map(|(w_jk, dw_k)| dw_k * w_jk.powi(2))
+            .sum::<f64>();
+        d[j] = d_j;
+
+        if d_j > 0.0 {
+            for i in 0..j {
+                let u_ij = (0..w.ncols())
+                    .map(|k| dw[k] * w[(i, k)] * w[(j, k)])
+                    .sum::<f64>()
+                    / d_j;
+                u[(i, j)] = u_ij;
+                for k in 0..w.ncols() {
+                    let w_jk = w[(j, k)];
+                    w[(i, k)] -= u_ij * w_jk;
+                }
+            }
+        }
+    }
+
+    (u, d)
+}
+
+/// Bierman's scalar measurement update of the UD factors, for a scalar measurement of sensitivity `h` and variance `r`.
+/// The factors are updated in place and the Kalman gain is returned.
+pub(crate) fn bierman_update<N: DimName>(
+    u: &mut OMatrix<f64, N, N>,
+    d: &mut OVector<f64, N>,
+    h: &OVector<f64, N>,
+    r: f64,
+) -> OVector<f64, N>
+where
+    DefaultAllocator: Allocator<f64, N> + Allocator<f64, N, N>,
+{
+    let n = N::dim();
+    let f = u.transpose() * h;
+    let v = d.component_mul(&f);
+
+    let mut gain = OVector::<f64, N>::zeros();
+    let mut alpha = r + v[0] * f[0];
+    d[0] *= r / alpha;
+    gain[0] = v[0];
+
+    for j in 1..n {
+        let beta = alpha;
+        alpha += f[j] * v[j];
+        let lambda = -f[j] / beta;
+        d[j] *= beta / alpha;
+        for i in 0..j {
+            let u_ij = u[(i, j)];
+            u[(i, j)] = u_ij + lambda * gain[i];
+            gain[i] += v[j] * u_ij;
+        }
+        gain[j] = v[j];
+    }
+
+    gain / alpha
+}
+
+#[cfg(test)]
+mod ut_ud {
+    use super::*;
+    use crate::linalg::{Matrix3, Vector3};
+
+    #[test]
+    fn ud_roundtrip_and_updates() {
+        let covar = Matrix3::new(4.0, 1.0, 0.5, 1.0, 3.0, 0.2, 0.5, 0.2, 2.0);
+        let (u, d) = ud_factorize(&covar);
+        assert!((ud_to_covar(&u, &d) - covar).norm() < 1e-12);
+        for i in 0..3 {
+            assert_eq!(u[(i, i)], 1.0);
+            assert!(d[i] > 0.0);
+        }
+
+        // MWGS of [U | I] with diag(D, q) is the factorization of P + q I
+        let mut w = DMatrix::<f64>::zeros(3, 6);
+        let mut dw = DVector::<f64>::zeros(6);
+        for i in 0..3 {
+            for j in 0..3 {
+                w[(i, j)] = u[(i, j)];
+            }
+            w[(i, i + 3)] = 1.0;
+            dw[i] = d[i];
+            dw[i + 3] = 0.1;
+        }
+        let (u_bar, d_bar) = mwgs(&w, &dw);
+        let covar_bar = Matrix3::from_iterator(
+            (&u_bar * DMatrix::from_diagonal(&d_bar) * u_bar.transpose())
+                .iter()
+                .copied(),
+        );
+        assert!((covar_bar - (covar + Matrix3::identity() * 0.1)).norm() < 1e-12);
+
+        // Bierman update matches the conventional Kalman update
+        let h = Vector3::new(1.0, -0.5, 0.25);
+        let r = 0.3;
+        let (mut u_up, mut d_up) = (u, d);
+        let gain = bierman_update(&mut u_up, &mut d_up, &h, r);
+
+        let expected_gain = covar * h / ((h.transpose() * covar * h)[0] + r);
+        let expected_covar = covar - expected_gain * h.transpose() * covar;
+        assert!((gain - expected_gain).norm() < 1e-12);
+        assert!((ud_to_covar(&u_up, &d_up) - expected_covar).norm() < 1e-12);
+    }
+}
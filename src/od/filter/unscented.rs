@@ -0,0 +1,600 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::dynamics::Dynamics;
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector, U3};
+use crate::od::estimate::{Estimate, KfEstimate, Residual};
+use crate::od::snc::SNC;
+use crate::od::{Filter, ODError, ODPropSnafu, State};
+use crate::propagators::error_ctrl::ErrorCtrl;
+use crate::propagators::Propagator;
+use crate::time::Epoch;
+use snafu::prelude::*;
+
+/// Defines an Unscented Kalman Filter (UKF).
+///
+/// At each measurement update, the sigma points are propagated from the last measurement update through the same propagator (and therefore dynamics)
+/// as the nominal trajectory of the orbit determination process. Between measurements, the time update maps the covariance with the STM if the
+/// nominal state carries one, and otherwise propagates the sigma points. When used in an `ODProcess`, the computed observations of the sigma points
+/// are provided by the tracking device; otherwise they are linearized around the nominal observation with the sensitivity matrix.
+///
+/// D: Dynamics of the estimated state
+/// E: Error control of the propagator
+/// A: Acceleration size (for SNC)
+/// M: Measurement size (used for the sensitivity matrix)
+#[allow(clippy::upper_case_acronyms)]
+pub struct UKF<'a, D, E, A, M>
+where
+    D: Dynamics,
+    E: ErrorCtrl,
+    A: DimName,
+    M: DimName,
+    DefaultAllocator: Allocator<f64, M>
+        + Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, A>
+        + Allocator<f64, M, M>
+        + Allocator<f64, M, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, A, A>
+        + Allocator<f64, <D::StateType as State>::Size, A>
+        + Allocator<f64, A, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+    <DefaultAllocator as Allocator<f64, <D::StateType as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<
+        f64,
+        <D::StateType as State>::Size,
+        <D::StateType as State>::Size,
+    >>::Buffer: Copy,
+{
+    /// The previous estimate used in the UKF computations.
+    pub prev_estimate: KfEstimate<D::StateType>,
+    /// Sets the Measurement noise (usually noted R)
+    pub measurement_noise: OMatrix<f64, M, M>,
+    /// A sets of process noise (usually noted Q), must be ordered chronologically
+    pub process_noise: Vec<SNC<A>>,
+    /// Spread of the sigma points around the mean, usually between 1e-4 and 1 (defaults to 1)
+    pub alpha: f64,
+    /// Prior knowledge of the distribution of the state, optimal at 2 for Gaussian distributions (default)
+    pub beta: f64,
+    /// Secondary scaling parameter (defaults to 0)
+    pub kappa: f64,
+    prop: &'a Propagator<'a, D, E>,
+    extended: bool,
+    h_tilde: OMatrix<f64, M, <D::StateType as State>::Size>,
+    h_tilde_updated: bool,
+    sigma_anchor: KfEstimate<D::StateType>,
+    sigma_cache: Option<(Epoch, Vec<D::StateType>)>,
+    sigma_obs: Option<Vec<Option<OVector<f64, M>>>>,
+    prev_used_snc: usize,
+}
+
+impl<'a, D, E, A, M> UKF<'a, D, E, A, M>
+where
+    D: Dynamics,
+    E: ErrorCtrl,
+    A: DimName,
+    M: DimName,
+    DefaultAllocator: Allocator<f64, M>
+        + Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, A>
+        + Allocator<f64, M, M>
+        + Allocator<f64, M, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, M>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, A, A>
+        + Allocator<f64, <D::StateType as State>::Size, A>
+        + Allocator<f64, A, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+    <DefaultAllocator as Allocator<f64, <D::StateType as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<
+        f64,
+        <D::StateType as State>::Size,
+        <D::StateType as State>::Size,
+    >>::Buffer: Copy,
+{
+    /// Initializes this UKF with the propagator of the sigma points, an initial estimate, measurement noise, and one process noise
+    pub fn new(
+        prop: &'a Propagator<'a, D, E>,
+        initial_estimate: KfEstimate<D::StateType>,
+        process_noise: SNC<A>,
+        measurement_noise: OMatrix<f64, M, M>,
+    ) -> Self {
+        Self::with_sncs(
+            prop,
+            initial_estimate,
+            vec![process_noise],
+            measurement_noise,
+        )
+    }
+
+    /// Initializes this UKF with the propagator of the sigma points, an initial estimate, measurement noise, and several process noise
+    /// WARNING: SNCs MUST be ordered chronologically! They will be selected automatically by walking
+    /// the list of SNCs backward until one can be applied!
+    pub fn with_sncs(
+        prop: &'a Propagator<'a, D, E>,
+        initial_estimate: KfEstimate<D::StateType>,
+        process_noises: Vec<SNC<A>>,
+        measurement_noise: OMatrix<f64, M, M>,
+    ) -> Self {
+        assert_eq!(
+            A::dim() % 3,
+            0,
+            "SNC can only be applied to accelerations multiple of 3"
+        );
+        let mut process_noises = process_noises;
+        // Set the initial epoch of the SNC
+        for snc in &mut process_noises {
+            snc.init_epoch = Some(initial_estimate.epoch());
+        }
+
+        Self {
+            prev_estimate: initial_estimate,
+            sigma_anchor: initial_estimate,
+            measurement_noise,
+            process_noise: process_noises,
+            alpha: 1.0,
+            beta: 2.0,
+            kappa: 0.0,
+            prop,
+            extended: false,
+            h_tilde: OMatrix::<f64, M, <D::StateType as State>::Size>::zeros(),
+            h_tilde_updated: false,
+            sigma_cache: None,
+            sigma_obs: None,
+            prev_used_snc: 0,
+        }
+    }
+
+    /// Sets the scaling parameters of the unscented transform
+    pub fn with_scaling(mut self, alpha: f64, beta: f64, kappa: f64) -> Self {
+        self.alpha = alpha;
+        self.beta = beta;
+        self.kappa = kappa;
+        self
+    }
+
+    /// Returns the scaling λ, the weights of the central sigma point for the mean and for the covariance, and the weight of all other sigma points.
+    fn weights(&self) -> (f64, f64, f64, f64) {
+        let n = <D::StateType as State>::Size::dim() as f64;
+        let lambda = self.alpha.powi(2) * (n + self.kappa) - n;
+        let wm0 = lambda / (n + lambda);
+        let wc0 = wm0 + 1.0 - self.alpha.powi(2) + self.beta;
+        let wi = 1.0 / (2.0 * (n + lambda));
+        (lambda, wm0, wc0, wi)
+    }
+
+    /// Returns the estimated part of the state vector (i.e. without the STM)
+    fn state_vector(state: &D::StateType) -> OVector<f64, <D::StateType as State>::Size> {
+        OVector::<f64, <D::StateType as State>::Size>::from_iterator(
+            state
+                .as_vector()
+                .iter()
+                .take(<D::StateType as State>::Size::dim())
+                .copied(),
+        )
+    }
+
+    /// Builds the sigma points of the last measurement update (or of the initial estimate) and propagates them to the provided epoch.
+    /// The sigma points are cached until that estimate changes.
+    fn propagate_sigma_points(&mut self, epoch: Epoch) -> Result<Vec<D::StateType>, ODError> {
+        if let Some((cache_epoch, points)) = &self.sigma_cache {
+            if *cache_epoch == epoch {
+                return Ok(points.clone());
+            }
+        }
+
+        let n = <D::StateType as State>::Size::dim();
+        let (lambda, _, _, _) = self.weights();
+
+        // Parameters with a zero variance (e.g. fixed coefficients) are kept quasi constant
+        let mut scaled_covar = self.sigma_anchor.covar * (n as f64 + lambda);
+        for i in 0..n {
+            if scaled_covar[(i, i)] <= 0.0 {
+                scaled_covar[(i, i)] = f64::EPSILON.powi(2);
+            }
+        }
+
+        let sqrt_covar = match scaled_covar.cholesky() {
+            Some(cholesky) => cholesky.l(),
+            None => return Err(ODError::CovarianceNotPositiveDefinite),
+        };
+
+        let mut mean = self.sigma_anchor.state();
+        mean.unset_stm();
+
+        let mut points = Vec::with_capacity(2 * n + 1);
+        points.push(mean);
+        for sign in [1.0, -1.0] {
+            for i in 0..n {
+                points.push(mean.add(sqrt_covar.column(i) * sign));
+            }
+        }
+
+        let mut propagated = Vec::with_capacity(points.len());
+        for point in points {
+            if point.epoch() == epoch {
+                propagated.push(point);
+            } else {
+                propagated.push(
+                    self.prop
+                        .with(point)
+                        .until_epoch(epoch)
+                        .with_context(|_| ODPropSnafu)?,
+                );
+            }
+        }
+
+        self.sigma_cache = Some((epoch, propagated.clone()));
+        Ok(propagated)
+    }
+
+    /// Computes the unscented prediction at the epoch of the nominal state.
+    /// Returns the deviations of the sigma points from the nominal state, the mean deviation, and the predicted covariance (including process noise).
+    #[allow(clippy::type_complexity)]
+    fn predict(
+        &mut self,
+        nominal_state: D::StateType,
+    ) -> Result<
+        (
+            Vec<OVector<f64, <D::StateType as State>::Size>>,
+            OVector<f64, <D::StateType as State>::Size>,
+            OMatrix<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+        ),
+        ODError,
+    > {
+        let epoch = nominal_state.epoch();
+        let points = self.propagate_sigma_points(epoch)?;
+        let (_, wm0, wc0, wi) = self.weights();
+
+        let nominal_vec = Self::state_vector(&nominal_state);
+        let deviations = points
+            .iter()
+            .map(|point| Self::state_vector(point) - nominal_vec)
+            .collect::<Vec<_>>();
+
+        let mut mean = OVector::<f64, <D::StateType as State>::Size>::zeros();
+        for (i, dev) in deviations.iter().enumerate() {
+            mean += dev * if i == 0 { wm0 } else { wi };
+        }
+
+        let mut covar_bar =
+            OMatrix::<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>::zeros();
+        for (i, dev) in deviations.iter().enumerate() {
+            let delta = dev - mean;
+            covar_bar += &delta * delta.transpose() * if i == 0 { wc0 } else { wi };
+        }
+
+        if let Some(snc_covar) = self.process_noise_covar(epoch, self.sigma_anchor.epoch()) {
+            covar_bar += snc_covar;
+        }
+
+        Ok((deviations, mean, covar_bar))
+    }
+
+    /// Returns the process noise covariance of the applicable SNC, if any, accumulated between `since` and `epoch`.
+    fn process_noise_covar(
+        &mut self,
+        epoch: Epoch,
+        since: Epoch,
+    ) -> Option<OMatrix<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>> {
+        // Try to apply an SNC, if applicable
+        for (i, snc) in self.process_noise.iter().enumerate().rev() {
+            if let Some(snc_matrix) = snc.to_matrix(epoch) {
+                // Check if we're using another SNC than the one before
+                if self.prev_used_snc != i {
+                    info!("Switched to {}-th {}", i, snc);
+                    self.prev_used_snc = i;
+                }
+
+                // Let's compute the Gamma matrix, an approximation of the time integral
+                // which assumes that the acceleration is constant between these two measurements.
+                let mut gamma = OMatrix::<f64, <D::StateType as State>::Size, A>::zeros();
+                let delta_t = (epoch - since).to_seconds();
+                for blk in 0..A::dim() / 3 {
+                    for i in 0..3 {
+                        let idx_i = i + A::dim() * blk;
+                        let idx_j = i + 3 * blk;
+                        let idx_k = i + 3 + A::dim() * blk;
+                        gamma[(idx_i, idx_j)] = delta_t.powi(2) / 2.0;
+                        gamma[(idx_k, idx_j)] = delta_t;
+                    }
+                }
+                // Only the first applicable SNC is used
+                return Some(&gamma * snc_matrix * &gamma.transpose());
+            }
+        }
+
+        None
+    }
+
+    /// Stores the new estimate, from which the next sigma points are drawn, and updates the epoch of the SNCs.
+    fn store_estimate(&mut self, estimate: KfEstimate<D::StateType>) {
+        self.prev_estimate = estimate;
+        self.sigma_anchor = estimate;
+        self.sigma_cache = None;
+        // Update the prev epoch for all SNCs
+        for snc in &mut self.process_noise {
+            snc.prev_epoch = Some(self.prev_estimate.epoch());
+        }
+    }
+}
+
+impl<'a, D, E, M> UKF<'a, D, E, U3, M>
+where
+    D: Dynamics,
+    E: ErrorCtrl,
+    M: DimName,
+    DefaultAllocator: Allocator<f64, M>
+        + Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, M, M>
+        + Allocator<f64, M, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, M>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, U3, U3>
+        + Allocator<f64, <D::StateType as State>::Size, U3>
+        + Allocator<f64, U3, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>,
+    <DefaultAllocator as Allocator<f64, <D::StateType as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<
+        f64,
+        <D::StateType as State>::Size,
+        <D::StateType as State>::Size,
+    >>::Buffer: Copy,
+{
+    /// Initializes this UKF without SNC
+    pub fn no_snc(
+        prop: &'a Propagator<'a, D, E>,
+        initial_estimate: KfEstimate<D::StateType>,
+        measurement_noise: OMatrix<f64, M, M>,
+    ) -> Self {
+        Self::with_sncs(prop, initial_estimate, Vec::new(), measurement_noise)
+    }
+}
+
+impl<'a, D, E, A, M> Filter<D::StateType, A, M> for UKF<'a, D, E, A, M>
+where
+    D: Dynamics,
+    E: ErrorCtrl,
+    A: DimName,
+    M: DimName,
+    DefaultAllocator: Allocator<f64, M>
+        + Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, A>
+        + Allocator<f64, M, M>
+        + Allocator<f64, M, <D::StateType as State>::Size>
+        + Allocator<f64, <D::StateType as State>::Size, M>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, A, A>
+        + Allocator<f64, <D::StateType as State>::Size, A>
+        + Allocator<f64, A, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, na::Const<1>, M>,
+    <DefaultAllocator as Allocator<f64, <D::StateType as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<
+        f64,
+        <D::StateType as State>::Size,
+        <D::StateType as State>::Size,
+    >>::Buffer: Copy,
+{
+    type Estimate = KfEstimate<D::StateType>;
+
+    fn measurement_noise(&self, _epoch: Epoch) -> &OMatrix<f64, M, M> {
+        &self.measurement_noise
+    }
+
+    /// Returns the previous estimate
+    fn previous_estimate(&self) -> &Self::Estimate {
+        &self.prev_estimate
+    }
+
+    fn set_previous_estimate(&mut self, est: &Self::Estimate) {
+        self.prev_estimate = *est;
+        self.sigma_anchor = *est;
+        self.sigma_cache = None;
+    }
+
+    /// Update the sensitivity matrix (or "H tilde"). This function **must** be called prior to each
+    /// call to `measurement_update`, and is used to linearize the computed observations of the sigma points if these are not provided.
+    fn update_h_tilde(&mut self, h_tilde: OMatrix<f64, M, <D::StateType as State>::Size>) {
+        self.h_tilde = h_tilde;
+        self.h_tilde_updated = true;
+    }
+
+    /// Computes a time update/prediction.
+    ///
+    /// If the nominal state has an STM, the previous estimate is mapped with it and the sigma points are only propagated at the next
+    /// measurement update, from the last measurement update. Otherwise, the sigma points are propagated to the epoch of the nominal state.
+    fn time_update(&mut self, nominal_state: D::StateType) -> Result<Self::Estimate, ODError> {
+        let stm = match nominal_state.stm() {
+            Ok(stm) => stm,
+            Err(_) => {
+                let (_, mean, covar_bar) = self.predict(nominal_state)?;
+                let estimate =
+                    KfEstimate {
+                        nominal_state,
+                        state_deviation: mean,
+                        covar: covar_bar,
+                        covar_bar,
+                        stm: OMatrix::<
+                            f64,
+                            <D::StateType as State>::Size,
+                            <D::StateType as State>::Size,
+                        >::identity(),
+                        predicted: true,
+                        ud_factors: None,
+                    };
+                self.store_estimate(estimate);
+                return Ok(estimate);
+            }
+        };
+
+        let epoch = nominal_state.epoch();
+        let mut covar_bar = stm * self.prev_estimate.covar * stm.transpose();
+        if let Some(snc_covar) = self.process_noise_covar(epoch, self.prev_estimate.epoch()) {
+            covar_bar += snc_covar;
+        }
+
+        let state_deviation = if self.extended {
+            OVector::<f64, <D::StateType as State>::Size>::zeros()
+        } else {
+            stm * self.prev_estimate.state_deviation
+        };
+
+        let estimate = KfEstimate {
+            nominal_state,
+            state_deviation,
+            covar: covar_bar,
+            covar_bar,
+            stm,
+            predicted: true,
+            ud_factors: None,
+        };
+        self.prev_estimate = estimate;
+        // Update the prev epoch for all SNCs
+        for snc in &mut self.process_noise {
+            snc.prev_epoch = Some(self.prev_estimate.epoch());
+        }
+        Ok(estimate)
+    }
+
+    /// Computes the measurement update with a provided real observation and computed observation.
+    ///
+    /// The residual ratio is the prefit residual weighted by the inverse of the innovation covariance, i.e. `r' * (P_zz + R)^-1 * r`,
+    /// where the covariance of the computed observations of the sigma points `P_zz` replaces `H P H'`.
+    fn measurement_update(
+        &mut self,
+        nominal_state: D::StateType,
+        real_obs: &OVector<f64, M>,
+        computed_obs: &OVector<f64, M>,
+        resid_ratio_check: Option<f64>,
+    ) -> Result<(Self::Estimate, Residual<M>), ODError> {
+        if !self.h_tilde_updated {
+            return Err(ODError::SensitivityNotUpdated);
+        }
+
+        let epoch = nominal_state.epoch();
+        let (deviations, mean, covar_bar) = self.predict(nominal_state)?;
+        let (_, wm0, wc0, wi) = self.weights();
+
+        // Computed observations of each sigma point, linearized around the nominal if the device did not provide them.
+        let sigma_obs = self.sigma_obs.take().unwrap_or_default();
+        let obs = deviations
+            .iter()
+            .enumerate()
+            .map(|(i, dev)| match sigma_obs.get(i) {
+                Some(Some(obs)) => obs.clone(),
+                _ => computed_obs + &self.h_tilde * dev,
+            })
+            .collect::<Vec<_>>();
+
+        let mut obs_mean = OVector::<f64, M>::zeros();
+        for (i, z) in obs.iter().enumerate() {
+            obs_mean += z * if i == 0 { wm0 } else { wi };
+        }
+
+        let mut obs_covar = OMatrix::<f64, M, M>::zeros();
+        let mut cross_covar = OMatrix::<f64, <D::StateType as State>::Size, M>::zeros();
+        for (i, (z, dev)) in obs.iter().zip(deviations.iter()).enumerate() {
+            let weight = if i == 0 { wc0 } else { wi };
+            let dz = z - &obs_mean;
+            obs_covar += &dz * dz.transpose() * weight;
+            cross_covar += (dev - mean) * dz.transpose() * weight;
+        }
+        let innov_covar = &obs_covar + &self.measurement_noise;
+
+        let mut innov_covar_inv = innov_covar.clone();
+        if !innov_covar_inv.try_inverse_mut() {
+            return Err(ODError::SingularKalmanGain);
+        }
+
+        // The prefit residual is the innovation with respect to the predicted observation
+        let prefit = real_obs - &obs_mean;
+        let ratio = (prefit.transpose() * &innov_covar_inv * &prefit)[0];
+
+        if let Some(ratio_thresh) = resid_ratio_check {
+            if ratio > ratio_thresh {
+                warn!("{epoch} msr rejected: residual ratio {ratio:.3e} > {ratio_thresh}");
+                self.h_tilde_updated = false;
+                // Perform only a time update and return
+                let pred_est = self.time_update(nominal_state)?;
+                return Ok((pred_est, Residual::rejected(epoch, prefit, ratio)));
+            } else {
+                debug!("{epoch} msr accepted: residual ratio {ratio:.3e} < {ratio_thresh}");
+            }
+        }
+
+        let gain = &cross_covar * &innov_covar_inv;
+        let correction = &gain * &prefit;
+        let state_hat = mean + &correction;
+        let postfit = &prefit - &self.h_tilde * &correction;
+
+        let covar = covar_bar - &gain * innov_covar * gain.transpose();
+
+        let stm = nominal_state.stm().unwrap_or_else(|_| {
+            OMatrix::<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>::identity()
+        });
+
+        let estimate = KfEstimate {
+            nominal_state,
+            state_deviation: state_hat,
+            covar,
+            covar_bar,
+            stm,
+            predicted: false,
+            ud_factors: None,
+        };
+
+        self.h_tilde_updated = false;
+        self.store_estimate(estimate);
+        Ok((estimate, Residual::new(epoch, prefit, postfit, ratio)))
+    }
+
+    fn is_extended(&self) -> bool {
+        self.extended
+    }
+
+    /// The UKF always linearizes about its current estimate, so this only changes whether the `ODProcess` recenters its nominal trajectory.
+    fn set_extended(&mut self, status: bool) {
+        self.extended = status;
+    }
+
+    /// Overwrites all of the process noises to the one provided
+    fn set_process_noise(&mut self, snc: SNC<A>) {
+        self.process_noise = vec![snc];
+    }
+
+    fn sigma_points(
+        &mut self,
+        nominal_state: D::StateType,
+    ) -> Result<Option<Vec<D::StateType>>, ODError> {
+        self.propagate_sigma_points(nominal_state.epoch()).map(Some)
+    }
+
+    fn update_sigma_observations(&mut self, obs: Vec<Option<OVector<f64, M>>>) {
+        self.sigma_obs = Some(obs);
+    }
+}
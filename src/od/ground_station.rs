@@ -18,9 +18,10 @@
 
 use super::msr::RangeDoppler;
 use super::noise::GaussMarkov;
-use super::{ODError, ODTrajSnafu, TrackingDeviceSim};
+use super::{ODError, ODFrameSnafu, ODTrajSnafu, TrackingDeviceSim};
 use crate::cosmic::{Cosm, Frame, Orbit};
 use crate::io::{frame_from_str, frame_to_str, ConfigRepr, Configurable};
+use crate::linalg::Matrix3;
 use crate::md::prelude::{Interpolatable, Traj};
 use crate::md::EventEvaluator;
 use crate::time::Epoch;
@@ -318,8 +319,19 @@ impl TrackingDeviceSim<Orbit, RangeDoppler> for GroundStation {
         self.name.clone()
     }
 
-    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
-        cosm.frame_chg(&self.to_orbit(epoch), frame)
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError> {
+        cosm.try_frame_chg(&self.to_orbit(epoch), frame)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn location_dcm(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Matrix3<f64>, ODError> {
+        cosm.try_position_dcm_from_to(&self.frame, &frame, epoch)
+            .with_context(|_| ODFrameSnafu)
     }
 
     fn measure_instantaneous(
@@ -369,8 +381,19 @@ impl TrackingDeviceSim<Spacecraft, RangeDoppler> for GroundStation {
         self.name.clone()
     }
 
-    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit {
-        cosm.frame_chg(&self.to_orbit(epoch), frame)
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError> {
+        cosm.try_frame_chg(&self.to_orbit(epoch), frame)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn location_dcm(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Matrix3<f64>, ODError> {
+        cosm.try_position_dcm_from_to(&self.frame, &frame, epoch)
+            .with_context(|_| ODFrameSnafu)
     }
 
     fn measure_instantaneous(
@@ -0,0 +1,350 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::RangeDoppler;
+use super::noise::GaussMarkov;
+use super::{ODError, ODFrameSnafu, ODTrajSnafu, TrackingDeviceSim};
+use crate::cosmic::eclipse::{line_of_sight, EclipseState};
+use crate::cosmic::{Cosm, Frame, Orbit};
+use crate::io::{frames_from_str, frames_to_str, traj_from_states, traj_to_states};
+use crate::io::{ConfigRepr, Configurable};
+use crate::linalg::Matrix3;
+use crate::md::prelude::Traj;
+use crate::md::trajectory::TrajError;
+use crate::time::Epoch;
+use crate::Spacecraft;
+use hifitime::Duration;
+use rand_pcg::Pcg64Mcg;
+use serde_derive::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::fmt;
+use std::sync::Arc;
+
+/// InterlinkTxSpacecraft defines a spacecraft transmitting a range and Doppler signal to another spacecraft (inter-satellite link).
+///
+/// The location of the transmitter is given by its trajectory, e.g. that of a relay or of another spacecraft of the constellation.
+/// The link is only available when none of the occulting bodies is on the line of sight between both spacecraft.
+#[derive(Clone, Serialize, Deserialize, PartialEq)]
+pub struct InterlinkTxSpacecraft {
+    pub name: String,
+    /// Trajectory of the transmitter spacecraft
+    #[serde(
+        serialize_with = "traj_to_states",
+        deserialize_with = "traj_from_states"
+    )]
+    pub traj: Traj<Orbit>,
+    /// Bodies which may occult the line of sight between both spacecraft
+    #[serde(serialize_with = "frames_to_str", deserialize_with = "frames_from_str")]
+    pub occulting_bodies: Vec<Frame>,
+    /// Duration needed to generate a measurement (if unset, it is assumed to be instantaneous)
+    #[serde(skip)]
+    pub integration_time: Option<Duration>,
+    /// Noise on the timestamp of the measurement
+    pub timestamp_noise_s: Option<GaussMarkov>,
+    /// Noise on the range data of the measurement
+    pub range_noise_km: Option<GaussMarkov>,
+    /// Noise on the Doppler data of the measurement
+    pub doppler_noise_km_s: Option<GaussMarkov>,
+}
+
+impl InterlinkTxSpacecraft {
+    /// Initializes a new inter-satellite link transmitter from its trajectory, with no occulting body.
+    pub fn new(
+        name: String,
+        traj: Traj<Orbit>,
+        range_noise_km: GaussMarkov,
+        doppler_noise_km_s: GaussMarkov,
+    ) -> Self {
+        Self {
+            name,
+            traj,
+            occulting_bodies: Vec::new(),
+            integration_time: None,
+            timestamp_noise_s: None,
+            range_noise_km: Some(range_noise_km),
+            doppler_noise_km_s: Some(doppler_noise_km_s),
+        }
+    }
+
+    /// Initializes a new inter-satellite link transmitter from the trajectory of a spacecraft, with no occulting body.
+    pub fn from_sc_traj(
+        name: String,
+        traj: &Traj<Spacecraft>,
+        range_noise_km: GaussMarkov,
+        doppler_noise_km_s: GaussMarkov,
+    ) -> Self {
+        Self::new(name, traj.downcast(), range_noise_km, doppler_noise_km_s)
+    }
+
+    /// Sets the bodies which may occult the line of sight between both spacecraft
+    pub fn with_occulting_bodies(mut self, occulting_bodies: Vec<Frame>) -> Self {
+        self.occulting_bodies = occulting_bodies;
+        self
+    }
+
+    /// Returns the state of the transmitter at the provided epoch in the provided frame.
+    pub fn tx_at(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError> {
+        let tx = self.traj.at(epoch).with_context(|_| ODTrajSnafu)?;
+        cosm.try_frame_chg(&tx, frame)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    /// Returns the first occulting body on the line of sight between the transmitter and the receiver, if any.
+    pub fn occulted_by(&self, tx: &Orbit, rx: &Orbit, cosm: &Cosm) -> Option<Frame> {
+        self.occulting_bodies
+            .iter()
+            .find(|body| line_of_sight(tx, rx, **body, cosm) == EclipseState::Umbra)
+            .copied()
+    }
+
+    /// Returns the timestamp noise, range noise, and doppler noise for this link at the provided epoch.
+    fn noises(
+        &mut self,
+        epoch: Epoch,
+        rng: Option<&mut Pcg64Mcg>,
+    ) -> Result<(f64, f64, f64), ODError> {
+        match rng {
+            Some(rng) => {
+                // Add the range noise, or return an error if it's not configured.
+                let range_noise_km = self
+                    .range_noise_km
+                    .as_mut()
+                    .ok_or(ODError::NoiseNotConfigured { kind: "Range" })?
+                    .next_bias(epoch, rng);
+
+                // Add the Doppler noise, or return an error if it's not configured.
+                let doppler_noise_km_s = self
+                    .doppler_noise_km_s
+                    .as_mut()
+                    .ok_or(ODError::NoiseNotConfigured { kind: "Doppler" })?
+                    .next_bias(epoch, rng);
+
+                // Only add the epoch noise if it's configured, it's valid to not have any noise on the clock.
+                let timestamp_noise_s = match self.timestamp_noise_s.as_mut() {
+                    Some(timestamp_noise) => timestamp_noise.next_bias(epoch, rng),
+                    None => 0.0,
+                };
+
+                Ok((timestamp_noise_s, range_noise_km, doppler_noise_km_s))
+            }
+            None => Ok((0.0, 0.0, 0.0)),
+        }
+    }
+
+    /// Performs a measurement of the receiver, using a two-way measurement if the integration time is set.
+    /// The state of the receiver is queried with `rx_at`, e.g. from its trajectory.
+    fn measure_link<F>(
+        &mut self,
+        epoch: Epoch,
+        rx_at: F,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<RangeDoppler>, ODError>
+    where
+        F: Fn(Epoch) -> Result<Orbit, TrajError>,
+    {
+        match self.integration_time {
+            Some(integration_time) => {
+                let rx_0 = rx_at(epoch - integration_time).with_context(|_| ODTrajSnafu)?;
+                let rx_1 = rx_at(epoch).with_context(|_| ODTrajSnafu)?;
+
+                let tx_0 = self.tx_at(rx_0.epoch, rx_0.frame, cosm)?;
+                let tx_1 = self.tx_at(rx_1.epoch, rx_1.frame, cosm)?;
+
+                for (tx, rx) in [(&tx_0, &rx_0), (&tx_1, &rx_1)] {
+                    if let Some(body) = self.occulted_by(tx, rx, cosm) {
+                        debug!(
+                            "{} link occulted by {body} at {} -- no measurement",
+                            self.name, rx.epoch
+                        );
+                        return Ok(None);
+                    }
+                }
+
+                // Noises are computed at the midpoint of the integration time.
+                let (timestamp_noise_s, range_noise_km, doppler_noise_km_s) =
+                    self.noises(epoch - integration_time * 0.5, rng)?;
+
+                Ok(Some(RangeDoppler::two_way(
+                    (tx_0, tx_1),
+                    (rx_0, rx_1),
+                    timestamp_noise_s,
+                    range_noise_km,
+                    doppler_noise_km_s,
+                )))
+            }
+            None => {
+                let rx = rx_at(epoch).with_context(|_| ODTrajSnafu)?;
+                self.measure_one_way(rx, rng, cosm)
+            }
+        }
+    }
+
+    /// Performs an instantaneous one-way measurement of the receiver.
+    fn measure_one_way(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<RangeDoppler>, ODError> {
+        let tx = self.tx_at(rx.epoch, rx.frame, cosm)?;
+
+        if let Some(body) = self.occulted_by(&tx, &rx, cosm) {
+            debug!(
+                "{} link occulted by {body} at {} -- no measurement",
+                self.name, rx.epoch
+            );
+            return Ok(None);
+        }
+
+        // Only update the noises if the measurement is valid.
+        let (timestamp_noise_s, range_noise_km, doppler_noise_km_s) = self.noises(rx.epoch, rng)?;
+
+        Ok(Some(RangeDoppler::one_way(
+            tx,
+            rx,
+            timestamp_noise_s,
+            range_noise_km,
+            doppler_noise_km_s,
+        )))
+    }
+}
+
+impl ConfigRepr for InterlinkTxSpacecraft {}
+
+impl Configurable for InterlinkTxSpacecraft {
+    type IntermediateRepr = InterlinkTxSpacecraft;
+
+    fn from_config(
+        cfg: Self::IntermediateRepr,
+        _cosm: Arc<Cosm>,
+    ) -> Result<Self, crate::io::ConfigError>
+    where
+        Self: Sized,
+    {
+        Ok(cfg)
+    }
+
+    fn to_config(&self) -> Result<Self::IntermediateRepr, crate::io::ConfigError> {
+        Ok(self.clone())
+    }
+}
+
+impl TrackingDeviceSim<Orbit, RangeDoppler> for InterlinkTxSpacecraft {
+    /// Perform a measurement from the transmitter spacecraft to the receiver. If there is no integration time of the measurement, then this is assumed to be an instantaneous measurement instead of a two way measurement.
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Orbit>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RangeDoppler>, ODError> {
+        self.measure_link(epoch, |epoch| traj.at(epoch), rng, &cosm)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError> {
+        self.tx_at(epoch, frame, cosm)
+    }
+
+    fn location_dcm(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Matrix3<f64>, ODError> {
+        cosm.try_position_dcm_from_to(&self.traj.first().frame, &frame, epoch)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RangeDoppler>, ODError> {
+        self.measure_one_way(rx, rng, &cosm)
+    }
+}
+
+impl TrackingDeviceSim<Spacecraft, RangeDoppler> for InterlinkTxSpacecraft {
+    /// Perform a measurement from the transmitter spacecraft to the receiver spacecraft.
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Spacecraft>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RangeDoppler>, ODError> {
+        self.measure_link(epoch, |epoch| traj.at(epoch).map(|sc| sc.orbit), rng, &cosm)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError> {
+        self.tx_at(epoch, frame, cosm)
+    }
+
+    fn location_dcm(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Matrix3<f64>, ODError> {
+        cosm.try_position_dcm_from_to(&self.traj.first().frame, &frame, epoch)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Spacecraft,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RangeDoppler>, ODError> {
+        self.measure_one_way(rx.orbit, rng, &cosm)
+    }
+}
+
+impl fmt::Debug for InterlinkTxSpacecraft {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InterlinkTxSpacecraft")
+            .field("name", &self.name)
+            .field("traj", &format!("{}", self.traj))
+            .field("occulting_bodies", &self.occulting_bodies)
+            .field("integration_time", &self.integration_time)
+            .field("timestamp_noise_s", &self.timestamp_noise_s)
+            .field("range_noise_km", &self.range_noise_km)
+            .field("doppler_noise_km_s", &self.doppler_noise_km_s)
+            .finish()
+    }
+}
+
+impl fmt::Display for InterlinkTxSpacecraft {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} inter-satellite link transmitter ({})",
+            self.name, self.traj
+        )
+    }
+}
@@ -37,6 +37,14 @@ pub use filter::Filter;
 mod ground_station;
 pub use ground_station::GroundStation;
 
+/// Provides an optical telescope, which measures angles only
+mod telescope;
+pub use telescope::{FieldOfView, Telescope};
+
+/// Provides a spacecraft to spacecraft range and range rate measuring model.
+mod interlink;
+pub use interlink::InterlinkTxSpacecraft;
+
 /// Provides Estimate handling functionalities.
 pub mod estimate;
 
@@ -62,13 +70,16 @@ pub mod snc;
 pub mod prelude {
     pub use super::estimate::*;
     pub use super::filter::kalman::*;
+    pub use super::filter::unscented::*;
     pub use super::ground_station::*;
+    pub use super::interlink::*;
     pub use super::msr::*;
     pub use super::noise::GaussMarkov;
     pub use super::process::*;
     pub use super::simulator::TrackingArcSim;
     pub use super::simulator::*;
     pub use super::snc::*;
+    pub use super::telescope::*;
     pub use super::*;
 
     pub use crate::time::{Duration, Epoch, TimeUnits, Unit};
@@ -92,6 +103,18 @@ pub trait Measurement: Copy + TimeTagged {
     fn observation(&self) -> OVector<f64, Self::MeasurementSize>
     where
         DefaultAllocator: Allocator<f64, Self::MeasurementSize>;
+
+    /// Returns the observation made continuous with the provided computed observation, used to compute the residuals.
+    /// For example, an observed angle of 359.9 degrees is returned as -0.1 degrees if the computed angle is 0.1 degrees.
+    fn observation_near(
+        &self,
+        _computed: &OVector<f64, Self::MeasurementSize>,
+    ) -> OVector<f64, Self::MeasurementSize>
+    where
+        DefaultAllocator: Allocator<f64, Self::MeasurementSize>,
+    {
+        self.observation()
+    }
 }
 
 /// The Estimate trait defines the interface that is the opposite of a `SolveFor`.
@@ -178,6 +201,12 @@ pub enum ODError {
     SensitivityNotUpdated,
     #[snafu(display("Kalman gain is singular"))]
     SingularKalmanGain,
+    #[snafu(display("covariance is not positive definite"))]
+    CovarianceNotPositiveDefinite,
+    #[snafu(display(
+        "information matrix is singular, the measurements may not observe the full state"
+    ))]
+    SingularInformationMatrix,
     #[snafu(display("{kind} noise not configured"))]
     NoiseNotConfigured { kind: &'static str },
     #[snafu(display("during an OD encountered {source}"))]
@@ -186,4 +215,6 @@ pub enum ODError {
     ODConfigError { source: ConfigError },
     #[snafu(display("OD failed because of an I/O error: {source}"))]
     ODIOError { source: InputOutputError },
+    #[snafu(display("during an orbit determination, could not rotate between frames: {source}"))]
+    ODFrameError { source: NyxError },
 }
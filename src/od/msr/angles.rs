@@ -0,0 +1,471 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::cosmic::{Frame, Orbit};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{Const, DefaultAllocator, Matrix3, OMatrix, OVector, Vector2, Vector3, U2};
+use crate::od::{EstimateFrom, Measurement};
+use crate::utils::{between_0_360, between_pm_180};
+use crate::{Spacecraft, TimeTagged};
+use arrow::datatypes::{DataType, Field};
+use hifitime::{Epoch, Unit};
+use nalgebra::Matrix2x6;
+use std::collections::HashMap;
+
+/// A topocentric right ascension and declination measurement in degrees, e.g. from an optical telescope.
+///
+/// The right ascension is in [0; 360) degrees and the declination in [-90; 90] degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaDec {
+    /// Epoch of the observation
+    pub epoch: Epoch,
+    /// Observation vector in degrees
+    pub obs: Vector2<f64>,
+}
+
+impl RaDec {
+    /// Initialize a new right ascension and declination measurement of the receiver (rx) as seen from the transmitter (tx) and the effective noises.
+    /// The receiver should be the apparent state of the object, i.e. corrected for light time and aberration if needed.
+    ///
+    /// # Panics
+    /// + If the epochs of the two states differ.
+    /// + If the frames of the two states differ.
+    pub fn new(
+        tx: Orbit,
+        rx: Orbit,
+        timestamp_noise_s: f64,
+        ra_noise_deg: f64,
+        dec_noise_deg: f64,
+    ) -> Self {
+        assert_eq!(tx.frame, rx.frame, "tx & rx in different frames");
+        assert_eq!(tx.epoch, rx.epoch, "tx & rx states have different times");
+
+        let rho = rx - tx;
+
+        Self {
+            epoch: tx.epoch + timestamp_noise_s * Unit::Second,
+            obs: Vector2::new(
+                between_0_360(rho.right_ascension_deg() + ra_noise_deg),
+                rho.declination_deg() + dec_noise_deg,
+            ),
+        }
+    }
+
+    /// Returns the sensitivity of the right ascension and declination (in degrees) to the position of the receiver,
+    /// where `rho` is the line of sight vector from the transmitter to the receiver.
+    fn position_partials(rho: &Vector3<f64>) -> OMatrix<f64, U2, Const<3>> {
+        let (x, y, z) = (rho.x, rho.y, rho.z);
+        let rho_xy_sq = x.powi(2) + y.powi(2);
+        let rho_xy = rho_xy_sq.sqrt();
+        let rho_sq = rho.norm_squared();
+
+        OMatrix::<f64, U2, Const<3>>::new(
+            -y / rho_xy_sq,
+            x / rho_xy_sq,
+            0.0,
+            -x * z / (rho_sq * rho_xy),
+            -y * z / (rho_sq * rho_xy),
+            rho_xy / rho_sq,
+        ) * 1.0_f64.to_degrees()
+    }
+}
+
+impl TimeTagged for RaDec {
+    fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    fn set_epoch(&mut self, epoch: Epoch) {
+        self.epoch = epoch
+    }
+}
+
+impl Measurement for RaDec {
+    type MeasurementSize = U2;
+
+    /// Returns this measurement as a vector of right ascension and declination
+    ///
+    /// **Units:** deg, deg
+    fn observation(&self) -> Vector2<f64> {
+        self.obs
+    }
+
+    /// The right ascension is unwrapped to be within 180 degrees of the computed right ascension.
+    fn observation_near(&self, computed: &Vector2<f64>) -> Vector2<f64> {
+        Vector2::new(
+            computed[0] + between_pm_180(self.obs[0] - computed[0]),
+            self.obs[1],
+        )
+    }
+
+    fn fields() -> Vec<Field> {
+        let mut meta = HashMap::new();
+        meta.insert("unit".to_string(), "deg".to_string());
+
+        vec![
+            Field::new("Right ascension (deg)", DataType::Float64, false)
+                .with_metadata(meta.clone()),
+            Field::new("Declination (deg)", DataType::Float64, false).with_metadata(meta),
+        ]
+    }
+
+    fn from_observation(epoch: Epoch, obs: OVector<f64, Self::MeasurementSize>) -> Self {
+        Self { epoch, obs }
+    }
+}
+
+impl EstimateFrom<Orbit, RaDec> for Orbit {
+    fn extract(from: Orbit) -> Self {
+        from
+    }
+
+    /// The angles do not depend on the velocity of the receiver, so these partials are zero.
+    fn sensitivity(
+        _msr: &RaDec,
+        receiver: Self,
+        transmitter: Self,
+    ) -> OMatrix<f64, <RaDec as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <RaDec as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let mut h_tilde = Matrix2x6::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 3>(0, 0)
+            .copy_from(&RaDec::position_partials(
+                &(receiver.radius() - transmitter.radius()),
+            ));
+        h_tilde
+    }
+}
+
+impl EstimateFrom<Spacecraft, RaDec> for Orbit {
+    fn extract(from: Spacecraft) -> Self {
+        from.orbit
+    }
+
+    fn sensitivity(
+        msr: &RaDec,
+        receiver: Self,
+        transmitter: Self,
+    ) -> OMatrix<f64, <RaDec as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <RaDec as Measurement>::MeasurementSize, Self::Size>,
+    {
+        <Orbit as EstimateFrom<Orbit, RaDec>>::sensitivity(msr, receiver, transmitter)
+    }
+}
+
+impl EstimateFrom<Spacecraft, RaDec> for Spacecraft {
+    fn extract(from: Spacecraft) -> Self {
+        from
+    }
+
+    /// The angles do not depend on the Cr, Cd and fuel mass, so their partials are zero.
+    fn sensitivity(
+        msr: &RaDec,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <RaDec as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <RaDec as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let orbit_sensitivity =
+            <Orbit as EstimateFrom<Orbit, RaDec>>::sensitivity(msr, receiver.orbit, transmitter);
+
+        let mut h_tilde = OMatrix::<f64, U2, Const<9>>::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 6>(0, 0)
+            .copy_from(&orbit_sensitivity);
+        h_tilde
+    }
+}
+
+/// A topocentric azimuth and elevation measurement in degrees, e.g. from an optical telescope.
+///
+/// The azimuth is measured clockwise from the North, in [0; 360) degrees, and the elevation is in [-90; 90] degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AzEl {
+    /// Epoch of the observation
+    pub epoch: Epoch,
+    /// Observation vector in degrees
+    pub obs: Vector2<f64>,
+}
+
+impl AzEl {
+    /// Initialize a new azimuth and elevation measurement from the line of sight vector expressed in the topocentric (SEZ) frame of the observer and the effective noises.
+    pub fn new(
+        epoch: Epoch,
+        rho_sez: &Vector3<f64>,
+        timestamp_noise_s: f64,
+        az_noise_deg: f64,
+        el_noise_deg: f64,
+    ) -> Self {
+        let (azimuth_deg, elevation_deg) = Self::from_sez(rho_sez);
+        Self {
+            epoch: epoch + timestamp_noise_s * Unit::Second,
+            obs: Vector2::new(
+                between_0_360(azimuth_deg + az_noise_deg),
+                elevation_deg + el_noise_deg,
+            ),
+        }
+    }
+
+    /// Returns the azimuth and elevation in degrees of the provided line of sight vector, expressed in the topocentric (SEZ) frame.
+    pub fn from_sez(rho_sez: &Vector3<f64>) -> (f64, f64) {
+        // Source: Vallado, section 4.4.3, where the North is the opposite of the South axis.
+        let azimuth_deg = between_0_360(rho_sez.y.atan2(-rho_sez.x).to_degrees());
+        let elevation_deg = (rho_sez.z / rho_sez.norm()).asin().to_degrees();
+        (azimuth_deg, elevation_deg)
+    }
+
+    /// Returns the rotation from the frame of the body to the topocentric (SEZ) frame at the provided geodetic latitude and longitude.
+    ///
+    /// This is the transpose of the SEZ rotation of `Orbit::dcm_from_traj_frame`, from the GMAT MathSpec section 2.6.9.
+    pub fn sez_dcm(latitude_deg: f64, longitude_deg: f64) -> Matrix3<f64> {
+        let phi = latitude_deg.to_radians();
+        let lambda = longitude_deg.to_radians();
+        let z_hat = Vector3::new(
+            phi.cos() * lambda.cos(),
+            phi.cos() * lambda.sin(),
+            phi.sin(),
+        );
+        // y_hat MUST be renormalized otherwise the rotation looses the norms conservation property.
+        let mut y_hat = Vector3::new(0.0, 0.0, 1.0).cross(&z_hat);
+        y_hat /= y_hat.norm();
+        let x_hat = y_hat.cross(&z_hat);
+        Matrix3::from_rows(&[x_hat.transpose(), y_hat.transpose(), z_hat.transpose()])
+    }
+
+    /// Returns the rotation from the inertial frame to the topocentric (SEZ) frame of the observer.
+    ///
+    /// As in the simulation of the measurements, the local vertical is the geodetic normal of the observer, which accounts for the
+    /// flattening of the frame if it is a geoid. Only the Z axis of the inertial frame is assumed to be the rotation axis of the body,
+    /// which is accurate enough for the measurement sensitivity.
+    fn inertial_to_sez(observer: &Orbit) -> Matrix3<f64> {
+        let r_delta = (observer.x_km.powi(2) + observer.y_km.powi(2)).sqrt();
+        let mut latitude = (observer.z_km / observer.rmag_km()).asin();
+        if let Frame::Geoid {
+            flattening,
+            semi_major_radius,
+            ..
+        } = observer.frame
+        {
+            // Source: Vallado, 4th Ed., Algorithm 12 page 172, as `Orbit::geodetic_latitude_deg`
+            let e2 = flattening * (2.0 - flattening);
+            for _ in 0..20 {
+                let c_body = semi_major_radius / (1.0 - e2 * latitude.sin().powi(2)).sqrt();
+                let new_latitude = (observer.z_km + c_body * e2 * latitude.sin()).atan2(r_delta);
+                let converged = (latitude - new_latitude).abs() < 1e-12;
+                latitude = new_latitude;
+                if converged {
+                    break;
+                }
+            }
+        }
+        let longitude = observer.y_km.atan2(observer.x_km);
+        Self::sez_dcm(latitude.to_degrees(), longitude.to_degrees())
+    }
+}
+
+impl TimeTagged for AzEl {
+    fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    fn set_epoch(&mut self, epoch: Epoch) {
+        self.epoch = epoch
+    }
+}
+
+impl Measurement for AzEl {
+    type MeasurementSize = U2;
+
+    /// Returns this measurement as a vector of azimuth and elevation
+    ///
+    /// **Units:** deg, deg
+    fn observation(&self) -> Vector2<f64> {
+        self.obs
+    }
+
+    /// The azimuth is unwrapped to be within 180 degrees of the computed azimuth.
+    fn observation_near(&self, computed: &Vector2<f64>) -> Vector2<f64> {
+        Vector2::new(
+            computed[0] + between_pm_180(self.obs[0] - computed[0]),
+            self.obs[1],
+        )
+    }
+
+    fn fields() -> Vec<Field> {
+        let mut meta = HashMap::new();
+        meta.insert("unit".to_string(), "deg".to_string());
+
+        vec![
+            Field::new("Azimuth (deg)", DataType::Float64, false).with_metadata(meta.clone()),
+            Field::new("Elevation (deg)", DataType::Float64, false).with_metadata(meta),
+        ]
+    }
+
+    fn from_observation(epoch: Epoch, obs: OVector<f64, Self::MeasurementSize>) -> Self {
+        Self { epoch, obs }
+    }
+}
+
+impl EstimateFrom<Orbit, AzEl> for Orbit {
+    fn extract(from: Orbit) -> Self {
+        from
+    }
+
+    /// The angles do not depend on the velocity of the receiver, so these partials are zero.
+    fn sensitivity(
+        _msr: &AzEl,
+        receiver: Self,
+        transmitter: Self,
+    ) -> OMatrix<f64, <AzEl as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <AzEl as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let dcm = AzEl::inertial_to_sez(&transmitter);
+        let rho_sez = dcm * (receiver.radius() - transmitter.radius());
+        let (s, e, z) = (rho_sez.x, rho_sez.y, rho_sez.z);
+        let rho_se_sq = s.powi(2) + e.powi(2);
+        let rho_se = rho_se_sq.sqrt();
+        let rho_sq = rho_sez.norm_squared();
+
+        // Partials with respect to the line of sight in the topocentric frame
+        let partials_sez = OMatrix::<f64, U2, Const<3>>::new(
+            e / rho_se_sq,
+            -s / rho_se_sq,
+            0.0,
+            -s * z / (rho_sq * rho_se),
+            -e * z / (rho_sq * rho_se),
+            rho_se / rho_sq,
+        ) * 1.0_f64.to_degrees();
+
+        let mut h_tilde = Matrix2x6::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 3>(0, 0)
+            .copy_from(&(partials_sez * dcm));
+        h_tilde
+    }
+}
+
+impl EstimateFrom<Spacecraft, AzEl> for Orbit {
+    fn extract(from: Spacecraft) -> Self {
+        from.orbit
+    }
+
+    fn sensitivity(
+        msr: &AzEl,
+        receiver: Self,
+        transmitter: Self,
+    ) -> OMatrix<f64, <AzEl as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <AzEl as Measurement>::MeasurementSize, Self::Size>,
+    {
+        <Orbit as EstimateFrom<Orbit, AzEl>>::sensitivity(msr, receiver, transmitter)
+    }
+}
+
+impl EstimateFrom<Spacecraft, AzEl> for Spacecraft {
+    fn extract(from: Spacecraft) -> Self {
+        from
+    }
+
+    /// The angles do not depend on the Cr, Cd and fuel mass, so their partials are zero.
+    fn sensitivity(
+        msr: &AzEl,
+        receiver: Self,
+        transmitter: Orbit,
+    ) -> OMatrix<f64, <AzEl as Measurement>::MeasurementSize, Self::Size>
+    where
+        DefaultAllocator: Allocator<f64, <AzEl as Measurement>::MeasurementSize, Self::Size>,
+    {
+        let orbit_sensitivity =
+            <Orbit as EstimateFrom<Orbit, AzEl>>::sensitivity(msr, receiver.orbit, transmitter);
+
+        let mut h_tilde = OMatrix::<f64, U2, Const<9>>::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 6>(0, 0)
+            .copy_from(&orbit_sensitivity);
+        h_tilde
+    }
+}
+
+#[cfg(test)]
+mod ut_angles {
+    use super::*;
+    use crate::cosmic::Cosm;
+
+    #[test]
+    fn angles_sensitivity() {
+        let cosm = Cosm::de438();
+        let eme2k = cosm.frame("EME2000");
+        let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+        let tx = Orbit::cartesian(4000.0, 3000.0, 3500.0, 0.0, 0.0, 0.0, epoch, eme2k);
+        let rx = Orbit::cartesian(-2000.0, 6000.0, 4000.0, 1.0, -2.0, 5.0, epoch, eme2k);
+
+        let radec = RaDec::new(tx, rx, 0.0, 0.0, 0.0);
+        let h_radec = <Orbit as EstimateFrom<Orbit, RaDec>>::sensitivity(&radec, rx, tx);
+
+        let rho_sez = AzEl::inertial_to_sez(&tx) * (rx.radius() - tx.radius());
+        let azel = AzEl::new(epoch, &rho_sez, 0.0, 0.0, 0.0);
+        let h_azel = <Orbit as EstimateFrom<Orbit, AzEl>>::sensitivity(&azel, rx, tx);
+
+        // Compare with central finite differences of the measurements
+        let step_km = 1e-3;
+        for i in 0..3 {
+            let mut plus = rx;
+            let mut minus = rx;
+            match i {
+                0 => {
+                    plus.x_km += step_km;
+                    minus.x_km -= step_km;
+                }
+                1 => {
+                    plus.y_km += step_km;
+                    minus.y_km -= step_km;
+                }
+                _ => {
+                    plus.z_km += step_km;
+                    minus.z_km -= step_km;
+                }
+            }
+
+            let fd_radec = (RaDec::new(tx, plus, 0.0, 0.0, 0.0).obs
+                - RaDec::new(tx, minus, 0.0, 0.0, 0.0).obs)
+                / (2.0 * step_km);
+
+            let sez_plus = AzEl::inertial_to_sez(&tx) * (plus.radius() - tx.radius());
+            let sez_minus = AzEl::inertial_to_sez(&tx) * (minus.radius() - tx.radius());
+            let fd_azel = (AzEl::new(epoch, &sez_plus, 0.0, 0.0, 0.0).obs
+                - AzEl::new(epoch, &sez_minus, 0.0, 0.0, 0.0).obs)
+                / (2.0 * step_km);
+
+            for j in 0..2 {
+                assert!((h_radec[(j, i)] - fd_radec[j]).abs() < 1e-9);
+                assert!((h_azel[(j, i)] - fd_azel[j]).abs() < 1e-9);
+            }
+        }
+
+        // The angles are unwrapped near the computed observation
+        let msr = RaDec::from_observation(epoch, Vector2::new(359.9, 10.0));
+        let unwrapped = msr.observation_near(&Vector2::new(0.1, 10.0));
+        assert!((unwrapped[0] + 0.1).abs() < 1e-12);
+    }
+}
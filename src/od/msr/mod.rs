@@ -16,11 +16,13 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
+mod angles;
 mod arc;
 mod range;
 mod range_doppler;
 mod rangerate;
 
+pub use angles::{AzEl, RaDec};
 pub use arc::TrackingArc;
 pub use range::RangeMsr;
 pub use range_doppler::RangeDoppler;
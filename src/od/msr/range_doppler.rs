@@ -18,7 +18,7 @@
 
 use crate::cosmic::Orbit;
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, OMatrix, OVector, Vector2, U2};
+use crate::linalg::{Const, DefaultAllocator, OMatrix, OVector, Vector2, U2};
 use crate::od::msr::RangeMsr;
 use crate::od::{EstimateFrom, Measurement};
 use crate::{Spacecraft, TimeTagged};
@@ -222,15 +222,27 @@ impl EstimateFrom<Spacecraft, RangeDoppler> for Spacecraft {
         from
     }
 
+    /// The range and Doppler do not depend on the Cr, Cd and fuel mass, so their partials are zero.
+    /// These parameters are instead observed through their effect on the dynamics, i.e. through the STM.
     fn sensitivity(
-        _msr: &RangeDoppler,
-        _receiver: Self,
-        _transmitter: Orbit,
+        msr: &RangeDoppler,
+        receiver: Self,
+        transmitter: Orbit,
     ) -> OMatrix<f64, <RangeDoppler as Measurement>::MeasurementSize, Self::Size>
     where
         DefaultAllocator:
             Allocator<f64, <RangeDoppler as Measurement>::MeasurementSize, Self::Size>,
     {
-        todo!("cannot yet estimate a full spacecraft state")
+        let orbit_sensitivity = <Orbit as EstimateFrom<Orbit, RangeDoppler>>::sensitivity(
+            msr,
+            receiver.orbit,
+            transmitter,
+        );
+
+        let mut h_tilde = OMatrix::<f64, U2, Const<9>>::zeros();
+        h_tilde
+            .fixed_view_mut::<2, 6>(0, 0)
+            .copy_from(&orbit_sensitivity);
+        h_tilde
     }
 }
@@ -0,0 +1,547 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::{FltResid, IterationConf};
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, DimName, OMatrix, OVector};
+use crate::md::trajectory::{Interpolatable, Traj};
+use crate::od::estimate::{Estimate, KfEstimate, Residual};
+use crate::od::msr::TrackingArc;
+use crate::od::{
+    Cosm, Dynamics, EstimateFrom, Measurement, ODConfigSnafu, ODDynamicsSnafu, ODError,
+    ODPropSnafu, State, TrackingDeviceSim,
+};
+use crate::propagators::error_ctrl::ErrorCtrl;
+use crate::propagators::PropInstance;
+use crate::time::{Duration, Epoch};
+use snafu::prelude::*;
+use std::collections::BTreeMap;
+use std::ops::Add;
+use std::sync::Arc;
+
+/// A batch weighted least squares estimator.
+///
+/// All of the measurements of a tracking arc are accumulated into the normal equations, mapped to the solution epoch
+/// with the state transition matrix of the reference trajectory. The solution is then iterated, i.e. the reference
+/// trajectory is corrected and the arc is processed again, until the weighted RMS of the prefit residuals and the state
+/// correction converge.
+///
+/// The solution epoch is the epoch of the initial state of the propagator.
+pub struct BatchLeastSquares<
+    'a,
+    D: Dynamics,
+    E: ErrorCtrl,
+    Msr: Measurement,
+    S: EstimateFrom<D::StateType, Msr> + Interpolatable,
+> where
+    D::StateType: Interpolatable + Add<OVector<f64, <S as State>::Size>, Output = D::StateType>,
+    <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <S as State>::Size>
+        + Allocator<usize, <S as State>::Size>
+        + Allocator<f64, <S as State>::VecLength>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, Msr::MeasurementSize>
+        + Allocator<f64, Msr::MeasurementSize, S::Size>
+        + Allocator<f64, Msr::MeasurementSize, Msr::MeasurementSize>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, <S as State>::Size, <S as State>::Size>
+        + Allocator<usize, <S as State>::Size, <S as State>::Size>,
+    <DefaultAllocator as Allocator<f64, <S as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<f64, <S as State>::Size, <S as State>::Size>>::Buffer: Copy,
+{
+    /// PropInstance used for the estimation, its initial state defines the reference trajectory and the solution epoch
+    pub prop: PropInstance<'a, D, E>,
+    /// A priori information: its state deviation is that of the a priori state with respect to the initial state of the propagator.
+    /// The a priori covariance is ignored if it is not invertible (e.g. zero), in which case the solution only relies on the measurements.
+    pub apriori: KfEstimate<S>,
+    /// Measurement noise (usually noted R), the inverse of which is the weight of each measurement
+    pub measurement_noise: OMatrix<f64, Msr::MeasurementSize, Msr::MeasurementSize>,
+    /// Residual rejection criteria allows preventing bad measurements from affecting the solution.
+    pub resid_crit: Option<FltResid>,
+    /// Outer iteration configuration, where the tolerances apply to the weighted RMS of the prefit residuals and to the relative
+    /// change of the state correction. The smoothing arc is not used by the batch estimator.
+    pub config: IterationConf,
+    pub cosm: Arc<Cosm>,
+    /// Solution at the epoch of the initial state, available after `process_arc`
+    pub solution: Option<KfEstimate<S>>,
+    /// Solution mapped to the epoch of each measurement of the last iteration, with its propagated covariance
+    pub estimates: Vec<KfEstimate<S>>,
+    /// Residuals of each measurement of the last iteration
+    pub residuals: Vec<Option<Residual<Msr::MeasurementSize>>>,
+    /// Number of iterations of the last call to `process_arc`
+    pub iterations: usize,
+    init_state: D::StateType,
+}
+
+impl<
+        'a,
+        D: Dynamics,
+        E: ErrorCtrl,
+        Msr: Measurement,
+        S: EstimateFrom<D::StateType, Msr> + Interpolatable,
+    > BatchLeastSquares<'a, D, E, Msr, S>
+where
+    D::StateType: Interpolatable + Add<OVector<f64, <S as State>::Size>, Output = D::StateType>,
+    <DefaultAllocator as Allocator<f64, <D::StateType as State>::VecLength>>::Buffer: Send,
+    DefaultAllocator: Allocator<f64, <D::StateType as State>::Size>
+        + Allocator<f64, <S as State>::Size>
+        + Allocator<usize, <S as State>::Size>
+        + Allocator<f64, <S as State>::VecLength>
+        + Allocator<f64, <D::StateType as State>::VecLength>
+        + Allocator<f64, Msr::MeasurementSize>
+        + Allocator<f64, Msr::MeasurementSize, S::Size>
+        + Allocator<f64, S::Size, Msr::MeasurementSize>
+        + Allocator<f64, Msr::MeasurementSize, Msr::MeasurementSize>
+        + Allocator<f64, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<usize, <D::StateType as State>::Size, <D::StateType as State>::Size>
+        + Allocator<f64, <S as State>::Size, <S as State>::Size>
+        + Allocator<usize, <S as State>::Size, <S as State>::Size>
+        + Allocator<f64, na::Const<1>, Msr::MeasurementSize>,
+    <DefaultAllocator as Allocator<f64, <S as State>::Size>>::Buffer: Copy,
+    <DefaultAllocator as Allocator<f64, <S as State>::Size, <S as State>::Size>>::Buffer: Copy,
+{
+    /// Initialize a new batch least squares estimator.
+    ///
+    /// The propagator state must have its STM enabled (e.g. `Orbit::with_stm`).
+    pub fn new(
+        prop: PropInstance<'a, D, E>,
+        apriori: KfEstimate<S>,
+        measurement_noise: OMatrix<f64, Msr::MeasurementSize, Msr::MeasurementSize>,
+        resid_crit: Option<FltResid>,
+        config: IterationConf,
+        cosm: Arc<Cosm>,
+    ) -> Self {
+        let init_state = prop.state;
+        Self {
+            prop,
+            apriori,
+            measurement_noise,
+            resid_crit,
+            config,
+            cosm,
+            solution: None,
+            estimates: Vec::new(),
+            residuals: Vec::new(),
+            iterations: 0,
+            init_state,
+        }
+    }
+
+    /// Returns the root mean square of the prefit residual ratios of the accepted measurements of the last iteration,
+    /// or None if no measurement was accepted.
+    pub fn rms_residual_ratios(&self) -> Option<f64> {
+        let mut sum = 0.0;
+        let mut cnt = 0;
+        for residual in self.residuals.iter().flatten() {
+            if !residual.rejected {
+                sum += residual.ratio.powi(2);
+                cnt += 1;
+            }
+        }
+        if cnt == 0 {
+            None
+        } else {
+            Some((sum / (cnt as f64)).sqrt())
+        }
+    }
+
+    /// Returns the weighted root mean square of the prefit residuals of the accepted measurements of the last iteration,
+    /// i.e. `sqrt(Σ y' * R^-1 * y / m)` where `m` is the number of accepted observations, or None if no measurement was accepted.
+    pub fn rms_weighted_residuals(&self) -> Option<f64> {
+        let weight = self.measurement_noise.try_inverse()?;
+        let mut sum = 0.0;
+        let mut cnt = 0;
+        for residual in self.residuals.iter().flatten() {
+            if !residual.rejected {
+                sum += (residual.prefit.transpose() * weight * &residual.prefit)[0];
+                cnt += Msr::MeasurementSize::dim();
+            }
+        }
+        if cnt == 0 {
+            None
+        } else {
+            Some((sum / (cnt as f64)).sqrt())
+        }
+    }
+
+    /// Process the provided tracking arc, iterating on the solution until convergence. Returns the solution at the initial epoch.
+    pub fn process_arc<Dev>(&mut self, arc: &TrackingArc<Msr>) -> Result<KfEstimate<S>, ODError>
+    where
+        Dev: TrackingDeviceSim<S, Msr>,
+    {
+        let mut devices = arc
+            .rebuild_devices::<S, Dev>(self.cosm.clone())
+            .with_context(|_| ODConfigSnafu)?;
+
+        let step_size = match arc.min_duration_sep() {
+            Some(step_size) => step_size,
+            None => {
+                return Err(ODError::TooFewMeasurements {
+                    action: "determining the minimum step size",
+                    need: 2,
+                })
+            }
+        };
+
+        self.process(&arc.measurements, &mut devices, step_size)
+    }
+
+    /// Process the provided measurements given the associated devices, iterating on the solution until convergence.
+    /// Returns the solution at the initial epoch.
+    ///
+    /// # Argument details
+    /// + The measurements must be a chronological list mapping the name of the measurement device to the measurement itself.
+    /// + The name of all measurement devices must be present in the provided devices.
+    /// + The maximum step size to ensure we don't skip any measurements.
+    pub fn process<Dev>(
+        &mut self,
+        measurements: &[(String, Msr)],
+        devices: &mut BTreeMap<String, Dev>,
+        max_step: Duration,
+    ) -> Result<KfEstimate<S>, ODError>
+    where
+        Dev: TrackingDeviceSim<S, Msr>,
+    {
+        ensure!(
+            measurements.len() >= 2,
+            TooFewMeasurementsSnafu {
+                need: 2_usize,
+                action: "running a batch least squares"
+            }
+        );
+
+        ensure!(
+            !max_step.is_negative() && max_step != Duration::ZERO,
+            StepSizeSnafu { step: max_step }
+        );
+
+        let mut previous_rms = f64::INFINITY;
+        let mut previous_correction = f64::INFINITY;
+        let mut divergence_cnt = 0;
+        self.iterations = 0;
+
+        let solution = loop {
+            self.iterations += 1;
+            info!("*** Batch iteration number {:02} ***", self.iterations);
+
+            let solution = self.single_pass(measurements, devices, max_step)?;
+
+            let new_rms = self
+                .rms_weighted_residuals()
+                .ok_or(ODError::TooFewMeasurements {
+                    need: 1,
+                    action: "computing the batch weighted residuals",
+                })?;
+            let correction = solution.state_deviation.norm();
+            info!("Batch correction of {correction:.3e} with weighted prefit residual RMS {new_rms:.5}");
+
+            // Correct the reference trajectory for the next iteration, and the a priori deviation with respect to it.
+            self.init_state = self.init_state + solution.state_deviation;
+            self.apriori.state_deviation -= solution.state_deviation;
+            self.solution = Some(solution);
+
+            if new_rms <= self.config.absolute_tol {
+                info!(
+                    "Batch converged to absolute tolerance ({new_rms:.2e} < {:.2e}) after {} iterations",
+                    self.config.absolute_tol, self.iterations
+                );
+                break solution;
+            }
+
+            if previous_rms.is_finite() {
+                // The residuals have settled once their RMS no longer changes and the correction has become negligible
+                let cur_rel_rms = (new_rms - previous_rms).abs() / previous_rms;
+                let cur_rel_correction = correction / previous_correction;
+                if cur_rel_rms < self.config.relative_tol
+                    && cur_rel_correction < self.config.relative_tol
+                {
+                    info!(
+                        "Batch converged on relative change of the residuals ({cur_rel_rms:.2e}) and of the correction ({cur_rel_correction:.2e}) after {} iterations",
+                        self.iterations
+                    );
+                    break solution;
+                } else if new_rms > previous_rms {
+                    divergence_cnt += 1;
+                    warn!(
+                        "Batch iteration caused divergence {divergence_cnt} of {} acceptable subsequent divergences (RMS {new_rms:.5} > {previous_rms:.5})",
+                        self.config.max_divergences
+                    );
+                    if divergence_cnt >= self.config.max_divergences {
+                        if self.config.force_failure {
+                            return Err(ODError::Diverged {
+                                loops: self.config.max_divergences,
+                            });
+                        } else {
+                            error!(
+                                "Batch iterations have continuously diverged {} times: {}",
+                                self.config.max_divergences, self.config
+                            );
+                            break solution;
+                        }
+                    }
+                } else {
+                    divergence_cnt = 0;
+                }
+            }
+
+            previous_rms = new_rms;
+            previous_correction = correction;
+
+            if self.iterations >= self.config.max_iterations {
+                if self.config.force_failure {
+                    return Err(ODError::Diverged {
+                        loops: self.config.max_iterations,
+                    });
+                } else {
+                    error!(
+                        "Batch has iterated {} times but failed to reach convergence criteria: {}",
+                        self.config.max_iterations, self.config
+                    );
+                    break solution;
+                }
+            }
+        };
+
+        Ok(solution)
+    }
+
+    /// Processes all of the measurements once along the current reference trajectory and solves the normal equations.
+    fn single_pass<Dev>(
+        &mut self,
+        measurements: &[(String, Msr)],
+        devices: &mut BTreeMap<String, Dev>,
+        max_step: Duration,
+    ) -> Result<KfEstimate<S>, ODError>
+    where
+        Dev: TrackingDeviceSim<S, Msr>,
+    {
+        self.prop.state = self.init_state;
+        self.prop.state.reset_stm();
+        if !self.prop.fixed_step {
+            self.prop.set_step(max_step, false);
+        }
+
+        let reference = S::extract(self.prop.state);
+        let epoch0 = reference.epoch();
+
+        let mut weight = self.measurement_noise;
+        if !weight.try_inverse_mut() {
+            return Err(ODError::SingularInformationMatrix);
+        }
+
+        // Covariance used for the residual ratios: that of the previous iteration, or the a priori one
+        let covar0 = match &self.solution {
+            Some(solution) => solution.covar,
+            None => self.apriori.covar,
+        };
+
+        // Initialize the normal equations with the a priori information, if any
+        let (mut info_mat, mut normal) = match self.apriori.covar.try_inverse() {
+            Some(apriori_info) => (apriori_info, apriori_info * self.apriori.state_deviation),
+            None => {
+                debug!("a priori covariance not invertible, solving without a priori information");
+                (
+                    OMatrix::<f64, S::Size, S::Size>::zeros(),
+                    OVector::<f64, S::Size>::zeros(),
+                )
+            }
+        };
+
+        let mut traj: Traj<S> = Traj::new();
+        traj.states.push(reference);
+
+        let mut processed = Vec::with_capacity(measurements.len());
+        let mut msr_accepted_cnt = 0;
+
+        for (device_name, msr) in measurements {
+            let next_msr_epoch = msr.epoch();
+            if next_msr_epoch < epoch0 {
+                warn!("{device_name} measurement @ {next_msr_epoch} is before the solution epoch {epoch0} -- ignoring measurement");
+                continue;
+            }
+
+            for val in msr.observation().iter() {
+                ensure!(
+                    val.is_finite(),
+                    InvalidMeasurementSnafu {
+                        epoch: next_msr_epoch,
+                        val: *val
+                    }
+                );
+            }
+
+            // Advance the reference trajectory, without resetting the STM so that it maps to the solution epoch
+            loop {
+                let delta_t = next_msr_epoch - self.prop.state.epoch();
+                if delta_t <= Duration::ZERO {
+                    break;
+                }
+                let next_step_size = delta_t.min(self.prop.step_size).min(max_step);
+                let (_, traj_covar) = self
+                    .prop
+                    .for_duration_with_traj(next_step_size)
+                    .with_context(|_| ODPropSnafu)?;
+
+                for state in traj_covar.states {
+                    let state = S::extract(state);
+                    if traj
+                        .states
+                        .last()
+                        .map_or(true, |last| state.epoch() > last.epoch())
+                    {
+                        traj.states.push(state);
+                    }
+                }
+            }
+
+            let nominal_state = S::extract(self.prop.state);
+            let epoch = nominal_state.epoch();
+
+            let device = match devices.get_mut(device_name) {
+                Some(device) => device,
+                None => {
+                    error!("Tracking arc references {device_name} which is not in the list of configured devices");
+                    continue;
+                }
+            };
+
+            let computed_meas = match device.measure(epoch, &traj, None, self.cosm.clone())? {
+                Some(computed_meas) => computed_meas,
+                None => {
+                    warn!("Real observation exists @ {epoch} but simulated {device_name} does not see it -- ignoring measurement");
+                    continue;
+                }
+            };
+
+            let device_loc = device.location(epoch, nominal_state.frame(), &self.cosm)?;
+
+            // Map the sensitivity matrix to the solution epoch
+            let stm = nominal_state.stm().with_context(|_| ODDynamicsSnafu)?;
+            let h_mat = S::sensitivity(msr, nominal_state, device_loc) * stm;
+
+            let prefit =
+                msr.observation_near(&computed_meas.observation()) - computed_meas.observation();
+
+            // The residual ratio is computed as in the Kalman filter, from the covariance mapped to the epoch of the measurement
+            let h_p_ht = &h_mat * covar0 * h_mat.transpose();
+            let ratio = (prefit.transpose() * h_p_ht * &prefit)[0];
+
+            let rejected = match self.resid_crit {
+                Some(flt) if msr_accepted_cnt >= flt.min_accepted => ratio > flt.num_sigmas,
+                _ => false,
+            };
+
+            if rejected {
+                warn!("{epoch} msr rejected: residual ratio {ratio:.3e}");
+            } else {
+                msr_accepted_cnt += 1;
+                let h_t_w = h_mat.transpose() * weight;
+                info_mat += &h_t_w * &h_mat;
+                normal += h_t_w * &prefit;
+            }
+
+            processed.push((nominal_state, stm, h_mat, prefit, ratio, rejected));
+        }
+
+        ensure!(
+            msr_accepted_cnt > 0,
+            TooFewMeasurementsSnafu {
+                need: 1_usize,
+                action: "solving the batch normal equations"
+            }
+        );
+
+        // Solve the normal equations
+        let covar = match info_mat.try_inverse() {
+            Some(covar) => covar,
+            None => return Err(ODError::SingularInformationMatrix),
+        };
+        let state_hat = covar * normal;
+
+        // Map the solution to each measurement epoch and compute the postfit residuals
+        self.estimates = Vec::with_capacity(processed.len());
+        self.residuals = Vec::with_capacity(processed.len());
+        for (nominal_state, stm, h_mat, prefit, ratio, rejected) in processed {
+            let epoch = nominal_state.epoch();
+            let mapped_covar = stm * covar * stm.transpose();
+            self.estimates.push(KfEstimate {
+                nominal_state,
+                state_deviation: stm * state_hat,
+                covar: mapped_covar,
+                covar_bar: mapped_covar,
+                predicted: false,
+                stm,
+                ud_factors: None,
+            });
+            self.residuals.push(Some(if rejected {
+                Residual::rejected(epoch, prefit, ratio)
+            } else {
+                let postfit = &prefit - h_mat * state_hat;
+                Residual::new(epoch, prefit, postfit, ratio)
+            }));
+        }
+
+        Ok(KfEstimate {
+            nominal_state: reference,
+            state_deviation: state_hat,
+            covar,
+            covar_bar: self.apriori.covar,
+            predicted: false,
+            stm: OMatrix::<f64, S::Size, S::Size>::identity(),
+            ud_factors: None,
+        })
+    }
+
+    /// Returns the solution epoch, i.e. the epoch of the initial state of the reference trajectory
+    pub fn epoch(&self) -> Epoch {
+        S::extract(self.init_state).epoch()
+    }
+
+    /// Returns the last solution propagated to the provided epoch with its covariance, i.e. a `KfEstimate` at that epoch.
+    pub fn predict(&mut self, epoch: Epoch) -> Result<KfEstimate<S>, ODError> {
+        let solution = match self.solution {
+            Some(solution) => solution,
+            None => {
+                return Err(ODError::TooFewMeasurements {
+                    need: 2,
+                    action: "predicting a batch solution before processing",
+                })
+            }
+        };
+
+        // The reference trajectory has already been corrected with the last solution
+        self.prop.state = self.init_state;
+        self.prop.state.reset_stm();
+        let state = self.prop.until_epoch(epoch).with_context(|_| ODPropSnafu)?;
+        let nominal_state = S::extract(state);
+        let stm = nominal_state.stm().with_context(|_| ODDynamicsSnafu)?;
+        let covar = stm * solution.covar * stm.transpose();
+
+        Ok(KfEstimate {
+            nominal_state,
+            state_deviation: OVector::<f64, S::Size>::zeros(),
+            covar,
+            covar_bar: covar,
+            predicted: true,
+            stm,
+            ud_factors: None,
+        })
+    }
+}
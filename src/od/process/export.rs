@@ -19,7 +19,7 @@
 use crate::io::watermark::pq_writer;
 use crate::io::{ArrowSnafu, ExportCfg, ParquetSnafu, StdIOSnafu};
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName};
+use crate::linalg::{DMatrix, DefaultAllocator, DimName};
 use crate::md::prelude::Frame;
 use crate::md::trajectory::Interpolatable;
 use crate::od::estimate::*;
@@ -130,39 +130,24 @@ where
             hdrs.push(field.to_field(more_meta.clone()));
         }
 
-        let cov_hdrs = match <S as State>::Size::dim() {
-            6 => {
-                // Add orbit 1-sigma covariance info, plotting to perform computations as desired
-                vec![
-                    "Covariance XX",
-                    "Covariance XY",
-                    "Covariance XZ",
-                    "Covariance XVx",
-                    "Covariance XVy",
-                    "Covariance XVz",
-                    "Covariance YY",
-                    "Covariance YZ",
-                    "Covariance YVx",
-                    "Covariance YVy",
-                    "Covariance YVz",
-                    "Covariance ZZ",
-                    "Covariance ZVx",
-                    "Covariance ZVy",
-                    "Covariance ZVz",
-                    "Covariance VxVx",
-                    "Covariance VxVy",
-                    "Covariance VxVz",
-                    "Covariance VyVy",
-                    "Covariance VyVz",
-                    "Covariance VzVz",
-                ]
-            }
+        let state_items = match <S as State>::Size::dim() {
+            // Orbit 1-sigma covariance info, plotting to perform computations as desired
+            6 => vec!["X", "Y", "Z", "Vx", "Vy", "Vz"],
+            // Spacecraft covariance, including the Cr, Cd and fuel mass
+            9 => vec!["X", "Y", "Z", "Vx", "Vy", "Vz", "Cr", "Cd", "Mass"],
             _ => todo!(
                 "exporting a state of size {} is not yet supported",
                 <S as State>::Size::dim()
             ),
         };
 
+        let mut cov_hdrs = Vec::new();
+        for (i, item_i) in state_items.iter().enumerate() {
+            for item_j in state_items.iter().skip(i) {
+                cov_hdrs.push(format!("Covariance {item_i}{item_j}"));
+            }
+        }
+
         // Add the covariance in the integration frame
         for hdr in &cov_hdrs {
             hdrs.push(Field::new(
@@ -177,6 +162,34 @@ where
             hdrs.push(Field::new(format!("{hdr} (RIC)"), DataType::Float64, false));
         }
 
+        // Add the consider covariance, i.e. the covariance inflated by the uncertainty of the consider parameters
+        let with_consider = !self.consider_estimates.is_empty();
+        if with_consider {
+            if self.consider_estimates.len() != self.estimates.len() {
+                return Err(ODError::ODConfigError {
+                    source: ConfigError::InvalidConfig {
+                        msg: "Estimates and consider estimates are not aligned.".to_string(),
+                    },
+                });
+            }
+
+            for hdr in &cov_hdrs {
+                hdrs.push(Field::new(
+                    format!("Consider {hdr} ({frame_name})"),
+                    DataType::Float64,
+                    false,
+                ));
+            }
+
+            for hdr in &cov_hdrs {
+                hdrs.push(Field::new(
+                    format!("Consider {hdr} (RIC)"),
+                    DataType::Float64,
+                    false,
+                ));
+            }
+        }
+
         // Add the fields of the residuals
         let mut msr_fields = Vec::new();
         for f in Msr::fields() {
@@ -203,7 +216,7 @@ where
         let mut record: Vec<Arc<dyn Array>> = Vec::new();
 
         // Build the states iterator -- this does require copying the current states but I can't either get a reference or a copy of all the states.
-        let (estimates, residuals) =
+        let (estimates, residuals, considers) =
             if cfg.start_epoch.is_some() || cfg.end_epoch.is_some() || cfg.step.is_some() {
                 // Must interpolate the data!
                 let start = cfg
@@ -216,17 +229,27 @@ where
                 let mut residuals: Vec<Option<Residual<Msr::MeasurementSize>>> =
                     Vec::with_capacity(self.residuals.len());
                 let mut estimates = Vec::with_capacity(self.estimates.len());
+                let mut considers = Vec::with_capacity(self.consider_estimates.len());
 
-                for (estimate, residual) in self.estimates.iter().zip(self.residuals.iter()) {
+                for (idx, (estimate, residual)) in
+                    self.estimates.iter().zip(self.residuals.iter()).enumerate()
+                {
                     if estimate.epoch() >= start && estimate.epoch() <= end {
                         estimates.push(estimate.clone());
                         residuals.push(residual.clone());
+                        if with_consider {
+                            considers.push(self.consider_estimates[idx].clone());
+                        }
                     }
                 }
 
-                (estimates, residuals)
+                (estimates, residuals, considers)
             } else {
-                (self.estimates.to_vec(), self.residuals.to_vec())
+                (
+                    self.estimates.to_vec(),
+                    self.residuals.to_vec(),
+                    self.consider_estimates.to_vec(),
+                )
             };
 
         // Build all of the records
@@ -264,6 +287,7 @@ where
         }
         // Add the 1-sigma covariance in the RIC frame
         let mut ric_covariances = Vec::new();
+        let mut ric_dcms = Vec::new();
 
         for s in &estimates {
             let dcm6x6 = s
@@ -281,6 +305,7 @@ where
             let ric_covar = &dcm * s.covar() * &dcm.transpose();
 
             ric_covariances.push(ric_covar);
+            ric_dcms.push(dcm);
         }
 
         // Now store the RIC covariance data.
@@ -294,6 +319,36 @@ where
             }
         }
 
+        // Add the consider covariance in the integration frame and in the RIC frame
+        if with_consider {
+            let n = <S as State>::Size::dim();
+            let mut consider_covariances = Vec::with_capacity(estimates.len());
+            let mut ric_consider_covariances = Vec::with_capacity(estimates.len());
+            for ((s, consider), dcm) in estimates.iter().zip(considers.iter()).zip(ric_dcms.iter())
+            {
+                let consider_covar = consider.consider_covar(&DMatrix::from_column_slice(
+                    n,
+                    n,
+                    s.covar().as_slice(),
+                ));
+                let dcm = DMatrix::from_column_slice(n, n, dcm.as_slice());
+                ric_consider_covariances.push(&dcm * &consider_covar * dcm.transpose());
+                consider_covariances.push(consider_covar);
+            }
+
+            for covariances in [&consider_covariances, &ric_consider_covariances] {
+                for i in 0..n {
+                    for j in i..n {
+                        let mut data = Float64Builder::new();
+                        for cov in covariances {
+                            data.append_value(cov[(i, j)]);
+                        }
+                        record.push(Arc::new(data.finish()));
+                    }
+                }
+            }
+        }
+
         // Finally, add the residuals.
         // Prefits
         for i in 0..Msr::MeasurementSize::dim() {
@@ -17,7 +17,7 @@
 */
 
 use crate::linalg::allocator::Allocator;
-use crate::linalg::{DefaultAllocator, DimName};
+use crate::linalg::{DMatrix, DefaultAllocator, DimName};
 use crate::md::trajectory::{Interpolatable, Traj};
 pub use crate::od::estimate::*;
 pub use crate::od::ground_station::*;
@@ -27,6 +27,8 @@ use crate::propagators::error_ctrl::ErrorCtrl;
 use crate::propagators::PropInstance;
 pub use crate::time::{Duration, Unit};
 use snafu::prelude::*;
+mod batch;
+pub use batch::BatchLeastSquares;
 mod conf;
 pub use conf::{IterationConf, SmoothingArc};
 mod trigger;
@@ -80,6 +82,10 @@ pub struct ODProcess<
     pub estimates: Vec<K::Estimate>,
     /// Vector of residuals available after a pass
     pub residuals: Vec<Option<Residual<Msr::MeasurementSize>>>,
+    /// Vector of the measurement bias estimates after each time and measurement update, aligned with the estimates, empty if the filter does not estimate any bias
+    pub bias_estimates: Vec<BiasEstimate>,
+    /// Vector of the consider covariance analyses aligned with the estimates, empty if the filter has no consider parameters
+    pub consider_estimates: Vec<ConsiderEstimate>,
     pub ekf_trigger: Option<EkfTrigger>,
     /// Residual rejection criteria allows preventing bad measurements from affecting the estimation.
     pub resid_crit: Option<FltResid>,
@@ -137,6 +143,8 @@ where
             kf,
             estimates: Vec::with_capacity(10_000),
             residuals: Vec::with_capacity(10_000),
+            bias_estimates: Vec::new(),
+            consider_estimates: Vec::new(),
             ekf_trigger,
             resid_crit,
             cosm,
@@ -159,6 +167,8 @@ where
             kf,
             estimates: Vec::with_capacity(10_000),
             residuals: Vec::with_capacity(10_000),
+            bias_estimates: Vec::new(),
+            consider_estimates: Vec::new(),
             ekf_trigger: Some(trigger),
             resid_crit,
             cosm,
@@ -325,6 +335,8 @@ where
             // Empty the estimates and add the first smoothed estimate as the initial estimate
             self.estimates = Vec::with_capacity(measurements.len().max(self.estimates.len()));
             self.residuals = Vec::with_capacity(measurements.len().max(self.estimates.len()));
+            self.bias_estimates.clear();
+            self.consider_estimates.clear();
 
             self.kf.set_previous_estimate(&smoothed[0]);
             // And re-run the filter
@@ -565,7 +577,7 @@ where
                             {
                                 // Grab the device location
                                 let device_loc =
-                                    device.location(epoch, nominal_state.frame(), &self.cosm);
+                                    device.location(epoch, nominal_state.frame(), &self.cosm)?;
 
                                 // Switch back from extended if necessary
                                 if let Some(trigger) = &mut self.ekf_trigger {
@@ -577,7 +589,52 @@ where
 
                                 let h_tilde = S::sensitivity(msr, nominal_state, device_loc);
 
+                                if let Some(consider) = self.kf.consider_estimate() {
+                                    let h_position =
+                                        DMatrix::from_fn(Msr::MeasurementSize::dim(), 3, |i, j| {
+                                            h_tilde[(i, j)]
+                                        });
+                                    let dcm = device.location_dcm(
+                                        epoch,
+                                        nominal_state.frame(),
+                                        &self.cosm,
+                                    )?;
+                                    let h_c =
+                                        consider.msr_sensitivity(device_name, &h_position, &dcm);
+                                    self.kf.update_consider_sensitivity(h_c);
+                                }
+
                                 self.kf.update_h_tilde(h_tilde);
+                                self.kf.set_tracking_device(device_name);
+
+                                // Filters relying on sigma points need their computed observations. These are computed as a
+                                // difference from the nominal so that they share the modeling of the computed observation.
+                                if let Some(sigma_points) = self.kf.sigma_points(nominal_state)? {
+                                    let nominal_inst = device.measure_instantaneous(
+                                        nominal_state,
+                                        None,
+                                        self.cosm.clone(),
+                                    )?;
+                                    let mut sigma_obs = Vec::with_capacity(sigma_points.len());
+                                    for point in sigma_points {
+                                        let point_inst = device.measure_instantaneous(
+                                            point,
+                                            None,
+                                            self.cosm.clone(),
+                                        )?;
+                                        sigma_obs.push(match (&nominal_inst, point_inst) {
+                                            (Some(nominal_msr), Some(point_msr)) => Some(
+                                                computed_meas.observation()
+                                                    + point_msr.observation_near(
+                                                        &nominal_msr.observation(),
+                                                    )
+                                                    - nominal_msr.observation(),
+                                            ),
+                                            _ => None,
+                                        });
+                                    }
+                                    self.kf.update_sigma_observations(sigma_obs);
+                                }
 
                                 let resid_ratio_check = self
                                     .resid_crit
@@ -586,7 +643,7 @@ where
 
                                 match self.kf.measurement_update(
                                     nominal_state,
-                                    &msr.observation(),
+                                    &msr.observation_near(&computed_meas.observation()),
                                     &computed_meas.observation(),
                                     resid_ratio_check,
                                 ) {
@@ -621,6 +678,12 @@ where
 
                                         self.prop.state.reset_stm();
 
+                                        if let Some(biases) = self.kf.bias_estimate() {
+                                            self.bias_estimates.push(biases.clone());
+                                        }
+                                        if let Some(consider) = self.kf.consider_estimate() {
+                                            self.consider_estimates.push(consider.clone());
+                                        }
                                         self.estimates.push(estimate);
                                         self.residuals.push(Some(residual));
                                     }
@@ -655,6 +718,12 @@ where
                             self.estimates.push(est);
                             // We push None so that the residuals and estimates are aligned
                             self.residuals.push(None);
+                            if let Some(biases) = self.kf.bias_estimate() {
+                                self.bias_estimates.push(biases.clone());
+                            }
+                            if let Some(consider) = self.kf.consider_estimate() {
+                                self.consider_estimates.push(consider.clone());
+                            }
                         }
                         Err(e) => return Err(e),
                     }
@@ -704,6 +773,12 @@ where
                     // therefore we don't do anything different for an extended filter
                     self.estimates.push(est);
                     self.residuals.push(None);
+                    if let Some(biases) = self.kf.bias_estimate() {
+                        self.bias_estimates.push(biases.clone());
+                    }
+                    if let Some(consider) = self.kf.consider_estimate() {
+                        self.consider_estimates.push(consider.clone());
+                    }
                 }
                 Err(e) => return Err(e),
             }
@@ -733,14 +808,12 @@ where
                 msg: "No navigation trajectory to generate: run the OD process first".to_string(),
             })
         } else {
-            Ok(Traj {
-                states: self
-                    .estimates
+            Ok(Traj::from_states(
+                self.estimates
                     .iter()
                     .map(|est| est.nominal_state())
                     .collect(),
-                name: None,
-            })
+            ))
         }
     }
 }
@@ -791,6 +864,8 @@ where
             kf,
             estimates: Vec::with_capacity(10_000),
             residuals: Vec::with_capacity(10_000),
+            bias_estimates: Vec::new(),
+            consider_estimates: Vec::new(),
             resid_crit,
             ekf_trigger: None,
             init_state,
@@ -27,7 +27,7 @@ use crate::md::trajectory::Interpolatable;
 use crate::od::msr::{RangeDoppler, TrackingArc};
 use crate::od::prelude::Strand;
 use crate::od::simulator::Cadence;
-use crate::od::{GroundStation, Measurement};
+use crate::od::{GroundStation, InterlinkTxSpacecraft, Measurement, Telescope};
 use crate::{cosmic::Cosm, State};
 use crate::{linalg::allocator::Allocator, od::TrackingDeviceSim};
 use crate::{linalg::DefaultAllocator, md::prelude::Traj};
@@ -256,6 +256,107 @@ where
 
         Ok(trk)
     }
+
+    /// Builds the schedule of each device by sampling the trajectory at the sampling rate of its configuration.
+    /// A tracking strand is built from each set of contiguous samples where a noiseless measurement is available,
+    /// applying the minimum number of samples, the sample alignment and the cadence of the scheduler.
+    /// The devices do not hand off their tracking to one another. Returns an error if a device cannot compute a measurement.
+    fn sampled_schedule(&self, cosm: Arc<Cosm>) -> Result<BTreeMap<String, TrkConfig>, NyxError>
+    where
+        D: Clone,
+    {
+        let mut built_cfg = self.configs.clone();
+        for (name, device) in self.devices.iter() {
+            let cfg = &self.configs[name];
+            if let Some(scheduler) = cfg.scheduler {
+                info!("Building schedule for {name}");
+                built_cfg.get_mut(name).unwrap().scheduler = None;
+
+                // Find the visibility windows by sampling the trajectory, without any noise.
+                let mut device = device.clone();
+                let mut windows = Vec::new();
+                let mut window: Option<Strand> = None;
+                for epoch in TimeSeries::inclusive(
+                    self.trajectory.first().epoch(),
+                    self.trajectory.last().epoch(),
+                    cfg.sampling,
+                ) {
+                    let visible = device
+                        .measure(epoch, &self.trajectory, None, cosm.clone())
+                        .map_err(|e| NyxError::CustomError {
+                            msg: format!("could not build the schedule of {name}: {e}"),
+                        })?
+                        .is_some();
+                    match (visible, window.as_mut()) {
+                        (true, Some(strand)) => strand.end = epoch,
+                        (true, None) => {
+                            window = Some(Strand {
+                                start: epoch,
+                                end: epoch,
+                            })
+                        }
+                        (false, _) => {
+                            if let Some(strand) = window.take() {
+                                windows.push(strand);
+                            }
+                        }
+                    }
+                }
+                if let Some(strand) = window {
+                    windows.push(strand);
+                }
+
+                let mut strands: Vec<Strand> = Vec::new();
+                for window in windows {
+                    let (strand_start, strand_end) = (window.start, window.end);
+
+                    if strand_end - strand_start < cfg.sampling * i64::from(scheduler.min_samples) {
+                        info!(
+                            "Too few samples from {name} opportunity from {strand_start} to {strand_end}, discarding strand",
+                        );
+                        continue;
+                    }
+
+                    let mut strand_range = window;
+
+                    // If there is an alignment, apply it
+                    if let Some(alignment) = scheduler.sample_alignment {
+                        strand_range.start = strand_range.start.round(alignment);
+                        strand_range.end = strand_range.end.round(alignment);
+                    }
+
+                    if let Cadence::Intermittent { on, off } = scheduler.cadence {
+                        // Check that the next start time is within the allocated time
+                        if let Some(prev_strand) = strands.last() {
+                            if prev_strand.end + off > strand_range.start {
+                                strand_range.start = prev_strand.end + off;
+                                if strand_range.start > strand_end {
+                                    info!("Discarding {name} opportunity from {strand_start} to {strand_end} due to cadence {:?}", scheduler.cadence);
+                                    continue;
+                                }
+                            }
+                        }
+                        // Check that we aren't tracking for longer than configured
+                        if strand_range.end - strand_range.start > on {
+                            strand_range.end = strand_range.start + on;
+                        }
+                    }
+
+                    strands.push(strand_range);
+                }
+
+                if strands.is_empty() {
+                    info!("No measurements from {name}");
+                } else {
+                    info!("Built {} tracking strands for {name}", strands.len());
+                }
+
+                built_cfg.get_mut(name).unwrap().strands = Some(strands);
+            }
+        }
+
+        Ok(built_cfg)
+    }
 }
 
 impl<MsrIn, Msr, D> Display for TrackingArcSim<MsrIn, Msr, D>
@@ -573,3 +674,74 @@ impl TrackingArcSim<Spacecraft, RangeDoppler, GroundStation> {
         Ok(())
     }
 }
+
+impl<MsrIn, Msr> TrackingArcSim<MsrIn, Msr, Telescope>
+where
+    Telescope: TrackingDeviceSim<MsrIn, Msr>,
+    MsrIn: State,
+    Msr: Measurement,
+    MsrIn: Interpolatable,
+    DefaultAllocator: Allocator<f64, <MsrIn as State>::Size>
+        + Allocator<f64, <MsrIn as State>::Size, <MsrIn as State>::Size>
+        + Allocator<f64, <MsrIn as State>::VecLength>
+        + Allocator<f64, Msr::MeasurementSize, <MsrIn as State>::Size>
+        + Allocator<f64, Msr::MeasurementSize>,
+{
+    /// Builds the schedule provided the config. Requires the tracker to be a telescope.
+    ///
+    /// # Algorithm
+    ///
+    /// 1. For each telescope, sample the trajectory at the sampling rate of its configuration
+    /// 2. Check whether the object is visible at each sample, accounting for the elevation mask, the field of view, the Sun exclusion and the night-time constraint
+    /// 3. Build a tracking strand from each set of contiguous visible samples, applying the minimum number of samples, the sample alignment and the cadence of the scheduler.
+    ///
+    /// Unlike ground stations, telescopes do not hand off their tracking: several telescopes may observe the object simultaneously.
+    pub fn generate_schedule(
+        &self,
+        cosm: Arc<Cosm>,
+    ) -> Result<BTreeMap<String, TrkConfig>, NyxError> {
+        self.sampled_schedule(cosm)
+    }
+
+    /// Sets the schedule to that built in `generate_schedule`
+    pub fn build_schedule(&mut self, cosm: Arc<Cosm>) -> Result<(), NyxError> {
+        self.configs = self.generate_schedule(cosm)?;
+
+        Ok(())
+    }
+}
+
+impl<MsrIn> TrackingArcSim<MsrIn, RangeDoppler, InterlinkTxSpacecraft>
+where
+    InterlinkTxSpacecraft: TrackingDeviceSim<MsrIn, RangeDoppler>,
+    MsrIn: State,
+    MsrIn: Interpolatable,
+    DefaultAllocator: Allocator<f64, <MsrIn as State>::Size>
+        + Allocator<f64, <MsrIn as State>::Size, <MsrIn as State>::Size>
+        + Allocator<f64, <MsrIn as State>::VecLength>
+        + Allocator<f64, <RangeDoppler as Measurement>::MeasurementSize, <MsrIn as State>::Size>
+        + Allocator<f64, <RangeDoppler as Measurement>::MeasurementSize>,
+{
+    /// Builds the schedule provided the config. Requires the tracker to be an inter-satellite link.
+    ///
+    /// # Algorithm
+    ///
+    /// 1. For each link, sample the trajectory at the sampling rate of its configuration
+    /// 2. Check whether the line of sight between both spacecraft is occulted at each sample
+    /// 3. Build a tracking strand from each set of contiguous visible samples, applying the minimum number of samples, the sample alignment and the cadence of the scheduler.
+    ///
+    /// Inter-satellite links do not hand off their tracking: several links may be active simultaneously.
+    pub fn generate_schedule(
+        &self,
+        cosm: Arc<Cosm>,
+    ) -> Result<BTreeMap<String, TrkConfig>, NyxError> {
+        self.sampled_schedule(cosm)
+    }
+
+    /// Sets the schedule to that built in `generate_schedule`
+    pub fn build_schedule(&mut self, cosm: Arc<Cosm>) -> Result<(), NyxError> {
+        self.configs = self.generate_schedule(cosm)?;
+
+        Ok(())
+    }
+}
@@ -21,7 +21,7 @@ use std::sync::Arc;
 use hifitime::Epoch;
 use rand_pcg::Pcg64Mcg;
 
-use crate::linalg::DefaultAllocator;
+use crate::linalg::{DefaultAllocator, Matrix3};
 use crate::md::prelude::{Frame, Traj};
 use crate::md::trajectory::Interpolatable;
 use crate::od::{Measurement, ODError};
@@ -61,8 +61,16 @@ where
         cosm: Arc<Cosm>,
     ) -> Result<Option<Msr>, ODError>;
 
-    /// Returns the device location at the given epoch and in the given frame.
-    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Orbit;
+    /// Returns the device location at the given epoch and in the given frame, or an error if it is not available (e.g. outside of the trajectory of the device).
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError>;
+
+    /// Returns the rotation from the frame in which the device is fixed to the given frame, at the given epoch.
+    fn location_dcm(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Matrix3<f64>, ODError>;
 
     // Perform an instantaneous measurement (without integration times, i.e. one-way). Returns None if the object is not visible, else returns the measurement.
     fn measure_instantaneous(
@@ -0,0 +1,544 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::msr::{AzEl, RaDec};
+use super::noise::GaussMarkov;
+use super::{ODError, ODFrameSnafu, ODTrajSnafu, TrackingDeviceSim};
+use crate::cosmic::{Cosm, Frame, LightTimeCalc, Orbit};
+use crate::io::{frame_from_str, frame_to_str, ConfigRepr, Configurable};
+use crate::linalg::{Matrix3, Vector3};
+use crate::md::prelude::Traj;
+use crate::time::Epoch;
+use crate::Spacecraft;
+use rand_pcg::Pcg64Mcg;
+use serde_derive::{Deserialize, Serialize};
+use snafu::ResultExt;
+use std::fmt;
+use std::sync::Arc;
+
+/// Circular field of view of a telescope with a fixed pointing.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FieldOfView {
+    /// Azimuth of the boresight, measured clockwise from the North, in degrees
+    pub azimuth_deg: f64,
+    /// Elevation of the boresight, in degrees
+    pub elevation_deg: f64,
+    /// Half angle of the field of view, in degrees
+    pub half_angle_deg: f64,
+}
+
+impl FieldOfView {
+    /// Returns the unit vector of the boresight in the topocentric (SEZ) frame
+    fn boresight_sez(&self) -> Vector3<f64> {
+        let (az, el) = (
+            self.azimuth_deg.to_radians(),
+            self.elevation_deg.to_radians(),
+        );
+        Vector3::new(-el.cos() * az.cos(), el.cos() * az.sin(), el.sin())
+    }
+}
+
+/// Telescope defines an optical tracker measuring the topocentric right ascension and declination, or the azimuth and elevation, of an object.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Telescope {
+    pub name: String,
+    /// in degrees
+    pub elevation_mask_deg: f64,
+    /// in degrees
+    pub latitude_deg: f64,
+    /// in degrees
+    pub longitude_deg: f64,
+    /// in km
+    pub height_km: f64,
+    /// Frame in which this telescope is defined
+    #[serde(serialize_with = "frame_to_str", deserialize_with = "frame_from_str")]
+    pub frame: Frame,
+    /// Field of view if the telescope has a fixed pointing. If unset, the telescope tracks the object.
+    #[serde(default)]
+    pub field_of_view: Option<FieldOfView>,
+    /// Minimum angle between the line of sight and the Sun, in degrees
+    pub sun_exclusion_deg: f64,
+    /// Maximum elevation of the Sun for the telescope to observe (night-time constraint), in degrees
+    pub max_sun_elevation_deg: f64,
+    /// Light time and stellar aberration corrections of the line of sight
+    pub light_time_correction: LightTimeCalc,
+    /// Noise on the timestamp of the measurement
+    pub timestamp_noise_s: Option<GaussMarkov>,
+    /// Noise on the first angle of the measurement (right ascension or azimuth), in degrees
+    pub first_angle_noise_deg: Option<GaussMarkov>,
+    /// Noise on the second angle of the measurement (declination or elevation), in degrees
+    pub second_angle_noise_deg: Option<GaussMarkov>,
+}
+
+impl Telescope {
+    /// Initializes a tracking telescope with an elevation mask of 10 degrees, a Sun exclusion angle of 30 degrees,
+    /// observing when the Sun is 12 degrees below the horizon (nautical twilight) and with light time correction.
+    /// Each angle is affected by its own copy of the provided noise process, so the noises of both angles are independent.
+    pub fn new(
+        name: String,
+        latitude_deg: f64,
+        longitude_deg: f64,
+        height_km: f64,
+        angle_noise_deg: GaussMarkov,
+        frame: Frame,
+    ) -> Self {
+        Self {
+            name,
+            elevation_mask_deg: 10.0,
+            latitude_deg,
+            longitude_deg,
+            height_km,
+            frame,
+            field_of_view: None,
+            sun_exclusion_deg: 30.0,
+            max_sun_elevation_deg: -12.0,
+            light_time_correction: LightTimeCalc::LightTime,
+            timestamp_noise_s: None,
+            first_angle_noise_deg: Some(angle_noise_deg.clone()),
+            second_angle_noise_deg: Some(angle_noise_deg),
+        }
+    }
+
+    /// Return this telescope as an orbit in its current frame
+    pub fn to_orbit(&self, epoch: Epoch) -> Orbit {
+        Orbit::from_geodesic(
+            self.latitude_deg,
+            self.longitude_deg,
+            self.height_km,
+            epoch,
+            self.frame,
+        )
+    }
+
+    /// Returns the state of this telescope (tx) and the apparent state of the object (rx) in the frame of the object.
+    ///
+    /// The light time and stellar aberration corrections are those of the celestial states of the `Cosm`, so the frame of the object
+    /// must share the orientation of the solar system barycenter frame. The object is queried at the light time corrected epoch with
+    /// `rx_at`, e.g. from its trajectory; if the latter returns None, the object is propagated with two-body dynamics from `rx`.
+    pub fn line_of_sight<F>(
+        &self,
+        rx: Orbit,
+        rx_at: F,
+        cosm: &Cosm,
+    ) -> Result<(Orbit, Orbit), ODError>
+    where
+        F: Fn(Epoch) -> Option<Orbit>,
+    {
+        let tx = cosm
+            .try_frame_chg(&self.to_orbit(rx.epoch), rx.frame)
+            .with_context(|_| ODFrameSnafu)?;
+
+        let ssb2k = cosm.frame("SSB J2000");
+        let obs = cosm
+            .try_frame_chg(&tx, ssb2k)
+            .with_context(|_| ODFrameSnafu)?;
+        let rho = cosm
+            .try_light_time_correction(
+                obs,
+                |epoch| {
+                    let tgt = match rx_at(epoch) {
+                        Some(tgt) => tgt,
+                        None if epoch == rx.epoch => rx,
+                        None => rx.at_epoch(epoch)?,
+                    };
+                    cosm.try_frame_chg(&tgt, ssb2k)
+                },
+                rx.epoch,
+                rx.frame,
+                self.light_time_correction,
+            )
+            .with_context(|_| ODFrameSnafu)?;
+
+        let mut apparent = rx;
+        apparent.x_km = tx.x_km + rho.x_km;
+        apparent.y_km = tx.y_km + rho.y_km;
+        apparent.z_km = tx.z_km + rho.z_km;
+
+        Ok((tx, apparent))
+    }
+
+    /// Returns the provided line of sight vector, expressed in the provided frame, in the topocentric (SEZ) frame of this telescope.
+    ///
+    /// The local vertical is the geodetic normal at the latitude and longitude of this telescope.
+    pub fn to_sez(
+        &self,
+        rho: &Vector3<f64>,
+        frame: Frame,
+        epoch: Epoch,
+        cosm: &Cosm,
+    ) -> Result<Vector3<f64>, ODError> {
+        let dcm_to_fixed = cosm
+            .try_position_dcm_from_to(&frame, &self.frame, epoch)
+            .with_context(|_| ODFrameSnafu)?;
+        // Note: we're only looking at the radii so we don't need to apply the transport theorem here.
+        Ok(AzEl::sez_dcm(self.latitude_deg, self.longitude_deg) * dcm_to_fixed * rho)
+    }
+
+    /// Returns whether the object is visible from this telescope given the line of sight from this telescope (tx) to its apparent state (rx).
+    /// The object must be above the elevation mask, in the field of view if the pointing is fixed, away from the Sun, and the Sun must be low enough below the horizon.
+    pub fn is_visible(&self, tx: &Orbit, rx: &Orbit, cosm: &Cosm) -> Result<bool, ODError> {
+        let epoch = rx.epoch;
+        let rho = rx.radius() - tx.radius();
+        let rho_sez = self.to_sez(&rho, rx.frame, epoch, cosm)?;
+        let (_, elevation_deg) = AzEl::from_sez(&rho_sez);
+
+        if elevation_deg < self.elevation_mask_deg {
+            debug!(
+                "{} (el. mask {:.3} deg), object at {elevation_deg:.3} deg -- no measurement",
+                self.name, self.elevation_mask_deg
+            );
+            return Ok(false);
+        }
+
+        if let Some(fov) = &self.field_of_view {
+            let off_boresight_deg = rho_sez.angle(&fov.boresight_sez()).to_degrees();
+            if off_boresight_deg > fov.half_angle_deg {
+                debug!(
+                    "{} object {off_boresight_deg:.3} deg off boresight -- no measurement",
+                    self.name
+                );
+                return Ok(false);
+            }
+        }
+
+        let sun = cosm
+            .try_celestial_state(
+                &cosm.frame("Sun J2000").ephem_path(),
+                epoch,
+                rx.frame,
+                LightTimeCalc::None,
+            )
+            .with_context(|_| ODFrameSnafu)?;
+        let rho_sun = sun.radius() - tx.radius();
+
+        let sun_angle_deg = rho.angle(&rho_sun).to_degrees();
+        if sun_angle_deg < self.sun_exclusion_deg {
+            debug!(
+                "{} object {sun_angle_deg:.3} deg from the Sun -- no measurement",
+                self.name
+            );
+            return Ok(false);
+        }
+
+        let (_, sun_elevation_deg) = AzEl::from_sez(&self.to_sez(&rho_sun, rx.frame, epoch, cosm)?);
+        if sun_elevation_deg > self.max_sun_elevation_deg {
+            debug!(
+                "{} Sun at {sun_elevation_deg:.3} deg elevation -- no measurement",
+                self.name
+            );
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the timestamp noise and the noises of both angles for this telescope at the provided epoch.
+    fn noises(
+        &mut self,
+        epoch: Epoch,
+        rng: Option<&mut Pcg64Mcg>,
+    ) -> Result<(f64, f64, f64), ODError> {
+        match rng {
+            Some(rng) => {
+                // Add the angle noises, or return an error if they're not configured.
+                let angle_1_noise_deg = self
+                    .first_angle_noise_deg
+                    .as_mut()
+                    .ok_or(ODError::NoiseNotConfigured { kind: "Angle" })?
+                    .next_bias(epoch, rng);
+                let angle_2_noise_deg = self
+                    .second_angle_noise_deg
+                    .as_mut()
+                    .ok_or(ODError::NoiseNotConfigured { kind: "Angle" })?
+                    .next_bias(epoch, rng);
+
+                // Only add the epoch noise if it's configured, it's valid to not have any noise on the clock.
+                let timestamp_noise_s = match self.timestamp_noise_s.as_mut() {
+                    Some(timestamp_noise) => timestamp_noise.next_bias(epoch, rng),
+                    None => 0.0,
+                };
+
+                Ok((timestamp_noise_s, angle_1_noise_deg, angle_2_noise_deg))
+            }
+            None => Ok((0.0, 0.0, 0.0)),
+        }
+    }
+
+    /// Measures the right ascension and declination of the object, if visible.
+    fn measure_radec<F>(
+        &mut self,
+        rx: Orbit,
+        rx_at: F,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<RaDec>, ODError>
+    where
+        F: Fn(Epoch) -> Option<Orbit>,
+    {
+        let (tx, rx) = self.line_of_sight(rx, rx_at, cosm)?;
+        if !self.is_visible(&tx, &rx, cosm)? {
+            return Ok(None);
+        }
+
+        let (timestamp_noise_s, ra_noise_deg, dec_noise_deg) = self.noises(rx.epoch, rng)?;
+
+        Ok(Some(RaDec::new(
+            tx,
+            rx,
+            timestamp_noise_s,
+            ra_noise_deg,
+            dec_noise_deg,
+        )))
+    }
+
+    /// Measures the azimuth and elevation of the object, if visible.
+    fn measure_azel<F>(
+        &mut self,
+        rx: Orbit,
+        rx_at: F,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: &Cosm,
+    ) -> Result<Option<AzEl>, ODError>
+    where
+        F: Fn(Epoch) -> Option<Orbit>,
+    {
+        let (tx, rx) = self.line_of_sight(rx, rx_at, cosm)?;
+        if !self.is_visible(&tx, &rx, cosm)? {
+            return Ok(None);
+        }
+
+        let (timestamp_noise_s, az_noise_deg, el_noise_deg) = self.noises(rx.epoch, rng)?;
+        let rho_sez = self.to_sez(&(rx.radius() - tx.radius()), rx.frame, rx.epoch, cosm)?;
+
+        Ok(Some(AzEl::new(
+            rx.epoch,
+            &rho_sez,
+            timestamp_noise_s,
+            az_noise_deg,
+            el_noise_deg,
+        )))
+    }
+}
+
+impl ConfigRepr for Telescope {}
+
+impl Configurable for Telescope {
+    type IntermediateRepr = Telescope;
+
+    fn from_config(
+        cfg: Self::IntermediateRepr,
+        _cosm: Arc<Cosm>,
+    ) -> Result<Self, crate::io::ConfigError>
+    where
+        Self: Sized,
+    {
+        Ok(cfg)
+    }
+
+    fn to_config(&self) -> Result<Self::IntermediateRepr, crate::io::ConfigError> {
+        Ok(self.clone())
+    }
+}
+
+impl TrackingDeviceSim<Orbit, RaDec> for Telescope {
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Orbit>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RaDec>, ODError> {
+        let rx = traj.at(epoch).with_context(|_| ODTrajSnafu)?;
+        self.measure_radec(rx, |epoch| traj.at(epoch).ok(), rng, &cosm)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError> {
+        cosm.try_frame_chg(&self.to_orbit(epoch), frame)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn location_dcm(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Matrix3<f64>, ODError> {
+        cosm.try_position_dcm_from_to(&self.frame, &frame, epoch)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RaDec>, ODError> {
+        self.measure_radec(rx, |_| None, rng, &cosm)
+    }
+}
+
+impl TrackingDeviceSim<Spacecraft, RaDec> for Telescope {
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Spacecraft>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RaDec>, ODError> {
+        let rx = traj.at(epoch).with_context(|_| ODTrajSnafu)?;
+        self.measure_radec(
+            rx.orbit,
+            |epoch| traj.at(epoch).ok().map(|sc| sc.orbit),
+            rng,
+            &cosm,
+        )
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError> {
+        cosm.try_frame_chg(&self.to_orbit(epoch), frame)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn location_dcm(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Matrix3<f64>, ODError> {
+        cosm.try_position_dcm_from_to(&self.frame, &frame, epoch)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Spacecraft,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<RaDec>, ODError> {
+        self.measure_radec(rx.orbit, |_| None, rng, &cosm)
+    }
+}
+
+impl TrackingDeviceSim<Orbit, AzEl> for Telescope {
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Orbit>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<AzEl>, ODError> {
+        let rx = traj.at(epoch).with_context(|_| ODTrajSnafu)?;
+        self.measure_azel(rx, |epoch| traj.at(epoch).ok(), rng, &cosm)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError> {
+        cosm.try_frame_chg(&self.to_orbit(epoch), frame)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn location_dcm(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Matrix3<f64>, ODError> {
+        cosm.try_position_dcm_from_to(&self.frame, &frame, epoch)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Orbit,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<AzEl>, ODError> {
+        self.measure_azel(rx, |_| None, rng, &cosm)
+    }
+}
+
+impl TrackingDeviceSim<Spacecraft, AzEl> for Telescope {
+    fn measure(
+        &mut self,
+        epoch: Epoch,
+        traj: &Traj<Spacecraft>,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<AzEl>, ODError> {
+        let rx = traj.at(epoch).with_context(|_| ODTrajSnafu)?;
+        self.measure_azel(
+            rx.orbit,
+            |epoch| traj.at(epoch).ok().map(|sc| sc.orbit),
+            rng,
+            &cosm,
+        )
+    }
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn location(&self, epoch: Epoch, frame: Frame, cosm: &Cosm) -> Result<Orbit, ODError> {
+        cosm.try_frame_chg(&self.to_orbit(epoch), frame)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn location_dcm(
+        &self,
+        epoch: Epoch,
+        frame: Frame,
+        cosm: &Cosm,
+    ) -> Result<Matrix3<f64>, ODError> {
+        cosm.try_position_dcm_from_to(&self.frame, &frame, epoch)
+            .with_context(|_| ODFrameSnafu)
+    }
+
+    fn measure_instantaneous(
+        &mut self,
+        rx: Spacecraft,
+        rng: Option<&mut Pcg64Mcg>,
+        cosm: Arc<Cosm>,
+    ) -> Result<Option<AzEl>, ODError> {
+        self.measure_azel(rx.orbit, |_| None, rng, &cosm)
+    }
+}
+
+impl fmt::Display for Telescope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} telescope (lat.: {:.4} deg    long.: {:.4} deg    alt.: {:.3} m) [{}]",
+            self.name,
+            self.latitude_deg,
+            self.longitude_deg,
+            self.height_km * 1e3,
+            self.frame,
+        )
+    }
+}
@@ -0,0 +1,157 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::linalg::allocator::Allocator;
+use crate::linalg::{DefaultAllocator, OVector};
+use crate::md::trajectory::TrajError;
+use crate::time::{Duration, Epoch};
+use crate::State;
+
+/// The continuous extension (or dense output) of a single integration step.
+///
+/// The state vector within the step is the polynomial `y(θ) = y_n + c_1 θ + c_2 θ^2 + ... + c_d θ^d`, where `θ = (t - t_n) / h`
+/// is the fraction of the step `h` since the start of the step. The coefficients are computed by the propagator from the stages of
+/// the step, so evaluating the interpolant does not call the dynamics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepInterpolant<S: State>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    /// State at the start of the step, used as the template of the interpolated states
+    pub start: S,
+    /// Step size, negative when propagating backward
+    pub step: Duration,
+    /// Coefficients of the polynomial, from the first degree to the highest
+    pub coeffs: Vec<OVector<f64, S::VecLength>>,
+}
+
+impl<S: State> StepInterpolant<S>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    /// Builds the continuous extension of a Runge Kutta step from its stages, where the weight of stage `i` is the polynomial
+    /// `b_i(θ) = \sum_j d_{ij} θ^j` and the `d_{ij}` are stored by stage in `dense_coeffs`.
+    pub(crate) fn from_stages(
+        start: S,
+        step: Duration,
+        dense_coeffs: &[f64],
+        k: &[OVector<f64, S::VecLength>],
+    ) -> Self {
+        let degree = dense_coeffs.len() / k.len();
+        let step_s = step.to_seconds();
+        let mut coeffs = Vec::with_capacity(degree);
+        for j in 0..degree {
+            let mut c_j = OVector::<f64, S::VecLength>::zeros();
+            for (i, ki) in k.iter().enumerate() {
+                let d_ij = dense_coeffs[i * degree + j];
+                if d_ij != 0.0 {
+                    c_j += step_s * d_ij * ki;
+                }
+            }
+            coeffs.push(c_j);
+        }
+        Self {
+            start,
+            step,
+            coeffs,
+        }
+    }
+
+    /// Builds the cubic Hermite interpolant of a step from the state vectors and their derivatives at both ends of the step.
+    pub(crate) fn hermite(
+        start: S,
+        step: Duration,
+        d_start: &OVector<f64, S::VecLength>,
+        end: &OVector<f64, S::VecLength>,
+        d_end: &OVector<f64, S::VecLength>,
+    ) -> Self {
+        let step_s = step.to_seconds();
+        let delta = end - start.as_vector();
+        let c1 = step_s * d_start;
+        let c2 = 3.0 * &delta - step_s * (2.0 * d_start + d_end);
+        let c3 = -2.0 * &delta + step_s * (d_start + d_end);
+        Self {
+            start,
+            step,
+            coeffs: vec![c1, c2, c3],
+        }
+    }
+
+    /// Returns the degree of the interpolating polynomial
+    pub fn degree(&self) -> usize {
+        self.coeffs.len()
+    }
+
+    /// Returns the epoch of the start of the step
+    pub fn start_epoch(&self) -> Epoch {
+        self.start.epoch()
+    }
+
+    /// Returns the epoch of the end of the step
+    pub fn end_epoch(&self) -> Epoch {
+        self.start.epoch() + self.step
+    }
+
+    /// Returns whether the provided epoch is within this step (boundaries included)
+    pub fn contains(&self, epoch: Epoch) -> bool {
+        step_contains(self.start_epoch(), self.step, epoch)
+    }
+
+    /// Evaluates the interpolant at the provided epoch, which must be within the step.
+    pub fn at(&self, epoch: Epoch) -> Result<S, TrajError> {
+        evaluate_step(&self.start, self.step, &self.coeffs, epoch)
+    }
+}
+
+/// Returns whether the provided epoch is within the step of the provided size from the start epoch (boundaries included)
+pub(crate) fn step_contains(start_epoch: Epoch, step: Duration, epoch: Epoch) -> bool {
+    let end_epoch = start_epoch + step;
+    let (lower, upper) = if step.is_negative() {
+        (end_epoch, start_epoch)
+    } else {
+        (start_epoch, end_epoch)
+    };
+    lower <= epoch && epoch <= upper
+}
+
+/// Evaluates the polynomial of a step from its start state and coefficients at the provided epoch, which must be within the step.
+pub(crate) fn evaluate_step<S: State>(
+    start: &S,
+    step: Duration,
+    coeffs: &[OVector<f64, S::VecLength>],
+    epoch: Epoch,
+) -> Result<S, TrajError>
+where
+    DefaultAllocator:
+        Allocator<f64, S::VecLength> + Allocator<f64, S::Size> + Allocator<f64, S::Size, S::Size>,
+{
+    if !step_contains(start.epoch(), step, epoch) {
+        return Err(TrajError::NoInterpolationData { epoch });
+    }
+    let theta = (epoch - start.epoch()).to_seconds() / step.to_seconds();
+    // Horner's scheme
+    let mut poly = OVector::<f64, S::VecLength>::zeros();
+    for c_j in coeffs.iter().rev() {
+        poly = theta * (poly + c_j);
+    }
+    let mut state = *start;
+    state.set(epoch, &(start.as_vector() + poly));
+    Ok(state)
+}
@@ -239,6 +239,54 @@ impl ErrorCtrl for RSSCartesianState {
     }
 }
 
+/// An RSS state error control for the modified equinoctial elements `[p, f, g, h, k, L]` integrated by `EquinoctialDynamics`,
+/// followed by any other component (e.g. the fuel mass).
+///
+/// The error is relative on the semi-parameter `p`, and absolute on the eccentricity vector `(f, g)`, on the node vector `(h, k)`
+/// and on the true longitude `L`, since these are dimensionless (or in radians) and often close to zero.
+#[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct RSSEquinoctialState;
+impl ErrorCtrl for RSSEquinoctialState {
+    fn estimate<N: DimName>(
+        error_est: &OVector<f64, N>,
+        candidate: &OVector<f64, N>,
+        cur_state: &OVector<f64, N>,
+    ) -> f64
+    where
+        DefaultAllocator: Allocator<f64, N>,
+    {
+        if N::dim() >= 6 {
+            let err_p = RSSState::estimate::<U1>(
+                &error_est.fixed_rows::<1>(0).into_owned(),
+                &candidate.fixed_rows::<1>(0).into_owned(),
+                &cur_state.fixed_rows::<1>(0).into_owned(),
+            );
+            let err_ecc = error_est.fixed_rows::<2>(1).norm();
+            let err_node = error_est.fixed_rows::<2>(3).norm();
+            let err_longitude = error_est[5].abs();
+            let mut remaining_err = 0.0;
+            for i in 6..N::dim() {
+                let this_err = RSSState::estimate::<U1>(
+                    &error_est.fixed_rows::<1>(i).into_owned(),
+                    &candidate.fixed_rows::<1>(i).into_owned(),
+                    &cur_state.fixed_rows::<1>(i).into_owned(),
+                );
+                if this_err > remaining_err {
+                    remaining_err = this_err;
+                }
+            }
+            remaining_err
+                .max(err_p)
+                .max(err_ecc)
+                .max(err_node)
+                .max(err_longitude)
+        } else {
+            RSSState::estimate(error_est, candidate, cur_state)
+        }
+    }
+}
+
 /// An RSS state error control which effectively for the provided vector
 /// composed of two vectors of the same unit, both of size 3 (e.g. position + velocity).
 #[derive(Clone, Copy)]
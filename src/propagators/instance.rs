@@ -17,8 +17,12 @@
 */
 
 use super::error_ctrl::ErrorCtrl;
-use super::{DynamicsSnafu, IntegrationDetails, PropagationError, Propagator};
+use super::{
+    lagrange_weights, spans_steps, DynamicsSnafu, IntegrationDetails, MultistepCoeffs,
+    PropagationError, Propagator, StepInterpolant,
+};
 use crate::dynamics::Dynamics;
+use crate::io::ConfigError;
 use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, OVector};
 use crate::md::trajectory::{Interpolatable, Traj};
@@ -34,6 +38,12 @@ use std::sync::mpsc::{channel, Sender};
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
+/// Maximum number of fixed point iterations to solve for the stages of an implicit integrator
+const IMPLICIT_MAX_ITER: usize = 50;
+/// Smallest convergence threshold of the fixed point iterations, at the rounding error of the iterations, used when the
+/// tolerance of the propagator options is smaller (e.g. zero for a fixed step)
+const IMPLICIT_MIN_REL_TOL: f64 = 10.0 * f64::EPSILON;
+
 /// A Propagator allows propagating a set of dynamics forward or backward in time.
 /// It is an EventTracker, without any event tracking. It includes the options, the integrator
 /// details of the previous step, and the set of coefficients used for the monomorphic instance.
@@ -55,6 +65,20 @@ where
     pub(crate) fixed_step: bool,
     // Allows us to do pre-allocation of the ki vectors
     pub(crate) k: Vec<OVector<f64, <D::StateType as State>::VecLength>>,
+    // Epochs and derivatives at the previous steps of a multistep method, most recent first
+    pub(crate) history: Vec<(Epoch, OVector<f64, <D::StateType as State>::VecLength>)>,
+    // Epoch and integration vector at the end of the latest step of a multistep method, to detect changes of the state outside of the integrator
+    pub(crate) history_end: Option<(Epoch, OVector<f64, <D::StateType as State>::VecLength>)>,
+    // Whether to compute the continuous extension of each step
+    pub(crate) dense_output: bool,
+    // Continuous extension of the latest step, if the dense output is enabled
+    pub(crate) interpolant: Option<StepInterpolant<D::StateType>>,
+    // Energy at the creation of this instance, if the dynamics define one
+    pub(crate) initial_energy: Option<f64>,
+    // Whether the steps are in the fictitious time of the time transformation of the dynamics, if they define one
+    pub(crate) fictitious_time: bool,
+    // Derivative of the physical time with respect to the fictitious time for each of the ki vectors (one without time transformation)
+    pub(crate) dt_ds: Vec<f64>,
 }
 
 impl<'a, D: Dynamics, E: ErrorCtrl> PropInstance<'a, D, E>
@@ -70,11 +94,28 @@ where
         self.fixed_step = fixed;
     }
 
+    /// Enables the dense output: the continuous extension of each step is computed, and stored in the trajectories generated by this instance.
+    ///
+    /// The continuous extension is native for integrators which have one (`Dormand45` and `Verner56`), and otherwise is a cubic Hermite
+    /// interpolation of each step, which costs an additional call to the dynamics per step. Trajectories store the coefficients of the
+    /// polynomial of each step (as many vectors as its degree) in addition to the states.
+    /// It is not available for dynamics which integrate other coordinates than the state vector (e.g. `EquinoctialDynamics`).
+    pub fn with_dense_output(mut self) -> Self {
+        self.dense_output = true;
+        self
+    }
+
+    /// Returns the continuous extension of the latest step, if the dense output is enabled.
+    pub fn latest_interpolant(&self) -> Option<&StepInterpolant<D::StateType>> {
+        self.interpolant.as_ref()
+    }
+
     #[allow(clippy::erasing_op)]
     fn for_duration_channel_option(
         &mut self,
         duration: Duration,
         maybe_tx_chan: Option<Sender<D::StateType>>,
+        maybe_tx_dense: Option<Sender<StepInterpolant<D::StateType>>>,
     ) -> Result<D::StateType, PropagationError> {
         if duration == 0 * Unit::Second {
             return Ok(self.state);
@@ -102,41 +143,30 @@ where
         }
         loop {
             let epoch = self.state.epoch();
-            if (!backprop && epoch + self.step_size > stop_time)
-                || (backprop && epoch + self.step_size <= stop_time)
+            let next_step = self.next_time_step()?;
+            if (!backprop && epoch + next_step <= stop_time)
+                || (backprop && epoch + next_step > stop_time)
             {
-                if stop_time == epoch {
-                    // No propagation necessary
-                    #[cfg(not(target_arch = "wasm32"))]
-                    {
-                        if log_progress {
-                            let tock: Duration = tick.elapsed().into();
-                            debug!("Done in {}", tock);
-                        }
-                    }
-                    return Ok(self.state);
-                }
-                // Take one final step of exactly the needed duration until the stop time
-                let prev_step_size = self.step_size;
-                let prev_step_kind = self.fixed_step;
-                self.set_step(stop_time - epoch, true);
-
+                let prev_state = self.state;
                 self.single_step()?;
-
-                // Publish to channel if provided
-                if let Some(ref chan) = maybe_tx_chan {
-                    if let Err(e) = chan.send(self.state) {
-                        warn!("{} when sending on channel", e)
+                let next_epoch = self.state.epoch();
+                if (!backprop && next_epoch <= stop_time) || (backprop && next_epoch >= stop_time) {
+                    // Publish to channel if provided
+                    if let Some(ref chan) = maybe_tx_chan {
+                        if let Err(e) = chan.send(self.state) {
+                            warn!("{} when sending on channel", e)
+                        }
                     }
+                    self.publish_interpolant(&maybe_tx_dense);
+                    continue;
                 }
+                // With a time transformation, the duration of the step may exceed its estimate: reject this step
+                // which went past the stop time, and end exactly at the stop time instead.
+                self.state = prev_state;
+            }
 
-                // Restore the step size for subsequent calls
-                self.set_step(prev_step_size, prev_step_kind);
-
-                if backprop {
-                    self.step_size = -self.step_size; // Restore to a positive step size
-                }
-
+            if stop_time == epoch {
+                // No propagation necessary
                 #[cfg(not(target_arch = "wasm32"))]
                 {
                     if log_progress {
@@ -144,23 +174,47 @@ where
                         debug!("Done in {}", tock);
                     }
                 }
-
                 return Ok(self.state);
-            } else {
-                self.single_step()?;
-                // Publish to channel if provided
-                if let Some(ref chan) = maybe_tx_chan {
-                    if let Err(e) = chan.send(self.state) {
-                        warn!("{} when sending on channel", e)
-                    }
+            }
+            // Take one final step of exactly the needed duration until the stop time
+            let prev_step_size = self.step_size;
+            let prev_step_kind = self.fixed_step;
+            self.set_step(stop_time - epoch, true);
+            // This step is in physical time to end exactly at the stop time
+            self.fictitious_time = false;
+
+            let result = self.single_step();
+            self.fictitious_time = true;
+            // Restore the step size for subsequent calls
+            self.set_step(prev_step_size, prev_step_kind);
+            if backprop {
+                self.step_size = -self.step_size; // Restore to a positive step size
+            }
+            result?;
+
+            // Publish to channel if provided
+            if let Some(ref chan) = maybe_tx_chan {
+                if let Err(e) = chan.send(self.state) {
+                    warn!("{} when sending on channel", e)
+                }
+            }
+            self.publish_interpolant(&maybe_tx_dense);
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                if log_progress {
+                    let tock: Duration = tick.elapsed().into();
+                    debug!("Done in {}", tock);
                 }
             }
+
+            return Ok(self.state);
         }
     }
 
     /// This method propagates the provided Dynamics for the provided duration.
     pub fn for_duration(&mut self, duration: Duration) -> Result<D::StateType, PropagationError> {
-        self.for_duration_channel_option(duration, None)
+        self.for_duration_channel_option(duration, None, None)
     }
 
     /// This method propagates the provided Dynamics for the provided duration and publishes each state on the channel.
@@ -169,7 +223,7 @@ where
         duration: Duration,
         tx_chan: Sender<D::StateType>,
     ) -> Result<D::StateType, PropagationError> {
-        self.for_duration_channel_option(duration, Some(tx_chan))
+        self.for_duration_channel_option(duration, Some(tx_chan), None)
     }
 
     /// Propagates the provided Dynamics until the provided epoch. Returns the end state.
@@ -203,16 +257,29 @@ where
         let mut traj = Traj::new();
         let start_state = self.state;
 
-        let rx = {
+        let (rx, rx_dense) = {
             // Channels that have a single state for the propagator
             let (tx, rx) = channel();
+            // And the continuous extension of each step, if enabled
+            let (tx_dense, rx_dense) = channel();
             // Propagate the dynamics
             // Note that the end state is also sent on the channel before the return of this function.
-            end_state = self.for_duration_with_channel(duration, tx)?;
-            rx
+            end_state = self.for_duration_channel_option(
+                duration,
+                Some(tx),
+                if self.dense_output {
+                    Some(tx_dense)
+                } else {
+                    None
+                },
+            )?;
+            (rx, rx_dense)
         };
 
         traj.states = rx.into_iter().par_bridge().collect();
+        for step in rx_dense {
+            traj.push_step(step);
+        }
         // Push the start state -- will be reordered in the finalize call.
         // For some reason, this must happen at the end -- can't figure out why.
         traj.states.push(start_state);
@@ -279,17 +346,156 @@ where
 
     /// Take a single propagator step and emit the result on the TX channel (if enabled)
     pub fn single_step(&mut self) -> Result<(), PropagationError> {
+        let start = self.state;
         let (t, state_vec) = self.derive()?;
-        self.state.set(self.state.epoch() + t, &state_vec);
+        if self.dense_output {
+            self.interpolant = self.build_interpolant(start, t, &state_vec)?;
+        }
+        let epoch = self.state.epoch() + t;
+        self.prop
+            .dynamics
+            .set_integration_vector(&mut self.state, epoch, &state_vec)
+            .with_context(|_| DynamicsSnafu)?;
         self.state = self
             .prop
             .dynamics
             .finally(self.state)
             .with_context(|_| DynamicsSnafu)?;
 
+        if let (Some(initial_energy), Some(energy)) =
+            (self.initial_energy, self.prop.dynamics.energy(&self.state))
+        {
+            self.details.energy_drift = Some((energy - initial_energy) / initial_energy.abs());
+        }
+        if self.prop.multistep.is_some() {
+            // The history of the multistep method remains valid until the state is modified outside of the integrator
+            self.history_end = Some((self.state.epoch(), self.integration_vector()?));
+        }
+
         Ok(())
     }
 
+    /// Builds the continuous extension of the step which was just taken from the `start` state.
+    /// There is none if the dynamics do not integrate the state vector itself, because the interpolant is a polynomial of the state vector.
+    fn build_interpolant(
+        &self,
+        start: D::StateType,
+        step: Duration,
+        end: &OVector<f64, <D::StateType as State>::VecLength>,
+    ) -> Result<Option<StepInterpolant<D::StateType>>, PropagationError> {
+        if self
+            .prop
+            .dynamics
+            .integration_vector(&start)
+            .with_context(|_| DynamicsSnafu)?
+            .is_some()
+        {
+            return Ok(None);
+        }
+        // The stages are in fictitious time if the step was regularized
+        let regularized = self.time_transformation(&start.as_vector()).is_some();
+        if self.prop.multistep.is_none() && !self.prop.dense_coeffs.is_empty() && !regularized {
+            return Ok(Some(StepInterpolant::from_stages(
+                start,
+                step,
+                self.prop.dense_coeffs,
+                &self.k,
+            )));
+        }
+        // The derivative at the start of the step is the first stage of explicit integrators, or the latest derivative of the multistep history
+        let d_start = match self.prop.multistep {
+            Some(_) => self.history[0].1.clone(),
+            None if self.prop.implicit || regularized => self
+                .prop
+                .dynamics
+                .eom(0.0, &start.as_vector(), &start)
+                .with_context(|_| DynamicsSnafu)?,
+            None => self.k[0].clone(),
+        };
+        let d_end = self
+            .prop
+            .dynamics
+            .eom(step.to_seconds(), end, &start)
+            .with_context(|_| DynamicsSnafu)?;
+        Ok(Some(StepInterpolant::hermite(
+            start, step, &d_start, end, &d_end,
+        )))
+    }
+
+    /// Returns the derivative of the physical time with respect to the fictitious time at the provided state vector,
+    /// if the dynamics define a time transformation and the steps are in fictitious time.
+    fn time_transformation(
+        &self,
+        state_vec: &OVector<f64, <D::StateType as State>::VecLength>,
+    ) -> Option<f64> {
+        if self.fictitious_time {
+            self.prop
+                .dynamics
+                .time_transformation(state_vec, &self.state)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the duration of the next step in physical time. With a time transformation, this is twice the step in fictitious time scaled
+    /// at the current state, as a margin because the duration of the step also depends on the intermediate states (a step which
+    /// still goes past the stop time is rejected).
+    fn next_time_step(&self) -> Result<Duration, PropagationError> {
+        // Most dynamics do not define a time transformation: check it from the state vector so that the integration vector is only computed otherwise
+        if self.time_transformation(&self.state.as_vector()).is_none() {
+            return Ok(self.step_size);
+        }
+        match self.time_transformation(&self.integration_vector()?) {
+            Some(dt_ds) => Ok(self.step_size * (2.0 * dt_ds)),
+            None => Ok(self.step_size),
+        }
+    }
+
+    /// Returns the vector which is integrated from the current state, i.e. the state vector unless the dynamics integrate other coordinates.
+    fn integration_vector(
+        &self,
+    ) -> Result<OVector<f64, <D::StateType as State>::VecLength>, PropagationError> {
+        Ok(self
+            .prop
+            .dynamics
+            .integration_vector(&self.state)
+            .with_context(|_| DynamicsSnafu)?
+            .unwrap_or_else(|| self.state.as_vector()))
+    }
+
+    /// Evaluates a stage of a Runge Kutta method, i.e. the equations of motion scaled by the time transformation, if any.
+    /// Also returns the derivative of the physical time with respect to the fictitious time (one without time transformation).
+    fn stage(
+        &self,
+        delta_t: f64,
+        state_vec: &OVector<f64, <D::StateType as State>::VecLength>,
+    ) -> Result<
+        (
+            OVector<f64, <D::StateType as State>::VecLength>,
+            Option<f64>,
+        ),
+        PropagationError,
+    > {
+        let d_xdt = self
+            .prop
+            .dynamics
+            .eom(delta_t, state_vec, &self.state)
+            .with_context(|_| DynamicsSnafu)?;
+        match self.time_transformation(state_vec) {
+            Some(dt_ds) => Ok((dt_ds * d_xdt, Some(dt_ds))),
+            None => Ok((d_xdt, None)),
+        }
+    }
+
+    /// Sends the continuous extension of the latest step on the channel, if both are available
+    fn publish_interpolant(&self, maybe_tx_dense: &Option<Sender<StepInterpolant<D::StateType>>>) {
+        if let (Some(chan), Some(interpolant)) = (maybe_tx_dense, &self.interpolant) {
+            if let Err(e) = chan.send(interpolant.clone()) {
+                warn!("{} when sending on channel", e)
+            }
+        }
+    }
+
     /// This method integrates whichever function is provided as `d_xdt`. Everything passed to this function is in **seconds**.
     ///
     /// This function returns the step sized used (as a Duration) and the new state as y_{n+1} = y_n + \frac{dy_n}{dt}.
@@ -298,40 +504,214 @@ where
         &mut self,
     ) -> Result<(Duration, OVector<f64, <D::StateType as State>::VecLength>), PropagationError>
     {
-        let state_vec = &self.state.as_vector();
-        let state_ctx = &self.state;
-        // Reset the number of attempts used (we don't reset the error because it's set before it's read)
+        match self.prop.multistep {
+            Some(coeffs) => self.derive_multistep(coeffs),
+            None => self.derive_rk(),
+        }
+    }
+
+    /// Takes a single step of the multistep method, in a PECE sequence.
+    ///
+    /// The method uses the derivatives at equally spaced previous steps: when the step size changes, these are interpolated from the
+    /// derivatives of the history, which is only restarted with the Runge Kutta method when it does not span enough previous steps,
+    /// or when the state was modified outside of the integrator. With an adaptive step, the step size is halved if the error is greater
+    /// than the tolerance, and is doubled for the next step if the error is small enough for the doubled step to remain within the
+    /// tolerance and if the history is long enough.
+    fn derive_multistep(
+        &mut self,
+        coeffs: MultistepCoeffs,
+    ) -> Result<(Duration, OVector<f64, <D::StateType as State>::VecLength>), PropagationError>
+    {
+        let state_vec = self.integration_vector()?;
+        if self
+            .prop
+            .dynamics
+            .time_transformation(&state_vec, &self.state)
+            .is_some()
+        {
+            return Err(PropagationError::PropConfigError {
+                source: ConfigError::InvalidConfig {
+                    msg: "multistep propagators do not support the time transformation of the dynamics"
+                        .to_string(),
+                },
+            });
+        }
+        let epoch = self.state.epoch();
+        match &self.history_end {
+            Some((end_epoch, end_vec)) if *end_epoch == epoch && *end_vec == state_vec => {}
+            _ => self.history.clear(),
+        }
+
+        let f_n = self
+            .prop
+            .dynamics
+            .eom(0.0, &state_vec, &self.state)
+            .with_context(|_| DynamicsSnafu)?;
+        let steps = coeffs.predictor.len();
+        self.history.insert(0, (epoch, f_n));
+        // Keep twice as many derivatives as needed, so that the step size can be doubled without restarting
+        self.history.truncate(2 * steps - 1);
+
         self.details.attempts = 1;
-        // Convert the step size to seconds -- it's mutable because we may change it below
-        let mut step_size = self.step_size.to_seconds();
         loop {
-            let ki = self
+            let derivatives = match self.equally_spaced_history(self.step_size, steps) {
+                Some(derivatives) => derivatives,
+                None => {
+                    // Start up with the Runge Kutta method
+                    let (step, next_state) = self.derive_rk()?;
+                    // Do not increase the step size until the start up is over
+                    self.step_size = step;
+                    return Ok((step, next_state));
+                }
+            };
+
+            let step_size = self.step_size.to_seconds();
+            self.details.step = self.step_size;
+
+            // Predict
+            let mut predicted = state_vec.clone();
+            for (beta, f_i) in coeffs.predictor.iter().zip(&derivatives) {
+                predicted += step_size * beta * f_i;
+            }
+            // Evaluate
+            let f_pred = self
                 .prop
                 .dynamics
-                .eom(0.0, state_vec, state_ctx)
+                .eom(step_size, &predicted, &self.state)
                 .with_context(|_| DynamicsSnafu)?;
+            // Correct
+            let mut corrected = state_vec.clone() + step_size * coeffs.corrector[0] * f_pred;
+            for (beta, f_i) in coeffs.corrector[1..].iter().zip(&derivatives) {
+                corrected += step_size * beta * f_i;
+            }
+
+            if !self.fixed_step {
+                let error_est = coeffs.error_coeff * (&corrected - &predicted);
+                self.details.error = E::estimate(&error_est, &corrected, &state_vec);
+                if self.details.error > self.prop.opts.tolerance
+                    && 0.5 * step_size.abs() >= self.prop.opts.min_step.to_seconds()
+                {
+                    // Try again with half of the step size
+                    self.step_size = (0.5 * step_size) * Unit::Second;
+                    self.details.attempts += 1;
+                    continue;
+                }
+
+                // The derivatives kept after the next step must span the previous steps at twice the step size
+                let kept = self.history.len().min(2 * steps - 2);
+                let oldest = self.history[kept - 1].0;
+                if self.details.error
+                    < self.prop.opts.tolerance / 2.0_f64.powi(i32::from(coeffs.order) + 1)
+                    && 2.0 * step_size.abs() <= self.prop.opts.max_step.to_seconds()
+                    && spans_steps(
+                        epoch + self.details.step,
+                        oldest,
+                        self.details.step * 2_i64,
+                        steps,
+                    )
+                {
+                    // The next step will use twice the step size
+                    self.step_size = (2.0 * step_size) * Unit::Second;
+                }
+            }
+
+            return Ok((self.details.step, corrected));
+        }
+    }
+
+    /// Returns the derivatives at the provided number of equally spaced steps before the current epoch (included), most recent first,
+    /// or None if the history does not span them. Derivatives which are not in the history are interpolated from the nearest ones.
+    fn equally_spaced_history(
+        &self,
+        step: Duration,
+        steps: usize,
+    ) -> Option<Vec<OVector<f64, <D::StateType as State>::VecLength>>> {
+        let (epoch, _) = self.history.first()?;
+        let (oldest, _) = self.history.last()?;
+        if self.history.len() < steps || !spans_steps(*epoch, *oldest, step, steps) {
+            return None;
+        }
+        // Age of each derivative of the history, in number of steps
+        let ages: Vec<f64> = self
+            .history
+            .iter()
+            .map(|(e, _)| (*epoch - *e).to_seconds() / step.to_seconds())
+            .collect();
+
+        let mut derivatives = Vec::with_capacity(steps);
+        for j in 0..steps {
+            let node = *epoch - step * (j as i64);
+            if let Some((_, f_j)) = self.history.iter().find(|(e, _)| *e == node) {
+                derivatives.push(f_j.clone());
+                continue;
+            }
+            // Interpolate over as many derivatives as steps, around the node
+            let x = j as f64;
+            let older = ages.iter().position(|age| *age >= x).unwrap_or(ages.len());
+            let first = older
+                .saturating_sub(steps / 2)
+                .min(self.history.len() - steps);
+            let weights = lagrange_weights(&ages[first..first + steps], x);
+            let mut f_j = weights[0] * &self.history[first].1;
+            for (w, (_, f_i)) in weights
+                .iter()
+                .zip(&self.history[first..first + steps])
+                .skip(1)
+            {
+                f_j += *w * f_i;
+            }
+            derivatives.push(f_j);
+        }
+        Some(derivatives)
+    }
+
+    /// Takes a single step of the Runge Kutta method, adapting the step size if needed.
+    fn derive_rk(
+        &mut self,
+    ) -> Result<(Duration, OVector<f64, <D::StateType as State>::VecLength>), PropagationError>
+    {
+        if self.prop.implicit {
+            return self.derive_implicit();
+        }
+        let state_vec = &self.integration_vector()?;
+        // Reset the number of attempts used (we don't reset the error because it's set before it's read)
+        self.details.attempts = 1;
+        // Convert the step size to seconds -- it's mutable because we may change it below
+        let mut step_size = self.step_size.to_seconds();
+        loop {
+            let (ki, dt_ds) = self.stage(0.0, state_vec)?;
             self.k[0] = ki;
+            self.dt_ds[0] = dt_ds.unwrap_or(1.0);
+            let regularized = dt_ds.is_some();
             let mut a_idx: usize = 0;
             for i in 0..(self.prop.stages - 1) {
                 // Let's compute the c_i by summing the relevant items from the list of coefficients.
                 // \sum_{j=1}^{i-1} a_ij  ∀ i ∈ [2, s]
+                // With a time transformation, the time is integrated as well, so the time derivatives weigh the coefficients.
                 let mut ci: f64 = 0.0;
                 // The wi stores the a_{s1} * k_1 + a_{s2} * k_2 + ... + a_{s, s-1} * k_{s-1} +
                 let mut wi = OVector::<f64, <D::StateType as State>::VecLength>::from_element(0.0);
-                for kj in &self.k[0..i + 1] {
+                for (kj, dt_ds_j) in self.k[0..i + 1].iter().zip(&self.dt_ds) {
                     let a_ij = self.prop.a_coeffs[a_idx];
-                    ci += a_ij;
+                    ci += a_ij * dt_ds_j;
                     wi += a_ij * kj;
                     a_idx += 1;
                 }
 
-                let ki = self
-                    .prop
-                    .dynamics
-                    .eom(ci * step_size, &(state_vec + step_size * wi), state_ctx)
-                    .with_context(|_| DynamicsSnafu)?;
+                let (ki, dt_ds) = self.stage(ci * step_size, &(state_vec + step_size * wi))?;
                 self.k[i + 1] = ki;
+                self.dt_ds[i + 1] = dt_ds.unwrap_or(1.0);
             }
+            // Duration of the step in physical time
+            let time_step = if regularized {
+                let mut dt = 0.0;
+                for (i, dt_ds_i) in self.dt_ds.iter().enumerate() {
+                    dt += step_size * self.prop.b_coeffs[i] * dt_ds_i;
+                }
+                dt * Unit::Second
+            } else {
+                step_size * Unit::Second
+            };
             // Compute the next state and the error
             let mut next_state = state_vec.clone();
             // State error estimation from https://en.wikipedia.org/wiki/Runge%E2%80%93Kutta_methods#Adaptive_Runge%E2%80%93Kutta_methods
@@ -349,7 +729,11 @@ where
 
             if self.fixed_step {
                 // Using a fixed step, no adaptive step necessary
-                self.details.step = self.step_size;
+                self.details.step = if regularized {
+                    time_step
+                } else {
+                    self.step_size
+                };
                 return Ok(((self.details.step), next_state));
             } else {
                 // Compute the error estimate.
@@ -365,7 +749,7 @@ where
                         );
                     }
 
-                    self.details.step = step_size * Unit::Second;
+                    self.details.step = time_step;
                     if self.details.error < self.prop.opts.tolerance {
                         // Let's increase the step size for the next iteration.
                         // Error is less than tolerance, let's attempt to increase the step for the next iteration.
@@ -401,6 +785,74 @@ where
         }
     }
 
+    /// Takes a single step of an implicit Runge Kutta method, with a fixed step size.
+    ///
+    /// The stages are solved for with Gauss-Seidel fixed point iterations, starting from the derivative at the start of the step,
+    /// until the change of the next state is negligible. The error of the step is the relative change of the last iteration.
+    fn derive_implicit(
+        &mut self,
+    ) -> Result<(Duration, OVector<f64, <D::StateType as State>::VecLength>), PropagationError>
+    {
+        let state_vec = &self.integration_vector()?;
+        let step_size = self.step_size.to_seconds();
+        let stages = self.prop.stages;
+        // The iterations converge when the change of the state vector, relative to its largest component, is within the tolerance
+        let tolerance = self.prop.opts.tolerance.max(IMPLICIT_MIN_REL_TOL);
+
+        let (f_n, dt_ds) = self.stage(0.0, state_vec)?;
+        let regularized = dt_ds.is_some();
+        for (ki, dt_ds_i) in self.k.iter_mut().zip(self.dt_ds.iter_mut()) {
+            *ki = f_n.clone();
+            *dt_ds_i = dt_ds.unwrap_or(1.0);
+        }
+
+        let mut next_state = state_vec.clone();
+        for _ in 0..IMPLICIT_MAX_ITER {
+            for i in 0..stages {
+                // Same as for explicit integrators, but over all of the stages, using the latest estimates of the stages.
+                let mut ci: f64 = 0.0;
+                let mut wi = OVector::<f64, <D::StateType as State>::VecLength>::from_element(0.0);
+                for (j, (kj, dt_ds_j)) in self.k.iter().zip(&self.dt_ds).enumerate() {
+                    let a_ij = self.prop.a_coeffs[i * stages + j];
+                    ci += a_ij * dt_ds_j;
+                    wi += a_ij * kj;
+                }
+
+                let (ki, dt_ds) = self.stage(ci * step_size, &(state_vec + step_size * wi))?;
+                self.k[i] = ki;
+                self.dt_ds[i] = dt_ds.unwrap_or(1.0);
+            }
+
+            let mut candidate = state_vec.clone();
+            for (i, ki) in self.k.iter().enumerate() {
+                candidate += step_size * self.prop.b_coeffs[i] * ki;
+            }
+
+            let scale = candidate.amax().max(1.0);
+            let change = (&candidate - &next_state).amax() / scale;
+            next_state = candidate;
+
+            if change <= tolerance {
+                self.details.step = if regularized {
+                    let mut dt = 0.0;
+                    for (i, dt_ds_i) in self.dt_ds.iter().enumerate() {
+                        dt += step_size * self.prop.b_coeffs[i] * dt_ds_i;
+                    }
+                    dt * Unit::Second
+                } else {
+                    self.step_size
+                };
+                self.details.attempts = 1;
+                return Ok((self.details.step, next_state));
+            }
+        }
+
+        Err(PropagationError::ImplicitConvergence {
+            epoch: self.state.epoch(),
+            iterations: IMPLICIT_MAX_ITER,
+        })
+    }
+
     /// Copy the details of the latest integration step.
     pub fn latest_details(&self) -> IntegrationDetails {
         self.details
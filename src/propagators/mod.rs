@@ -30,10 +30,19 @@ mod propagator;
 pub use propagator::*;
 mod rk_methods;
 pub use rk_methods::*;
+mod multistep;
+pub use multistep::*;
+mod dense;
+pub use dense::*;
 mod options;
 pub use options::*;
 
-use crate::{dynamics::DynamicsError, io::ConfigError, md::trajectory::TrajError, time::Duration};
+use crate::{
+    dynamics::DynamicsError,
+    io::ConfigError,
+    md::trajectory::TrajError,
+    time::{Duration, Epoch},
+};
 
 /// Stores the details of the previous integration step of a given propagator. Access as `my_prop.clone().latest_details()`.
 #[derive(Copy, Clone, Debug)]
@@ -44,15 +53,24 @@ pub struct IntegrationDetails {
     pub error: f64,
     /// number of attempts needed by an adaptive step size to be within the tolerance
     pub attempts: u8,
+    /// relative drift of the energy since the creation of the propagator instance, if the dynamics define an energy (cf. `Dynamics::energy`)
+    pub energy_drift: Option<f64>,
 }
 
 impl fmt::Display for IntegrationDetails {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "IntegrationDetails {{step: {}, error: {:.3e}, attempts: {}}}",
-            self.step, self.error, self.attempts
-        )
+        match self.energy_drift {
+            Some(drift) => write!(
+                f,
+                "IntegrationDetails {{step: {}, error: {:.3e}, attempts: {}, energy drift: {:.3e}}}",
+                self.step, self.error, self.attempts, drift
+            ),
+            None => write!(
+                f,
+                "IntegrationDetails {{step: {}, error: {:.3e}, attempts: {}}}",
+                self.step, self.error, self.attempts
+            ),
+        }
     }
 }
 
@@ -66,4 +84,8 @@ pub enum PropagationError {
     NthEventError { nth: usize, found: usize },
     #[snafu(display("propagation failed because {source}"))]
     PropConfigError { source: ConfigError },
+    #[snafu(display(
+        "implicit integrator did not converge after {iterations} iterations at {epoch}: reduce the step size"
+    ))]
+    ImplicitConvergence { epoch: Epoch, iterations: usize },
 }
@@ -0,0 +1,121 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use crate::time::{Duration, Epoch};
+
+/// The `Multistep` trait defines a linear multistep predictor-corrector integrator of the Adams family.
+///
+/// The predictor and corrector use the derivatives at equally spaced previous steps, which are computed by a Runge Kutta
+/// integrator when starting up, and each step is a PECE sequence: predict, evaluate, correct, evaluate (the last evaluation
+/// is the first derivative of the next step).
+pub trait Multistep
+where
+    Self: Sized,
+{
+    /// Returns the order of this integrator, used to adapt the step size.
+    const ORDER: u8;
+
+    /// Returns the coefficients of the explicit predictor, applied to the derivatives from the current step to the oldest one.
+    /// The number of coefficients is the number of previous derivatives needed.
+    const PREDICTOR: &'static [f64];
+
+    /// Returns the coefficients of the implicit corrector, applied to the derivative at the predicted state followed by
+    /// the derivatives from the current step to the oldest one. `Self::CORRECTOR.len()` must be at most `Self::PREDICTOR.len() + 1`.
+    const CORRECTOR: &'static [f64];
+
+    /// Returns the ratio of the local truncation error of the corrector to the difference between the corrected and predicted states (Milne's device).
+    const ERROR_COEFF: f64;
+}
+
+/// `AdamsBashforthMoulton8` is the 8th order Adams-Bashforth predictor with the 8th order Adams-Moulton corrector.
+///
+/// Each step only requires two evaluations of the dynamics, regardless of the order, which makes it efficient for long propagations
+/// with expensive dynamics (e.g. high degree gravity fields). When the step size changes, the derivatives at the new spacing are
+/// interpolated from the previous ones, so the Runge Kutta start-up is only needed at the start of the propagation, or after
+/// the state was modified outside of the integrator.
+///
+/// Note that this is not the Gauss-Jackson (summed Cowell) method, which integrates the second order equations of motion of the
+/// position directly: the Adams methods integrate any first order dynamics (e.g. the STM, the mass or the attitude), at the cost
+/// of a slightly larger error per step on the position for the same order and step size.
+pub struct AdamsBashforthMoulton8 {}
+
+impl Multistep for AdamsBashforthMoulton8 {
+    const ORDER: u8 = 8;
+    const PREDICTOR: &'static [f64] = &[
+        434_241.0 / 120_960.0,
+        -1_152_169.0 / 120_960.0,
+        2_183_877.0 / 120_960.0,
+        -2_664_477.0 / 120_960.0,
+        2_102_243.0 / 120_960.0,
+        -1_041_723.0 / 120_960.0,
+        295_767.0 / 120_960.0,
+        -36_799.0 / 120_960.0,
+    ];
+    const CORRECTOR: &'static [f64] = &[
+        36_799.0 / 120_960.0,
+        139_849.0 / 120_960.0,
+        -121_797.0 / 120_960.0,
+        123_133.0 / 120_960.0,
+        -88_547.0 / 120_960.0,
+        41_499.0 / 120_960.0,
+        -11_351.0 / 120_960.0,
+        1_375.0 / 120_960.0,
+    ];
+    const ERROR_COEFF: f64 = 33_953.0 / 1_103_970.0;
+}
+
+/// The coefficients of a multistep integrator, stored by the propagator.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct MultistepCoeffs<'a> {
+    pub(crate) order: u8,
+    pub(crate) predictor: &'a [f64],
+    pub(crate) corrector: &'a [f64],
+    pub(crate) error_coeff: f64,
+}
+
+impl<'a> MultistepCoeffs<'a> {
+    pub(crate) fn from_method<M: Multistep>() -> Self {
+        Self {
+            order: M::ORDER,
+            predictor: M::PREDICTOR,
+            corrector: M::CORRECTOR,
+            error_coeff: M::ERROR_COEFF,
+        }
+    }
+}
+
+/// Returns whether the derivatives from the provided epoch back to the oldest one span the provided number of steps (including the epoch itself).
+pub(crate) fn spans_steps(epoch: Epoch, oldest: Epoch, step: Duration, steps: usize) -> bool {
+    (epoch - oldest).to_seconds() / step.to_seconds() >= (steps - 1) as f64 - 1e-9
+}
+
+/// Returns the weights of the Lagrange interpolation at `x` of values known at the provided distinct nodes.
+pub(crate) fn lagrange_weights(nodes: &[f64], x: f64) -> Vec<f64> {
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            nodes
+                .iter()
+                .enumerate()
+                .filter(|(l, _)| *l != i)
+                .map(|(_, x_l)| (x - x_l) / (x_i - x_l))
+                .product()
+        })
+        .collect()
+}
@@ -16,9 +16,12 @@
     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 */
 
-use super::error_ctrl::{ErrorCtrl, RSSCartesianStep};
-use super::{Dormand78, IntegrationDetails, PropInstance, PropOpts, RK, RK89};
-use crate::dynamics::Dynamics;
+use super::error_ctrl::{ErrorCtrl, RSSCartesianStep, RSSEquinoctialState};
+use super::{
+    AdamsBashforthMoulton8, Dormand78, IntegrationDetails, Multistep, MultistepCoeffs,
+    PropInstance, PropOpts, RK, RK89,
+};
+use crate::dynamics::{Dynamics, EquinoctialDynamics};
 use crate::linalg::allocator::Allocator;
 use crate::linalg::{DefaultAllocator, OVector};
 use crate::time::Duration;
@@ -41,6 +44,9 @@ where
     pub(crate) stages: usize, // Number of stages, i.e. how many times the derivatives will be called
     pub(crate) a_coeffs: &'a [f64],
     pub(crate) b_coeffs: &'a [f64],
+    pub(crate) dense_coeffs: &'a [f64], // Continuous extension, empty if the RK doesn't have one
+    pub(crate) implicit: bool, // Whether the stages are solved for, in which case the A coefficients are the full table
+    pub(crate) multistep: Option<MultistepCoeffs<'a>>, // Multistep method, started up with the Runge Kutta method
 }
 
 /// The `Propagator` trait defines the functions of a propagator and of an event tracker.
@@ -60,9 +66,19 @@ where
             order: T::ORDER,
             a_coeffs: T::A_COEFFS,
             b_coeffs: T::B_COEFFS,
+            dense_coeffs: T::DENSE_COEFFS,
+            implicit: T::IMPLICIT,
+            multistep: None,
         }
     }
 
+    /// Initializes a multistep propagator, which uses the provided Runge Kutta integrator to start up, i.e. until enough previous steps are known.
+    pub fn new_multistep<T: RK, M: Multistep>(dynamics: D, opts: PropOpts<E>) -> Self {
+        let mut me = Self::new::<T>(dynamics, opts);
+        me.multistep = Some(MultistepCoeffs::from_method::<M>());
+        me
+    }
+
     /// Set the tolerance for the propagator
     pub fn set_tolerance(&mut self, tol: f64) {
         self.opts.tolerance = tol;
@@ -88,6 +104,12 @@ where
         Self::new::<Dormand78>(dynamics, opts)
     }
 
+    /// An 8th order Adams-Bashforth-Moulton propagator started up with an RK89, with custom propagator options.
+    /// The step size may be adapted without restarting the integrator, but a fixed step is the most efficient for long propagations with expensive dynamics.
+    pub fn abm8(dynamics: D, opts: PropOpts<E>) -> Self {
+        Self::new_multistep::<RK89, AdamsBashforthMoulton8>(dynamics, opts)
+    }
+
     pub fn with(&'a self, state: D::StateType) -> PropInstance<'a, D, E> {
         // Pre-allocate the k used in the propagator
         let mut k = Vec::with_capacity(self.stages + 1);
@@ -101,10 +123,18 @@ where
                 step: self.opts.init_step,
                 error: 0.0,
                 attempts: 1,
+                energy_drift: None,
             },
             step_size: self.opts.init_step,
             fixed_step: self.opts.fixed_step,
             k,
+            history: Vec::new(),
+            history_end: None,
+            dense_output: false,
+            interpolant: None,
+            initial_energy: self.dynamics.energy(&state),
+            fictitious_time: true,
+            dt_ds: vec![1.0; self.stages],
         }
     }
 }
@@ -128,3 +158,31 @@ where
         Self::new::<Dormand78>(dynamics, PropOpts::default())
     }
 }
+
+impl<'a, D> Propagator<'a, EquinoctialDynamics<D>, RSSEquinoctialState>
+where
+    EquinoctialDynamics<D>: Dynamics,
+    DefaultAllocator: Allocator<f64, <<EquinoctialDynamics<D> as Dynamics>::StateType as State>::Size>
+        + Allocator<
+            f64,
+            <<EquinoctialDynamics<D> as Dynamics>::StateType as State>::Size,
+            <<EquinoctialDynamics<D> as Dynamics>::StateType as State>::Size,
+        > + Allocator<
+            usize,
+            <<EquinoctialDynamics<D> as Dynamics>::StateType as State>::Size,
+            <<EquinoctialDynamics<D> as Dynamics>::StateType as State>::Size,
+        > + Allocator<f64, <<EquinoctialDynamics<D> as Dynamics>::StateType as State>::VecLength>,
+{
+    /// Default propagator of the modified equinoctial elements: an RK89 with the default PropOpts, except for the error control which applies to the elements.
+    pub fn equinoctial(dynamics: EquinoctialDynamics<D>) -> Self {
+        let defaults = PropOpts::<RSSCartesianStep>::default();
+        let mut opts = PropOpts::with_adaptive_step(
+            defaults.min_step,
+            defaults.max_step,
+            defaults.tolerance,
+            RSSEquinoctialState {},
+        );
+        opts.init_step = defaults.init_step;
+        Self::new::<RK89>(dynamics, opts)
+    }
+}
@@ -63,6 +63,38 @@ impl RK for Dormand45 {
         187.0 / 2_100.0,
         1.0 / 40.0,
     ];
+    // Fourth order continuous extension of Shampine (1986), which only uses the stages of the step since the last stage is
+    // evaluated at the end of the step.
+    const DENSE_COEFFS: &'static [f64] = &[
+        1.0,
+        -8_048_581_381.0 / 2_820_520_608.0,
+        8_663_915_743.0 / 2_820_520_608.0,
+        -12_715_105_075.0 / 11_282_082_432.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        131_558_114_200.0 / 32_700_410_799.0,
+        -68_118_460_800.0 / 10_900_136_933.0,
+        87_487_479_700.0 / 32_700_410_799.0,
+        0.0,
+        -1_754_552_775.0 / 470_086_768.0,
+        14_199_869_525.0 / 1_410_260_304.0,
+        -10_690_763_975.0 / 1_880_347_072.0,
+        0.0,
+        127_303_824_393.0 / 49_829_197_408.0,
+        -318_862_633_887.0 / 49_829_197_408.0,
+        701_980_252_875.0 / 199_316_789_632.0,
+        0.0,
+        -282_668_133.0 / 205_662_961.0,
+        2_019_193_451.0 / 616_988_883.0,
+        -1_453_857_185.0 / 822_651_844.0,
+        0.0,
+        40_617_522.0 / 29_380_423.0,
+        -110_615_467.0 / 29_380_423.0,
+        69_997_945.0 / 29_380_423.0,
+    ];
 }
 
 /// `Dormand78` is a [Dormand-Prince integrator](https://en.wikipedia.org/wiki/Dormand%E2%80%93Prince_method).
@@ -0,0 +1,107 @@
+/*
+    Nyx, blazing fast astrodynamics
+    Copyright (C) 2023 Christopher Rabotin <christopher.rabotin@gmail.com>
+
+    This program is free software: you can redistribute it and/or modify
+    it under the terms of the GNU Affero General Public License as published
+    by the Free Software Foundation, either version 3 of the License, or
+    (at your option) any later version.
+
+    This program is distributed in the hope that it will be useful,
+    but WITHOUT ANY WARRANTY; without even the implied warranty of
+    MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+    GNU Affero General Public License for more details.
+
+    You should have received a copy of the GNU Affero General Public License
+    along with this program.  If not, see <https://www.gnu.org/licenses/>.
+*/
+
+use super::RK;
+
+const SQRT3: f64 = 1.732_050_807_568_877_2;
+const SQRT15: f64 = 3.872_983_346_207_417;
+const CBRT2: f64 = 1.259_921_049_894_873_2;
+
+// NOTE: The implicit integrators are only used with a fixed step, so their B coefficients are duplicated as the embedded ones
+// (i.e. the error estimate is zero).
+
+/// `GaussLegendre4` is the two stage [Gauss-Legendre](https://en.wikipedia.org/wiki/Gauss%E2%80%93Legendre_method) implicit integrator of order 4.
+///
+/// Gauss-Legendre integrators are symplectic and A-stable, so the energy of conservative dynamics does not drift over long propagations.
+/// This is a fixed step integrator: if initialized with an `PropOpts.with_adaptive_step`, the variable step will **not** be taken into consideration.
+pub struct GaussLegendre4 {}
+
+impl RK for GaussLegendre4 {
+    const ORDER: u8 = 4;
+    const STAGES: usize = 2;
+    const IMPLICIT: bool = true;
+    const A_COEFFS: &'static [f64] = &[
+        1.0 / 4.0,
+        1.0 / 4.0 - SQRT3 / 6.0,
+        1.0 / 4.0 + SQRT3 / 6.0,
+        1.0 / 4.0,
+    ];
+    const B_COEFFS: &'static [f64] = &[1.0 / 2.0, 1.0 / 2.0, 1.0 / 2.0, 1.0 / 2.0];
+}
+
+/// `GaussLegendre6` is the three stage [Gauss-Legendre](https://en.wikipedia.org/wiki/Gauss%E2%80%93Legendre_method) implicit integrator of order 6.
+///
+/// Gauss-Legendre integrators are symplectic and A-stable, so the energy of conservative dynamics does not drift over long propagations.
+/// This is a fixed step integrator: if initialized with an `PropOpts.with_adaptive_step`, the variable step will **not** be taken into consideration.
+pub struct GaussLegendre6 {}
+
+impl RK for GaussLegendre6 {
+    const ORDER: u8 = 6;
+    const STAGES: usize = 3;
+    const IMPLICIT: bool = true;
+    const A_COEFFS: &'static [f64] = &[
+        5.0 / 36.0,
+        2.0 / 9.0 - SQRT15 / 15.0,
+        5.0 / 36.0 - SQRT15 / 30.0,
+        5.0 / 36.0 + SQRT15 / 24.0,
+        2.0 / 9.0,
+        5.0 / 36.0 - SQRT15 / 24.0,
+        5.0 / 36.0 + SQRT15 / 30.0,
+        2.0 / 9.0 + SQRT15 / 15.0,
+        5.0 / 36.0,
+    ];
+    const B_COEFFS: &'static [f64] = &[
+        5.0 / 18.0,
+        4.0 / 9.0,
+        5.0 / 18.0,
+        5.0 / 18.0,
+        4.0 / 9.0,
+        5.0 / 18.0,
+    ];
+}
+
+const YOSHIDA_W1: f64 = 1.0 / (2.0 - CBRT2);
+const YOSHIDA_W0: f64 = -CBRT2 / (2.0 - CBRT2);
+
+/// `Yoshida4` is the symplectic integrator of order 4 from [Yoshida (1990)](https://doi.org/10.1016/0375-9601(90)90092-3), i.e. the "triple jump"
+/// composition of three implicit midpoint steps of `w1*h`, `w0*h` and `w1*h`.
+///
+/// The implicit midpoint rule is used instead of the leapfrog because it does not require splitting the state into positions and velocities,
+/// so this integrator applies to any state (e.g. with an STM, or a spacecraft mass). The composition is written as a diagonally implicit Runge Kutta.
+/// This is a fixed step integrator: if initialized with an `PropOpts.with_adaptive_step`, the variable step will **not** be taken into consideration.
+pub struct Yoshida4 {}
+
+impl RK for Yoshida4 {
+    const ORDER: u8 = 4;
+    const STAGES: usize = 3;
+    const IMPLICIT: bool = true;
+    const A_COEFFS: &'static [f64] = &[
+        YOSHIDA_W1 / 2.0,
+        0.0,
+        0.0,
+        YOSHIDA_W1,
+        YOSHIDA_W0 / 2.0,
+        0.0,
+        YOSHIDA_W1,
+        YOSHIDA_W0,
+        YOSHIDA_W1 / 2.0,
+    ];
+    const B_COEFFS: &'static [f64] = &[
+        YOSHIDA_W1, YOSHIDA_W0, YOSHIDA_W1, YOSHIDA_W1, YOSHIDA_W0, YOSHIDA_W1,
+    ];
+}
@@ -24,6 +24,8 @@ mod fehlberg;
 pub use self::fehlberg::*;
 mod verner;
 pub use self::verner::*;
+mod implicit;
+pub use self::implicit::*;
 
 /// The `RK` trait defines a Runge Kutta integrator.
 #[allow(clippy::upper_case_acronyms)]
@@ -38,12 +40,21 @@ where
     /// Returns the stages of this integrator (as usize because it's used as indexing)
     const STAGES: usize;
 
+    /// Returns whether this integrator is implicit, i.e. its stages are solved for at each step (by fixed point iteration).
+    const IMPLICIT: bool = false;
+
     /// Returns a pointer to a list of f64 corresponding to the A coefficients of the Butcher table for that RK.
-    /// This module only supports *implicit* integrators, and as such, `Self.a_coeffs().len()` must be of
-    /// size (order+1)*(order)/2.
+    /// For *explicit* integrators, only the strictly lower triangular part is stored, row by row, and as such,
+    /// `Self.A_COEFFS.len()` must be of size stages*(stages-1)/2.
+    /// For *implicit* integrators, the full table is stored row by row, i.e. `Self.A_COEFFS.len()` must be of size stages*stages.
     /// *Warning:* this RK trait supposes that the implementation is consistent, i.e. c_i = \sum_j a_{ij}.
     const A_COEFFS: &'static [f64];
     /// Returns a pointer to a list of f64 corresponding to the b_i and b^*_i coefficients of the
-    /// Butcher table for that RK. `Self.a_coeffs().len()` must be of size (order+1)*2.
+    /// Butcher table for that RK. `Self.B_COEFFS.len()` must be of size stages*2.
     const B_COEFFS: &'static [f64];
+    /// Returns a pointer to a list of f64 corresponding to the coefficients of the continuous extension of this RK, if it has one.
+    /// The weight of stage i at the fraction θ of the step is the polynomial b_i(θ) = \sum_j d_{ij} θ^j for j from 1 to the degree,
+    /// and the d_{ij} are stored stage by stage, so `Self.DENSE_COEFFS.len()` must be of size STAGES*degree.
+    /// If empty, the dense output is a cubic Hermite interpolation of the states and derivatives at both ends of each step.
+    const DENSE_COEFFS: &'static [f64] = &[];
 }
@@ -76,4 +76,41 @@ impl RK for Verner56 {
         0.0,
         3.0 / 44.0,
     ];
+    // Fourth order continuous extension which only uses the stages of the step, derived from the order conditions of the
+    // continuous weights up to the fourth order, with b_i(1) equal to the sixth order weights for the continuity between steps.
+    // The weights of the stages 6 and 8 are free and chosen as small as possible (stage 8 is not used).
+    const DENSE_COEFFS: &'static [f64] = &[
+        445.0 / 448.0,
+        -3_483.0 / 1_120.0,
+        2_011.0 / 560.0,
+        -627.0 / 448.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        -125.0 / 22_848.0,
+        10_375.0 / 2_464.0,
+        -146_375.0 / 20_944.0,
+        265_875.0 / 83_776.0,
+        5.0 / 2_016.0,
+        -151.0 / 112.0,
+        247.0 / 56.0,
+        -615.0 / 224.0,
+        -3.0 / 2_737.0,
+        -6.0 / 35.0,
+        228.0 / 595.0,
+        -9.0 / 119.0,
+        125.0 / 11_592.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        129.0 / 308.0,
+        -215.0 / 154.0,
+        645.0 / 616.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+    ];
 }
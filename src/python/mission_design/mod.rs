@@ -21,7 +21,8 @@ use crate::io::{ConfigError, ExportCfg};
 use crate::md::prelude::{PropOpts, Propagator, SpacecraftDynamics};
 use crate::md::{Event, StateParameter};
 use crate::propagators::{
-    CashKarp45, Dormand45, Dormand78, Fehlberg45, PropagationError, RK2Fixed, RK4Fixed, Verner56,
+    CashKarp45, Dormand45, Dormand78, Fehlberg45, GaussLegendre4, GaussLegendre6, PropagationError,
+    RK2Fixed, RK4Fixed, Verner56, Yoshida4,
 };
 use crate::{NyxError, Orbit, Spacecraft};
 use hifitime::{Duration, Epoch, Unit};
@@ -107,6 +108,9 @@ fn propagate(
             "verner56" => Propagator::new::<Verner56>(dynamics, opts),
             "rk4" => Propagator::new::<RK4Fixed>(dynamics, opts),
             "rk2" => Propagator::new::<RK2Fixed>(dynamics, opts),
+            "gausslegendre4" => Propagator::new::<GaussLegendre4>(dynamics, opts),
+            "gausslegendre6" => Propagator::new::<GaussLegendre6>(dynamics, opts),
+            "yoshida4" => Propagator::new::<Yoshida4>(dynamics, opts),
             _ => {
                 return Err(PropagationError::PropConfigError {
                     source: ConfigError::InvalidConfig {
@@ -17,7 +17,7 @@
 */
 use crate::python::PythonError;
 use crate::{
-    cosmic::{DragConfig, SrpConfig},
+    cosmic::{DragConfig, SrpConfig, MAX_TANKS},
     dynamics::guidance::Thruster,
     io::{ConfigError, ConfigRepr},
     md::prelude::GuidanceMode,
@@ -60,6 +60,7 @@ impl Spacecraft {
                 dry_mass_kg: dry_mass_kg.unwrap(),
                 fuel_mass_kg: fuel_mass_kg.unwrap_or(0.0),
                 thruster,
+                tank_masses_kg: [None; MAX_TANKS],
                 mode: mode.unwrap_or(GuidanceMode::Coast),
                 stm: None,
                 srp: srp.unwrap_or_else(|| SrpConfig::default()),
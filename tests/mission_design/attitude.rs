@@ -0,0 +1,104 @@
+extern crate nalgebra as na;
+extern crate nyx_space as nyx;
+
+use na::UnitQuaternion;
+use nyx::cosmic::{Attitude, Cosm, Orbit, Spacecraft, SpacecraftAttitude};
+use nyx::dynamics::{
+    AttitudeDynamics, GravityGradient, OrbitalDynamics, SpacecraftDynamics, TorqueModel,
+};
+use nyx::linalg::{Matrix3, Vector3};
+use nyx::propagators::{PropOpts, Propagator};
+use nyx::time::{Epoch, Unit};
+use nyx::utils::rss_orbit_errors;
+
+#[test]
+fn torque_free_attitude() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let orbit = Orbit::keplerian(7000.0, 0.01, 45.0, 30.0, 60.0, 0.0, epoch, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 2.0);
+
+    // Asymmetric body spinning about its intermediate axis with some nutation
+    let inertia = Matrix3::new(100.0, 2.0, 0.0, 2.0, 150.0, 1.0, 0.0, 1.0, 200.0);
+    let attitude = Attitude::new(
+        UnitQuaternion::from_euler_angles(0.1, -0.2, 0.3),
+        Vector3::new(0.01, 0.1, -0.02),
+    );
+    let state = SpacecraftAttitude::new(sc, attitude, inertia);
+
+    let prop_time = 20 * Unit::Minute;
+    let opts = PropOpts::with_fixed_step(1 * Unit::Second);
+
+    let sc_dyn = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let att_setup = Propagator::rk89(AttitudeDynamics::new(sc_dyn.clone()), opts);
+    let final_state = att_setup.with(state).for_duration(prop_time).unwrap();
+
+    println!("{state}\n{final_state}");
+
+    // The inertial angular momentum and the rotational kinetic energy are conserved
+    let h0 = state.angular_momentum_inertial();
+    let hf = final_state.angular_momentum_inertial();
+    let h_err = (hf - h0).norm() / h0.norm();
+    let e_err = (final_state.rotational_energy_j() - state.rotational_energy_j()).abs()
+        / state.rotational_energy_j();
+    println!("relative errors: angular momentum {h_err:e}\tenergy {e_err:e}");
+    assert!(h_err < 1e-9, "angular momentum not conserved");
+    assert!(e_err < 1e-9, "kinetic energy not conserved");
+    assert!((final_state.attitude.q.norm() - 1.0).abs() < 1e-12);
+
+    // The translational motion is identical to the one of the spacecraft dynamics alone
+    let sc_setup = Propagator::rk89(sc_dyn, opts);
+    let sc_final = sc_setup.with(sc).for_duration(prop_time).unwrap();
+    let (err_r, err_v) = rss_orbit_errors(&final_state.sc.orbit, &sc_final.orbit);
+    println!("translation errors: {:e} km\t{:e} km/s", err_r, err_v);
+    assert!(err_r < 1e-9 && err_v < 1e-12);
+    assert_eq!(final_state.sc.epoch(), sc_final.epoch());
+}
+
+#[test]
+fn gravity_gradient_torque() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let rmag_km = 7000.0;
+    let vmag_km_s = (eme2k.gm() / rmag_km).sqrt();
+    let orbit = Orbit::cartesian(rmag_km, 0.0, 0.0, 0.0, vmag_km_s, 0.0, epoch, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 2.0);
+
+    // The body X axis is pitched by theta away from the radial direction
+    let theta = 20.0_f64.to_radians();
+    let (ix, iy, iz) = (300.0, 250.0, 100.0);
+    let attitude = Attitude::new(
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), theta),
+        Vector3::zeros(),
+    );
+    let state = SpacecraftAttitude::new(
+        sc,
+        attitude,
+        Matrix3::from_diagonal(&Vector3::new(ix, iy, iz)),
+    );
+
+    let pointing_err = state
+        .attitude
+        .pointing_error_deg(&Vector3::x(), &orbit.radius());
+    assert!((pointing_err - 20.0).abs() < 1e-10);
+
+    let torque = GravityGradient.torque(&state).unwrap();
+    let expected = 3.0 * eme2k.gm() / rmag_km.powi(3) * (ix - iz) * theta.sin() * theta.cos();
+    println!("{torque}\nexpected {expected} N m about Y");
+    assert!(torque[0].abs() < 1e-15 && torque[2].abs() < 1e-15);
+    assert!((torque[1] - expected).abs() < 1e-12 * expected.abs());
+
+    // Propagate for a short time: the pitch rate matches the torque
+    let dynamics = AttitudeDynamics::with_gravity_gradient(SpacecraftDynamics::new(
+        OrbitalDynamics::two_body(),
+    ));
+    let setup = Propagator::rk89(dynamics, PropOpts::with_fixed_step_s(0.1));
+    let final_state = setup.with(state).for_duration(1 * Unit::Second).unwrap();
+    let omega_y = final_state.attitude.omega_rad_s[1];
+    println!("pitch rate after 1 s: {omega_y:e} rad/s");
+    assert!((omega_y - expected / iy).abs() < 1e-2 * (expected / iy).abs());
+}
@@ -332,3 +332,484 @@ fn std_atm_drag_earth_low() {
 
     */
 }
+
+#[test]
+fn drag_force_units() {
+    use nyx::dynamics::{ConstantDrag, ForceModel};
+    let cosm = Cosm::de438_gmat();
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2000, 1, 1);
+
+    // Equatorial prograde state in the body fixed frame, so the co-rotation only reduces the Y velocity
+    let orbit = Orbit::cartesian(7_000.0, 0.0, 0.0, 0.0, 7.5, 0.0, dt, iau_earth);
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 1.0).with_drag(2.0, 2.2);
+
+    let rho = 1e-11;
+    let drag = ConstantDrag {
+        rho,
+        drag_frame: iau_earth,
+        wind: None,
+        cosm: cosm.clone(),
+    };
+
+    // The force is in kN, with the relative velocity in m/s
+    let v_rel_m_s = (7.5 - iau_earth.angular_velocity() * 7_000.0) * 1e3;
+    let expected_kn = 0.5 * rho * 2.2 * 2.0 * v_rel_m_s.powi(2) * 1e-3;
+    let force = drag.eom(&sc).unwrap();
+    println!("{force}");
+    assert!((force[1] + expected_kn).abs() < 1e-12 * expected_kn);
+    assert!(force[0].abs() < 1e-12 * expected_kn && force[2].abs() < 1e-12 * expected_kn);
+
+    // The exponential density uses its reference altitude and scale height in meters
+    let exp_drag = Drag::earth_exp(cosm);
+    let at_altitude = |altitude_km: f64| {
+        Orbit::cartesian(
+            iau_earth.equatorial_radius() + altitude_km,
+            0.0,
+            0.0,
+            0.0,
+            7.5,
+            0.0,
+            dt,
+            iau_earth,
+        )
+    };
+    let rho0 = exp_drag.density_kg_m3(&at_altitude(700.0)).unwrap();
+    assert!((rho0 - 3.614e-13).abs() < 1e-12 * rho0);
+    let rho_scale = exp_drag.density_kg_m3(&at_altitude(788.667)).unwrap();
+    assert!((rho_scale - rho0 / std::f64::consts::E).abs() < 1e-9 * rho0);
+}
+
+#[test]
+fn drag_earth_leo_duals() {
+    use nyx::dynamics::ForceModel;
+    use nyx::linalg::Matrix3x6;
+    use nyx::State;
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2000, 1, 1);
+
+    let orbit = Orbit::keplerian(
+        eme2k.equatorial_radius() + 300.0,
+        0.001,
+        51.6,
+        30.0,
+        0.0,
+        0.0,
+        dt,
+        eme2k,
+    );
+
+    let prop_time = 1 * Unit::Day;
+
+    let sc_dyn = SpacecraftDynamics::from_model(
+        OrbitalDynamics::two_body(),
+        Drag::std_atm1976(cosm.clone()),
+    );
+
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 1.0).with_drag(1.0, 2.0);
+
+    let setup = Propagator::default(sc_dyn);
+    let final_state = setup.with(sc).for_duration(prop_time).unwrap();
+    println!("{}", final_state.orbit);
+
+    // The drag with respect to the co-rotating atmosphere decays the orbit
+    assert!(final_state.orbit.sma_km() < orbit.sma_km());
+
+    // Compare the case with the hyperdual EOMs (computation uses another part of the code)
+    let final_state_dual = setup.with(sc.with_stm()).for_duration(prop_time).unwrap();
+
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &final_state.orbit.to_cartesian_vec(),
+        &final_state_dual.orbit.to_cartesian_vec(),
+    );
+    println!(
+        "Error between reals and duals accumulated over {} : {:.3e} m \t{:.3e} m/s",
+        prop_time,
+        err_r * 1e3,
+        err_v * 1e3
+    );
+    assert!(
+        err_r < 1e-3,
+        "Error between reals and duals too large for drag"
+    );
+    assert!(
+        err_v < 1e-6,
+        "Error between reals and duals too large for drag"
+    );
+
+    // The STM must include the drag partials, and hence differ from the two body STM
+    let two_body_dyn = SpacecraftDynamics::new(OrbitalDynamics::two_body());
+    let final_two_body = Propagator::default(two_body_dyn)
+        .with(sc.with_stm())
+        .for_duration(prop_time)
+        .unwrap();
+    let stm_diff = final_state_dual.stm().unwrap() - final_two_body.stm().unwrap();
+    println!("STM difference due to drag: {:.3e}", stm_diff.norm());
+    assert!(stm_diff.fixed_view::<6, 6>(0, 0).norm() > 0.0);
+
+    // The drag partials match the central finite differences of the drag force
+    let drag = Drag::std_atm1976(cosm);
+    let (force, grad) = drag.dual_eom_pos_vel(&sc).unwrap();
+    assert!((force - drag.eom(&sc).unwrap()).norm() < 1e-12 * force.norm());
+
+    let mut grad_fd = Matrix3x6::zeros();
+    for j in 0..6 {
+        // Steps of 10 m in position and 1 mm/s in velocity
+        let step = if j < 3 { 1e-2 } else { 1e-6 };
+        let mut delta = Vector6::zeros();
+        delta[j] = step;
+        let mut plus = sc;
+        plus.orbit = sc.orbit + delta;
+        let mut minus = sc;
+        minus.orbit = sc.orbit + (-delta);
+        let column = (drag.eom(&plus).unwrap() - drag.eom(&minus).unwrap()) / (2.0 * step);
+        grad_fd.set_column(j, &column);
+    }
+
+    let pos_err = (grad.fixed_view::<3, 3>(0, 0) - grad_fd.fixed_view::<3, 3>(0, 0)).norm()
+        / grad_fd.fixed_view::<3, 3>(0, 0).norm();
+    let vel_err = (grad.fixed_view::<3, 3>(0, 3) - grad_fd.fixed_view::<3, 3>(0, 3)).norm()
+        / grad_fd.fixed_view::<3, 3>(0, 3).norm();
+    println!(
+        "Relative error of the drag partials: {pos_err:.3e} (position)\t{vel_err:.3e} (velocity)"
+    );
+    // The position partials use a locally exponential density, whose scale height is computed by finite differencing
+    assert!(pos_err < 1e-2, "position partials of the drag are wrong");
+    assert!(vel_err < 1e-6, "velocity partials of the drag are wrong");
+}
+
+#[test]
+fn earth_radiation_leo() {
+    use nyx::cosmic::LightTimeCalc;
+    use nyx::dynamics::{EarthRadiationPressure, ForceModel};
+    use nyx::io::earth_radiation::EarthRadiationMap;
+    use nyx::State;
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2000, 1, 1);
+
+    // The gridded map matches the zonal model it was generated from
+    let map = EarthRadiationMap::from_csv("data/tests/earth_radiation/knocke_10deg.csv").unwrap();
+    let knocke = EarthRadiationMap::knocke();
+    for lat_deg in [-80.0, -33.0, 0.0, 12.0, 61.0] {
+        let (albedo, emissivity) = map.at(lat_deg, 42.0);
+        let (albedo_k, emissivity_k) = knocke.at(lat_deg, -42.0);
+        assert!((albedo - albedo_k).abs() < 0.05);
+        assert!((emissivity - emissivity_k).abs() < 0.05);
+    }
+    assert!(EarthRadiationMap::from_csv("data/tests/earth_radiation/missing.csv").is_err());
+
+    let erp = EarthRadiationPressure::default(map.clone(), cosm.clone());
+    println!("{erp}");
+    let srp = SolarPressure::default(eme2k, cosm.clone());
+
+    // Place the spacecraft above the sub-solar point, and then above the anti-solar point
+    let sun_unit = cosm
+        .celestial_state(&[0], dt, eme2k, LightTimeCalc::None)
+        .radius()
+        .normalize();
+    let rmag_km = eme2k.equatorial_radius() + 400.0;
+    let day_pos = rmag_km * sun_unit;
+    let day = Orbit::cartesian(day_pos[0], day_pos[1], day_pos[2], 0.0, 0.0, 7.6, dt, eme2k);
+    let night = Orbit::cartesian(
+        -day_pos[0],
+        -day_pos[1],
+        -day_pos[2],
+        0.0,
+        0.0,
+        7.6,
+        dt,
+        eme2k,
+    );
+
+    let sc_day = Spacecraft::from_srp_defaults(day, 300.0, 1.0);
+    let sc_night = Spacecraft::from_srp_defaults(night, 300.0, 1.0);
+
+    let erp_day = erp.eom(&sc_day).unwrap();
+    let srp_day = srp.eom(&sc_day).unwrap();
+    println!(
+        "Sub-solar: ERP {:.3e} N\tSRP {:.3e} N",
+        erp_day.norm() * 1e3,
+        srp_day.norm() * 1e3
+    );
+    // Albedo pushes the spacecraft away from the Earth, at a fraction of the direct solar radiation pressure
+    assert!(erp_day.normalize().dot(&day_pos.normalize()) > 0.99);
+    assert!(erp_day.norm() > 0.1 * srp_day.norm());
+    assert!(erp_day.norm() < srp_day.norm());
+
+    // On the night side, only the infrared emission remains
+    let erp_night = erp.eom(&sc_night).unwrap();
+    assert!(erp_night.norm() > 0.0);
+    assert!(erp_night.norm() < erp_day.norm());
+
+    let mut albedo_only = EarthRadiationPressure::default_raw(map.clone(), cosm.clone());
+    albedo_only.infrared = false;
+    assert_eq!(albedo_only.eom(&sc_night).unwrap().norm(), 0.0);
+
+    // The visible cap is built around the origin of the integration frame, which must be the Earth
+    let luna = cosm.frame("Luna");
+    let sc_moon = Spacecraft::from_srp_defaults(
+        Orbit::keplerian(2_000.0, 0.01, 30.0, 0.0, 0.0, 0.0, dt, luna),
+        300.0,
+        1.0,
+    );
+    assert!(erp.eom(&sc_moon).is_err());
+    assert!(erp.dual_eom(&sc_moon).is_err());
+
+    // The reals and the duals must match
+    let (erp_day_dual, grad) = erp.dual_eom(&sc_day).unwrap();
+    assert!((erp_day_dual - erp_day).norm() < 1e-20);
+    assert!(grad.fixed_view::<3, 3>(0, 0).norm() > 0.0);
+
+    let orbit = Orbit::keplerian(rmag_km, 0.001, 51.6, 30.0, 0.0, 0.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 1.0);
+    let sc_dyn = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), erp);
+
+    let prop_time = 2 * Unit::Hour;
+    let setup = Propagator::default(sc_dyn);
+    let final_state = setup.with(sc).for_duration(prop_time).unwrap();
+    let final_state_dual = setup.with(sc.with_stm()).for_duration(prop_time).unwrap();
+
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &final_state.orbit.to_cartesian_vec(),
+        &final_state_dual.orbit.to_cartesian_vec(),
+    );
+    println!(
+        "Error between reals and duals accumulated over {} : {:.3e} m \t{:.3e} m/s",
+        prop_time,
+        err_r * 1e3,
+        err_v * 1e3
+    );
+    assert!(err_r < 1e-3, "Error between reals and duals too large");
+    assert!(err_v < 1e-6, "Error between reals and duals too large");
+
+    // The Earth radiation pressure perturbs the orbit at the centimeter to meter level
+    let two_body = Propagator::default(SpacecraftDynamics::new(OrbitalDynamics::two_body()))
+        .with(sc)
+        .for_duration(prop_time)
+        .unwrap();
+    let (err_r, _) = rss_orbit_vec_errors(
+        &final_state.orbit.to_cartesian_vec(),
+        &two_body.orbit.to_cartesian_vec(),
+    );
+    println!(
+        "Effect of the Earth radiation pressure: {:.3} m",
+        err_r * 1e3
+    );
+    assert!(err_r > 1e-6 && err_r < 1e-2);
+}
+
+#[test]
+fn box_wing_srp_drag() {
+    use nyx::dynamics::{
+        AttitudeLaw, ForceModel, InertialAttitude, NadirPointing, Plate, PlateModel, SunPointing,
+    };
+    use nyx::linalg::Vector3;
+    use nyx::State;
+    use std::sync::Arc;
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let sun_j2k = cosm.frame("Sun J2000");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2000, 1, 1);
+    let orbit = Orbit::keplerian(
+        eme2k.equatorial_radius() + 400.0,
+        0.001,
+        51.6,
+        30.0,
+        0.0,
+        0.0,
+        dt,
+        eme2k,
+    );
+    let sun_unit = -cosm.frame_chg(&orbit, sun_j2k).radius().normalize();
+
+    // A Sun pointing absorbing plate is equivalent to a cannonball with Cr = 1, and a specular one to Cr = 2
+    let srp = SolarPressure::default(eme2k, cosm.clone());
+    println!("{srp}");
+    for (specular, cr) in [(0.0, 1.0), (1.0, 2.0)] {
+        let plate = Plate::new(10.0, Vector3::z(), specular, 0.0);
+        let srp_plate = srp.with_plates(PlateModel::new(
+            vec![plate],
+            Arc::new(SunPointing::new(cosm.clone())),
+        ));
+        println!("{srp_plate}");
+        let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 10.0).with_srp(10.0, cr);
+        let cannonball = srp.eom(&sc).unwrap();
+        let plate_force = srp_plate.eom(&sc).unwrap();
+        assert!(
+            (cannonball - plate_force).norm() < 1e-9 * cannonball.norm(),
+            "{cannonball} != {plate_force}"
+        );
+        assert_eq!(srp_plate.estimation_index(), None);
+        // And the duals match the reals
+        let (dual_force, _) = srp_plate.dual_eom(&sc).unwrap();
+        assert!((dual_force - plate_force).norm() < 1e-9 * plate_force.norm());
+    }
+
+    // A plate facing the flow is close to a cannonball of the same area, and no drag applies on a plate facing backward
+    let drag = Drag::std_atm1976(cosm.clone());
+    let sc = Spacecraft::from_srp_defaults(orbit, 300.0, 1.0).with_drag(1.0, 2.2);
+    let cannonball = drag.eom(&sc).unwrap();
+    let forward = drag.with_plates(PlateModel::new(
+        vec![Plate::new(1.0, Vector3::x(), 0.0, 0.0)],
+        Arc::new(NadirPointing),
+    ));
+    println!("{forward}");
+    let plate_force = forward.eom(&sc).unwrap();
+    assert!((cannonball - plate_force).norm() < 1e-2 * cannonball.norm());
+    assert!(plate_force.norm() < cannonball.norm());
+    let backward = drag.with_plates(PlateModel::new(
+        vec![Plate::new(1.0, -Vector3::x(), 0.0, 0.0)],
+        Arc::new(NadirPointing),
+    ));
+    assert_eq!(backward.eom(&sc).unwrap().norm(), 0.0);
+
+    // The wing of a box-wing tracks the Sun about the body Y axis
+    let box_wing = PlateModel::box_wing(Vector3::new(1.0, 2.0, 1.5), 20.0, Arc::new(NadirPointing));
+    println!("{box_wing}");
+    let normals = box_wing.inertial_normals(&orbit, &sun_unit);
+    let wing_normal = normals[6];
+    let body_y = NadirPointing.dcm_to_inertial(&orbit) * Vector3::y();
+    assert!(wing_normal.dot(&body_y).abs() < 1e-12);
+    assert!(wing_normal.dot(&sun_unit) > 0.0);
+    // The box faces do not move in an inertial attitude
+    let inertial = PlateModel::box_wing(
+        Vector3::new(1.0, 2.0, 1.5),
+        20.0,
+        Arc::new(InertialAttitude::aligned()),
+    );
+    assert_eq!(
+        inertial.inertial_normals(&orbit, &sun_unit)[0],
+        Vector3::x()
+    );
+
+    // Propagate the box-wing with both SRP and drag, the reals and the duals must match
+    let sc_dyn = SpacecraftDynamics::from_models(
+        OrbitalDynamics::two_body(),
+        vec![
+            srp.with_plates(box_wing.clone()),
+            drag.with_plates(box_wing),
+        ],
+    );
+    let prop_time = 6 * Unit::Hour;
+    let setup = Propagator::default(sc_dyn);
+    let final_state = setup.with(sc).for_duration(prop_time).unwrap();
+    let final_state_dual = setup.with(sc.with_stm()).for_duration(prop_time).unwrap();
+
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &final_state.orbit.to_cartesian_vec(),
+        &final_state_dual.orbit.to_cartesian_vec(),
+    );
+    println!(
+        "Error between reals and duals accumulated over {} : {:.3e} m \t{:.3e} m/s",
+        prop_time,
+        err_r * 1e3,
+        err_v * 1e3
+    );
+    assert!(err_r < 1e-3, "Error between reals and duals too large");
+    assert!(err_v < 1e-6, "Error between reals and duals too large");
+    assert!(final_state.orbit.sma_km() < orbit.sma_km());
+}
+
+#[test]
+fn empirical_accel_leo() {
+    use nyx::cosmic::Frame;
+    use nyx::dynamics::{EmpiricalAccel, EmpiricalCoefficients, ForceModel};
+    use nyx::linalg::Vector3;
+    use std::sync::Arc;
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2000, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.0, 51.6, 30.0, 0.0, 0.0, dt, eme2k);
+    let sc = Spacecraft::from_srp_defaults(orbit, 500.0, 1.0);
+
+    // A constant in-track acceleration raises the semi-major axis of a circular orbit at 2 T sqrt(a^3/mu)
+    let thrust_km_s2 = 1e-9;
+    let empirical = EmpiricalAccel::constant_rcn(Vector3::new(0.0, thrust_km_s2, 0.0));
+    println!("{empirical}");
+    let sc_dyn = SpacecraftDynamics::from_model(OrbitalDynamics::two_body(), empirical);
+    let prop_time = orbit.period();
+    let final_state = Propagator::default(sc_dyn)
+        .with(sc)
+        .for_duration(prop_time)
+        .unwrap();
+    let expected_da_km =
+        2.0 * thrust_km_s2 * (orbit.sma_km().powi(3) / eme2k.gm()).sqrt() * prop_time.to_seconds();
+    let da_km = final_state.orbit.sma_km() - orbit.sma_km();
+    println!("SMA change: {da_km:.6} km (expected {expected_da_km:.6} km)");
+    assert!((da_km - expected_da_km).abs() < 0.02 * expected_da_km);
+
+    // Piecewise constant segments by epoch
+    let coeffs = EmpiricalCoefficients {
+        constant: Vector3::new(1e-9, -2e-9, 3e-9),
+        cos_1rev: Vector3::new(4e-9, 5e-9, -6e-9),
+        sin_1rev: Vector3::new(-7e-9, 8e-9, 9e-9),
+    };
+    assert!(EmpiricalAccel::new(Frame::SEZ, EmpiricalCoefficients::default()).is_err());
+    let empirical = EmpiricalAccel::new(Frame::VNC, EmpiricalCoefficients::default())
+        .unwrap()
+        .with_segment(dt + 1 * Unit::Hour, coeffs)
+        .with_segment(dt + 2 * Unit::Hour, EmpiricalCoefficients::default());
+    assert_eq!(empirical.segment_index(dt), None);
+    assert_eq!(empirical.segment_index(dt + 90 * Unit::Minute), Some(0));
+    assert_eq!(empirical.segment_index(dt + 3 * Unit::Hour), Some(1));
+    assert_eq!(empirical.eom(&sc).unwrap().norm(), 0.0);
+    assert_eq!(
+        EmpiricalCoefficients::from_vector(&coeffs.as_vector()),
+        coeffs
+    );
+
+    // The partials with respect to the state match finite differences of the EOMs, in the VNC and RCN frames
+    let mut sc_seg = sc;
+    sc_seg.orbit = Orbit::keplerian(
+        7000.0,
+        0.01,
+        51.6,
+        30.0,
+        15.0,
+        40.0,
+        dt + 90 * Unit::Minute,
+        eme2k,
+    );
+    let mut rcn = empirical.clone();
+    rcn.frame = Frame::RCN;
+    for model in [Arc::new(empirical), Arc::new(rcn)] {
+        let (force, grad) = model.dual_eom_pos_vel(&sc_seg).unwrap();
+        let force_eom = model.eom(&sc_seg).unwrap();
+        assert!((force - force_eom).norm() < 1e-12 * force_eom.norm());
+
+        for j in 0..6 {
+            let step = if j < 3 { 1e-3 } else { 1e-6 };
+            let mut plus = sc_seg;
+            let mut minus = sc_seg;
+            let mut vec_plus = plus.orbit.to_cartesian_vec();
+            let mut vec_minus = vec_plus;
+            vec_plus[j] += step;
+            vec_minus[j] -= step;
+            plus.orbit = Orbit::cartesian_vec(&vec_plus, plus.orbit.epoch, eme2k);
+            minus.orbit = Orbit::cartesian_vec(&vec_minus, minus.orbit.epoch, eme2k);
+            let fd = (model.eom(&plus).unwrap() - model.eom(&minus).unwrap()) / (2.0 * step);
+            let err = (grad.column(j) - fd).norm();
+            assert!(
+                err < 1e-6 * fd.norm().max(1e-12),
+                "partial #{j} of {model}: dual {} vs FD {}",
+                grad.column(j),
+                fd
+            );
+        }
+
+        // The coefficient partials rebuild the acceleration
+        let partials = model.coefficient_partials(&sc_seg.orbit).unwrap();
+        let accel = partials * coeffs.as_vector();
+        assert!((accel * sc_seg.mass_kg() - force).norm() < 1e-12 * force.norm());
+    }
+}
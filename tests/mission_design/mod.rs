@@ -1,3 +1,4 @@
+mod attitude;
 mod force_models;
 mod multishoot;
 mod orbitaldyn;
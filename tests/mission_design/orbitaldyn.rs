@@ -952,6 +952,163 @@ fn val_earth_sph_harmonics_12x12() {
     assert!(err_v < 2e-16, "velocity error too large for 12x12 gravity");
 }
 
+#[allow(clippy::identity_op)]
+#[test]
+fn earth_sph_harmonics_tides() {
+    extern crate pretty_env_logger;
+    let _ = pretty_env_logger::try_init();
+    use nyx::dynamics::sph_harmonics::Harmonics;
+    use nyx::dynamics::tides::Tides;
+    use nyx::io::gravity::*;
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, eme2k,
+    );
+
+    let static_field = Harmonics::from_stor(
+        iau_earth,
+        HarmonicsMem::from_cof("data/JGM3.cof.gz", 12, 12, true).unwrap(),
+        cosm.clone(),
+    );
+    // JGM3 is a zero-tide model
+    let tidal_field = Harmonics::from_stor_with_tides(
+        iau_earth,
+        HarmonicsMem::from_cof("data/JGM3.cof.gz", 12, 12, true).unwrap(),
+        Tides::solid_earth().with_zero_tide(true),
+        cosm.clone(),
+    );
+    println!("{tidal_field}");
+
+    let prop_time = 1 * Unit::Day;
+    let static_state = Propagator::default(OrbitalDynamics::from_model(static_field))
+        .with(state)
+        .for_duration(prop_time)
+        .unwrap();
+
+    let tidal_dynamics = OrbitalDynamics::from_model(tidal_field);
+    let tidal_state = Propagator::default(tidal_dynamics.clone())
+        .with(state)
+        .for_duration(prop_time)
+        .unwrap();
+
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &static_state.to_cartesian_vec(),
+        &tidal_state.to_cartesian_vec(),
+    );
+    println!(
+        "Solid Earth tides effect over {prop_time}: {:.6} m \t{:.6} m/s",
+        err_r * 1e3,
+        err_v * 1e3
+    );
+    // The solid Earth tides perturb a low Earth orbit by the order of a meter per day.
+    assert!(err_r > 1e-4, "solid tides have no effect");
+    assert!(err_r < 1e-1, "solid tides effect too large");
+
+    // The hyperdual EOMs apply the same tidal corrections
+    let setup = Propagator::rk89(tidal_dynamics, PropOpts::with_fixed_step_s(30.0));
+    let prop_time = 1 * Unit::Hour;
+    let final_state = setup.with(state).for_duration(prop_time).unwrap();
+    let final_state_dual = setup
+        .with(state.with_stm())
+        .for_duration(prop_time)
+        .unwrap();
+
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &final_state.to_cartesian_vec(),
+        &final_state_dual.to_cartesian_vec(),
+    );
+    assert!(err_r < 2e-16, "position error too large with tides");
+    assert!(err_v < 2e-16, "velocity error too large with tides");
+}
+
+#[allow(clippy::identity_op)]
+#[test]
+fn earth_sph_harmonics_ocean_tides() {
+    extern crate pretty_env_logger;
+    let _ = pretty_env_logger::try_init();
+    use nyx::dynamics::sph_harmonics::Harmonics;
+    use nyx::dynamics::tides::{OceanTides, Tides};
+    use nyx::io::gravity::*;
+
+    let cosm = Cosm::de438_gmat();
+    let eme2k = cosm.frame("EME2000");
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let state = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, eme2k,
+    );
+
+    // Semi-diurnal M2 and diurnal K1 waves of a few millimeters, in the format of the IERS ocean tide models
+    let ocean = OceanTides::parse(
+        "Doodson Darw  n  m    C+         S+        C-        S-
+255.555 M2    2  2   -0.1546    0.4321    0.0000    0.0000
+165.555 K1    2  1   -0.0412    0.1973    0.0000    0.0000
+",
+        2,
+    )
+    .unwrap();
+
+    let static_field = Harmonics::from_stor(
+        iau_earth,
+        HarmonicsMem::from_cof("data/JGM3.cof.gz", 12, 12, true).unwrap(),
+        cosm.clone(),
+    );
+    let tidal_field = Harmonics::from_stor_with_tides(
+        iau_earth,
+        HarmonicsMem::from_cof("data/JGM3.cof.gz", 12, 12, true).unwrap(),
+        Tides::ocean(ocean),
+        cosm.clone(),
+    );
+    println!("{tidal_field}");
+
+    let prop_time = 1 * Unit::Day;
+    let static_state = Propagator::default(OrbitalDynamics::from_model(static_field))
+        .with(state)
+        .for_duration(prop_time)
+        .unwrap();
+
+    let tidal_dynamics = OrbitalDynamics::from_model(tidal_field);
+    let tidal_state = Propagator::default(tidal_dynamics.clone())
+        .with(state)
+        .for_duration(prop_time)
+        .unwrap();
+
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &static_state.to_cartesian_vec(),
+        &tidal_state.to_cartesian_vec(),
+    );
+    println!(
+        "Ocean tides effect over {prop_time}: {:.6} m \t{:.6} m/s",
+        err_r * 1e3,
+        err_v * 1e3
+    );
+    // The coefficients of these waves are about a hundredth of the solid Earth tides, so is their effect.
+    assert!(err_r > 1e-7, "ocean tides have no effect");
+    assert!(err_r < 1e-2, "ocean tides effect too large");
+
+    // The hyperdual EOMs apply the same tidal corrections
+    let setup = Propagator::rk89(tidal_dynamics, PropOpts::with_fixed_step_s(30.0));
+    let prop_time = 1 * Unit::Hour;
+    let final_state = setup.with(state).for_duration(prop_time).unwrap();
+    let final_state_dual = setup
+        .with(state.with_stm())
+        .for_duration(prop_time)
+        .unwrap();
+
+    let (err_r, err_v) = rss_orbit_vec_errors(
+        &final_state.to_cartesian_vec(),
+        &final_state_dual.to_cartesian_vec(),
+    );
+    assert!(err_r < 2e-16, "position error too large with ocean tides");
+    assert!(err_v < 2e-16, "velocity error too large with ocean tides");
+}
+
 #[allow(clippy::identity_op)]
 #[test]
 fn val_earth_sph_harmonics_70x70() {
@@ -1064,6 +1221,169 @@ fn val_earth_sph_harmonics_70x70_partials() {
     );
 }
 
+#[test]
+fn third_body_lunar_harmonics() {
+    // A low lunar orbit integrated around the Earth with the lunar harmonics as a third body must match
+    // the same orbit integrated around the Moon with the lunar harmonics and the Earth as a third body.
+    // The Sun is included in both cases so that both models account for the same bodies.
+    extern crate pretty_env_logger;
+    let _ = pretty_env_logger::try_init();
+    use nyx::dynamics::sph_harmonics::Harmonics;
+    use nyx::dynamics::ThirdBodyHarmonics;
+    use nyx::io::gravity::*;
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let luna = cosm.frame("Luna");
+    let iau_moon = cosm.frame("IAU Moon");
+
+    // Normalized lunar C20 (GRAIL)
+    let moon_c20 = -9.088e-5;
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2022, 11, 27);
+    let llo = Orbit::keplerian(1_837.4, 0.01, 80.0, 10.0, 20.0, 0.0, dt, luna);
+    let prop_time = 12 * Unit::Hour;
+    let opts = PropOpts::with_tolerance(1e-12);
+
+    // Integrated around the Moon
+    let moon_dynamics = OrbitalDynamics::new(vec![
+        PointMasses::new(&[Bodies::Earth, Bodies::Sun], cosm.clone()),
+        Harmonics::from_stor(iau_moon, HarmonicsMem::from_j2(moon_c20), cosm.clone()),
+    ]);
+    let moon_rslt = Propagator::rk89(moon_dynamics, opts)
+        .with(llo)
+        .for_duration(prop_time)
+        .unwrap();
+
+    // Integrated around the Earth
+    let llo_earth = cosm.frame_chg(&llo, eme2k);
+    let earth_dynamics = OrbitalDynamics::new(vec![
+        PointMasses::new(&[Bodies::Luna, Bodies::Sun], cosm.clone()),
+        ThirdBodyHarmonics::from_stor(iau_moon, HarmonicsMem::from_j2(moon_c20), cosm.clone()),
+    ]);
+    println!("{earth_dynamics}");
+    let earth_rslt = Propagator::rk89(earth_dynamics, opts)
+        .with(llo_earth)
+        .for_duration(prop_time)
+        .unwrap();
+
+    // And without the lunar harmonics
+    let point_mass_rslt = Propagator::rk89(
+        OrbitalDynamics::new(vec![PointMasses::new(
+            &[Bodies::Luna, Bodies::Sun],
+            cosm.clone(),
+        )]),
+        opts,
+    )
+    .with(llo_earth)
+    .for_duration(prop_time)
+    .unwrap();
+
+    let moon_rslt_earth = cosm.frame_chg(&moon_rslt, eme2k);
+
+    let (err_r, err_v) = rss_orbit_errors(&earth_rslt, &moon_rslt_earth);
+    let (err_pm_r, _) = rss_orbit_errors(&point_mass_rslt, &moon_rslt_earth);
+    println!(
+        "Third body harmonics vs. central harmonics: {:.6} m\t{:.6} m/s -- without lunar harmonics: {:.3} km",
+        err_r * 1e3,
+        err_v * 1e3,
+        err_pm_r
+    );
+
+    assert!(err_pm_r > 1.0, "lunar J2 should matter in low lunar orbit");
+    assert!(
+        err_r < 0.1,
+        "third body harmonics error too large: {err_r} km"
+    );
+}
+
+#[test]
+fn gnss_relativity() {
+    // The relativistic correction on a GNSS orbit is at the meter level after a day, and the dual
+    // equations of motion must match the real ones and a finite difference of the acceleration.
+    extern crate pretty_env_logger;
+    let _ = pretty_env_logger::try_init();
+    use nyx::dynamics::{AccelModel, Relativity};
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_gregorian_tai_at_midnight(2022, 11, 27);
+    let gnss = Orbit::keplerian(26_560.0, 0.01, 55.0, 10.0, 20.0, 30.0, dt, eme2k);
+    let prop_time = 1 * Unit::Day;
+    let opts = PropOpts::with_tolerance(1e-12);
+
+    let schwarzschild = Relativity::schwarzschild(cosm.clone());
+    let iers = Relativity::iers_earth(cosm.clone());
+    println!("{iers}");
+
+    let acc_s = schwarzschild.eom(&gnss).unwrap();
+    let acc_iers = iers.eom(&gnss).unwrap();
+    println!(
+        "Schwarzschild: {:.3e} m/s^2\tLense-Thirring and de Sitter: {:.3e} m/s^2",
+        acc_s.norm() * 1e3,
+        (acc_iers - acc_s).norm() * 1e3
+    );
+    // The Schwarzschild term dominates
+    assert!(acc_s.norm() > 1e-13 && acc_s.norm() < 1e-12);
+    assert!((acc_iers - acc_s).norm() > 0.0);
+    assert!((acc_iers - acc_s).norm() < 0.2 * acc_s.norm());
+
+    // Dual equations of motion
+    let (dual_acc, grad) = iers.dual_eom(&gnss).unwrap();
+    assert!((dual_acc - acc_iers).norm() < 1e-20);
+    let eps_km = 1e-3;
+    for j in 0..3 {
+        let mut plus = gnss;
+        let mut minus = gnss;
+        match j {
+            0 => {
+                plus.x_km += eps_km;
+                minus.x_km -= eps_km;
+            }
+            1 => {
+                plus.y_km += eps_km;
+                minus.y_km -= eps_km;
+            }
+            _ => {
+                plus.z_km += eps_km;
+                minus.z_km -= eps_km;
+            }
+        }
+        let fd = (iers.eom(&plus).unwrap() - iers.eom(&minus).unwrap()) / (2.0 * eps_km);
+        for i in 0..3 {
+            assert!(
+                (fd[i] - grad[(i, j)]).abs() < 1e-6 * grad.norm(),
+                "gradient mismatch at ({i}, {j}): {} vs {}",
+                fd[i],
+                grad[(i, j)]
+            );
+        }
+    }
+
+    // Propagation with and without the correction
+    let newton_rslt = Propagator::rk89(OrbitalDynamics::two_body(), opts)
+        .with(gnss)
+        .for_duration(prop_time)
+        .unwrap();
+    let gr_rslt = Propagator::rk89(OrbitalDynamics::new(vec![iers]), opts)
+        .with(gnss)
+        .for_duration(prop_time)
+        .unwrap();
+
+    let (err_r, err_v) = rss_orbit_errors(&gr_rslt, &newton_rslt);
+    println!(
+        "Relativity effect after {prop_time}: {:.3} m\t{:.3} mm/s",
+        err_r * 1e3,
+        err_v * 1e6
+    );
+    assert!(
+        err_r > 1e-3 * 1e-3,
+        "relativity should be above the millimeter"
+    );
+    assert!(err_r < 0.1, "relativity effect too large: {err_r} km");
+}
+
 #[test]
 fn hf_prop() {
     // Tests a high fidelity propagation over several days for performance analysis.
@@ -0,0 +1,142 @@
+extern crate nyx_space as nyx;
+extern crate pretty_env_logger;
+
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::io::ConfigRepr;
+use nyx::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+
+#[allow(clippy::identity_op)]
+#[test]
+fn od_tb_batch_least_squares() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let elevation_mask = 0.0;
+    let dss65_madrid = GroundStation::dss65_madrid(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss34_canberra = GroundStation::dss34_canberra(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss13_goldstone = GroundStation::dss13_goldstone(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+
+    // Load the tracking configurations
+    let mut configs = BTreeMap::new();
+    let trkconfig_yaml: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "data",
+        "tests",
+        "config",
+        "trk_cfg_od_val.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let cfg = TrkConfig::load(trkconfig_yaml).unwrap();
+
+    configs.insert(dss65_madrid.name.clone(), cfg.clone());
+    configs.insert(dss34_canberra.name.clone(), cfg.clone());
+    configs.insert(dss13_goldstone.name.clone(), cfg);
+
+    let all_stations = vec![dss65_madrid, dss34_canberra, dss13_goldstone];
+
+    let step_size = 10.0 * Unit::Second;
+    let opts = PropOpts::with_fixed_step(step_size);
+
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    let setup = Propagator::new::<RK4Fixed>(OrbitalDynamics::two_body(), opts);
+    let (final_truth, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(1 * Unit::Day)
+        .unwrap();
+
+    let mut arc_sim = TrackingArcSim::with_seed(all_stations, traj, configs, 0).unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    // Start the batch from a reference trajectory dispersed by about a kilometer and a meter per second
+    let mut initial_guess = initial_state;
+    initial_guess.x_km += 1.0;
+    initial_guess.y_km -= 0.5;
+    initial_guess.vx_km_s += 1e-3;
+
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(10.0, 10.0, 10.0, 1e-4, 1e-4, 1e-4));
+    let apriori = KfEstimate::from_covar(initial_guess, init_covar);
+
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-9));
+
+    let mut batch = BatchLeastSquares::new(
+        setup.with(initial_guess.with_stm()),
+        apriori,
+        measurement_noise,
+        None,
+        IterationConf::builder().max_iterations(10).build(),
+        cosm,
+    );
+
+    let solution = batch.process_arc::<GroundStation>(&arc).unwrap();
+    println!(
+        "Batch solution after {} iterations:\n{solution}",
+        batch.iterations
+    );
+
+    assert!(batch.iterations > 1, "batch should have iterated");
+
+    // With perfect measurements, the weighted prefit residuals of the last iteration are within the measurement noise
+    let rms = batch.rms_weighted_residuals().unwrap();
+    println!("Weighted prefit residual RMS = {rms:.3e}");
+    assert!(rms < 1.0, "batch converged with large residuals");
+
+    let delta = solution.state() - initial_state;
+    println!(
+        "RMAG error = {:.3} m\tVMAG error = {:.3} mm/s",
+        delta.rmag_km() * 1e3,
+        delta.vmag_km_s() * 1e6
+    );
+    assert!(delta.rmag_km() < 1e-3, "Position error should be below 1 m");
+    assert!(
+        delta.vmag_km_s() < 1e-6,
+        "Velocity error should be below 1 mm/s"
+    );
+
+    // The postfit residuals are nil with perfect measurements
+    for residual in batch.residuals.iter().flatten() {
+        assert!(residual.postfit[0].abs() < 1e-3);
+    }
+
+    // The covariance is mapped to each measurement and has deflated from the a priori
+    let last = batch.estimates.last().unwrap();
+    for i in 0..6 {
+        assert!(last.covar[(i, i)] > 0.0);
+        assert!(last.covar[(i, i)] < init_covar[(i, i)]);
+    }
+
+    // And the solution can be predicted past the arc
+    let predicted = batch.predict(final_truth.epoch).unwrap();
+    let delta = predicted.state() - final_truth;
+    assert!(delta.rmag_km() < 1e-2, "Predicted position error too large");
+}
@@ -0,0 +1,137 @@
+extern crate nyx_space as nyx;
+extern crate pretty_env_logger;
+
+use nyx::cosmic::eclipse::{line_of_sight, EclipseState};
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::io::ConfigRepr;
+use nyx::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use std::collections::BTreeMap;
+
+#[test]
+fn od_interlink_ckf() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let prop_time = 12.hours();
+    let setup = Propagator::new::<RK4Fixed>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(10.seconds()),
+    );
+
+    // The relay is in a medium Earth orbit and tracks a spacecraft in low Earth orbit.
+    let relay_state = Orbit::keplerian(15_000.0, 0.001, 20.0, 10.0, 0.0, 0.0, epoch, eme2k);
+    let (_, relay_traj) = setup
+        .with(relay_state)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let initial_state = Orbit::keplerian(7_000.0, 0.01, 51.6, 80.0, 40.0, 0.0, epoch, eme2k);
+    let (final_truth, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let relay = InterlinkTxSpacecraft::new(
+        "Relay".to_string(),
+        relay_traj,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+    )
+    .with_occulting_bodies(vec![eme2k]);
+
+    // The link configuration round trips through its serialized representation.
+    let relay_yaml = serde_yaml::to_string(&vec![relay.clone()]).unwrap();
+    let relay_rebuilt = InterlinkTxSpacecraft::loads_many(&relay_yaml).unwrap();
+    assert_eq!(relay_rebuilt[0].name, relay.name);
+    assert_eq!(relay_rebuilt[0].occulting_bodies, relay.occulting_bodies);
+    assert_eq!(relay_rebuilt[0].traj.states.len(), relay.traj.states.len());
+
+    let cfg = TrkConfig::builder()
+        .sampling(1.minutes())
+        .scheduler(Scheduler::builder().min_samples(10).build())
+        .build();
+
+    let mut configs = BTreeMap::new();
+    configs.insert(relay.name.clone(), cfg);
+
+    let mut arc_sim =
+        TrackingArcSim::with_seed(vec![relay.clone()], traj.clone(), configs, 0).unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+    println!("{arc}");
+
+    let strands = arc_sim.configs[&relay.name].strands.as_ref().unwrap();
+    assert!(!strands.is_empty(), "no visibility from the relay");
+
+    // The Earth occults the link during part of the orbit.
+    let expected_count = (prop_time.to_seconds() / 60.0) as usize + 1;
+    assert!(arc.measurements.len() > 10);
+    assert!(
+        arc.measurements.len() < expected_count,
+        "link never occulted by the Earth"
+    );
+
+    for (_, msr) in &arc.measurements {
+        let rx = traj.at(msr.epoch()).unwrap();
+        let tx = relay.traj.at(msr.epoch()).unwrap();
+        assert_eq!(
+            line_of_sight(&tx, &rx, eme2k, &cosm),
+            EclipseState::Visibilis
+        );
+    }
+
+    // The schedule cannot be built past the end of the trajectory of the relay
+    let (_, short_relay_traj) = setup
+        .with(relay_state)
+        .for_duration_with_traj(6.hours())
+        .unwrap();
+    let short_relay = InterlinkTxSpacecraft::new(
+        "Short relay".to_string(),
+        short_relay_traj,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+    );
+    let mut short_configs = BTreeMap::new();
+    short_configs.insert(
+        short_relay.name.clone(),
+        TrkConfig::builder()
+            .sampling(1.minutes())
+            .scheduler(Scheduler::builder().min_samples(10).build())
+            .build(),
+    );
+    let mut short_arc_sim =
+        TrackingArcSim::with_seed(vec![short_relay], traj.clone(), short_configs, 0).unwrap();
+    assert!(short_arc_sim.build_schedule(cosm.clone()).is_err());
+
+    // Now run the filter from a dispersed state
+    let mut initial_state_est = initial_state.with_stm();
+    initial_state_est.x_km += 0.1;
+    initial_state_est.vz_km_s -= 1e-4;
+
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6));
+    let initial_estimate = KfEstimate::from_covar(initial_state_est, init_covar);
+
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-9));
+
+    let ckf = KF::no_snc(initial_estimate, measurement_noise);
+
+    let mut odp = ODProcess::ckf(setup.with(initial_state_est), ckf, None, cosm);
+
+    odp.process_arc::<InterlinkTxSpacecraft>(&arc).unwrap();
+
+    let est = odp.estimates.last().unwrap();
+    let err_km = (est.state().radius() - final_truth.radius()).norm();
+    println!("Final estimate:\n{est}\nPosition error: {err_km:.6} km");
+
+    assert!(
+        err_km < 0.05,
+        "crosslink estimate did not converge: {err_km} km"
+    );
+}
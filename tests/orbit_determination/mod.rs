@@ -7,8 +7,11 @@ use self::nyx::cosmic::Orbit;
 use self::nyx::od::prelude::{Estimate, Filter, KfEstimate, KF};
 use self::nyx::State;
 
+mod batch;
+mod interlink;
 mod measurements;
 mod multi_body;
+mod optical;
 mod resid_reject;
 mod robust;
 mod simulator;
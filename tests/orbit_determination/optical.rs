@@ -0,0 +1,203 @@
+extern crate nyx_space as nyx;
+extern crate pretty_env_logger;
+
+use nyx::cosmic::{Cosm, LightTimeCalc, Orbit};
+use nyx::dynamics::orbital::OrbitalDynamics;
+use nyx::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx::od::noise::GaussMarkov;
+use nyx::od::prelude::*;
+use nyx::propagators::{PropOpts, Propagator, RK4Fixed};
+use std::collections::BTreeMap;
+
+fn telescopes(cosm: &Cosm) -> Vec<Telescope> {
+    let iau_earth = cosm.frame("IAU Earth");
+    vec![
+        Telescope::new(
+            "Haleakala".to_string(),
+            20.7083,
+            -156.2571,
+            3.052,
+            GaussMarkov::ZERO,
+            iau_earth,
+        ),
+        Telescope::new(
+            "La Palma".to_string(),
+            28.7606,
+            -17.8816,
+            2.396,
+            GaussMarkov::ZERO,
+            iau_earth,
+        ),
+        Telescope::new(
+            "Siding Spring".to_string(),
+            -31.2733,
+            149.0617,
+            1.165,
+            GaussMarkov::ZERO,
+            iau_earth,
+        ),
+    ]
+}
+
+#[test]
+fn od_optical_radec_ckf() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+
+    let devices = telescopes(&cosm);
+
+    // Define the tracking configurations
+    let cfg = TrkConfig::builder()
+        .sampling(1.minutes())
+        .scheduler(Scheduler::builder().sample_alignment(1.minutes()).build())
+        .build();
+
+    let mut configs = BTreeMap::new();
+    for device in &devices {
+        configs.insert(device.name.clone(), cfg.clone());
+    }
+
+    // Define the propagator information.
+    let opts = PropOpts::with_fixed_step(30.seconds());
+
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(26_560.0, 0.01, 55.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    // Generate the truth data.
+    let setup = Propagator::new::<RK4Fixed>(OrbitalDynamics::two_body(), opts);
+    let (final_truth, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(2.days())
+        .unwrap();
+
+    // Simulate the optical tracking data
+    let mut arc_sim =
+        TrackingArcSim::<Orbit, RaDec, Telescope>::with_seed(devices, traj, configs, 0).unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+    println!("{arc}");
+    assert!(
+        arc.measurements.len() > 100,
+        "too few optical measurements: {}",
+        arc.measurements.len()
+    );
+
+    for (_, msr) in &arc.measurements {
+        let obs = msr.observation();
+        assert!((0.0..360.0).contains(&obs[0]), "RA out of bounds: {obs}");
+        assert!((-90.0..=90.0).contains(&obs[1]), "Dec out of bounds: {obs}");
+    }
+
+    // Start the filter from a dispersed state
+    let mut initial_state_est = initial_state.with_stm();
+    initial_state_est.x_km += 0.5;
+    initial_state_est.vy_km_s -= 5e-5;
+
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6));
+    let initial_estimate = KfEstimate::from_covar(initial_state_est, init_covar);
+
+    // One arcsecond of noise on each angle
+    let msr_noise_deg = 1.0 / 3600.0;
+    let measurement_noise =
+        Matrix2::from_diagonal(&Vector2::new(msr_noise_deg.powi(2), msr_noise_deg.powi(2)));
+
+    let ckf = KF::no_snc(initial_estimate, measurement_noise);
+
+    let mut odp = ODProcess::ckf(setup.with(initial_state_est), ckf, None, cosm);
+
+    odp.process_arc::<Telescope>(&arc).unwrap();
+
+    let est = odp.estimates.last().unwrap();
+    let err_km = (est.state().radius() - final_truth.radius()).norm();
+    println!("Final estimate:\n{est}\nPosition error: {err_km:.6} km");
+
+    assert!(
+        err_km < 0.1,
+        "angles-only estimate did not converge: {err_km} km"
+    );
+
+    // All of the residuals should be small, including on the wrap-around of the right ascension.
+    for resid in odp.residuals.iter().flatten() {
+        assert!(
+            resid.postfit.norm() < 1e-2,
+            "postfit residual too large: {}",
+            resid.postfit
+        );
+    }
+}
+
+#[test]
+fn od_optical_azel_constraints() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+
+    let mut devices = telescopes(&cosm);
+    // The Haleakala telescope stares at the zenith with a wide field of view
+    devices[0].field_of_view = Some(FieldOfView {
+        azimuth_deg: 0.0,
+        elevation_deg: 90.0,
+        half_angle_deg: 45.0,
+    });
+
+    let cfg = TrkConfig::builder()
+        .sampling(2.minutes())
+        .scheduler(Scheduler::builder().build())
+        .build();
+
+    let mut configs = BTreeMap::new();
+    for device in &devices {
+        configs.insert(device.name.clone(), cfg.clone());
+    }
+
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 6, 1);
+    let initial_state = Orbit::keplerian(26_560.0, 0.01, 55.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    let (_, traj) = Propagator::default(OrbitalDynamics::two_body())
+        .with(initial_state)
+        .for_duration_with_traj(1.days())
+        .unwrap();
+
+    let mut arc_sim =
+        TrackingArcSim::<Orbit, AzEl, Telescope>::with_seed(devices.clone(), traj, configs, 1)
+            .unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+    println!("{arc}");
+    assert!(!arc.measurements.is_empty());
+
+    let sun_path = cosm.frame("Sun J2000").ephem_path();
+
+    for (name, msr) in &arc.measurements {
+        let device = devices.iter().find(|device| &device.name == name).unwrap();
+        let obs = msr.observation();
+        // Above the elevation mask
+        assert!(obs[1] >= device.elevation_mask_deg, "{name}: {obs}");
+        // Within the field of view if one is defined
+        if device.field_of_view.is_some() {
+            assert!(obs[1] >= 45.0, "{name} out of FOV: {obs}");
+        }
+
+        // During the night
+        let tel = device.to_orbit(msr.epoch());
+        let sun = cosm.celestial_state(&sun_path, msr.epoch(), device.frame, LightTimeCalc::None);
+        let (_, sun_el_deg) = AzEl::from_sez(
+            &device
+                .to_sez(
+                    &(sun.radius() - tel.radius()),
+                    device.frame,
+                    msr.epoch(),
+                    &cosm,
+                )
+                .unwrap(),
+        );
+        assert!(
+            sun_el_deg <= device.max_sun_elevation_deg + 1e-6,
+            "{name} observed in daylight"
+        );
+    }
+}
@@ -1,10 +1,11 @@
 extern crate nyx_space as nyx;
 extern crate pretty_env_logger;
 
-use nyx::cosmic::{Bodies, Cosm, Orbit, Spacecraft};
+use nyx::cosmic::{Bodies, Cosm, Orbit, Spacecraft, CD_IDX};
 use nyx::dynamics::orbital::OrbitalDynamics;
 use nyx::dynamics::spacecraft::{SolarPressure, SpacecraftDynamics};
-use nyx::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx::dynamics::Drag;
+use nyx::linalg::{Const, Matrix2, Matrix6, OVector, Vector2, Vector6};
 use nyx::md::trajectory::ExportCfg;
 use nyx::md::{Event, StateParameter};
 use nyx::od::noise::GaussMarkov;
@@ -230,3 +231,278 @@ fn od_val_sc_mb_srp_reals_duals_models() {
     assert!(delta.rmag_km() < 1e-9, "More than 1 micrometer error");
     assert!(delta.vmag_km_s() < 1e-9, "More than 1 micrometer/s error");
 }
+
+#[allow(clippy::identity_op)]
+#[test]
+fn od_val_sc_srp_estimate_cr() {
+    /*
+     * This tests the estimation of the coefficient of reflectivity, which is observed through the partials
+     * of the SRP acceleration with respect to the Cr, i.e. through the spacecraft STM.
+     * The truth uses a Cr of 1.8, whereas the filter starts with a Cr of 1.5.
+     **/
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+
+    let iau_earth = cosm.frame("IAU Earth");
+    // Define the ground stations.
+    let elevation_mask = 0.0;
+    let dss65_madrid = GroundStation::dss65_madrid(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss34_canberra = GroundStation::dss34_canberra(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss13_goldstone = GroundStation::dss13_goldstone(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let prop_time = 2 * Unit::Day;
+
+    // Define the tracking configurations
+    let mut configs = BTreeMap::new();
+    let cfg = TrkConfig::builder()
+        .strands(vec![Strand {
+            start: epoch,
+            end: epoch + prop_time,
+        }])
+        .build();
+
+    configs.insert(dss65_madrid.name.clone(), cfg.clone());
+    configs.insert(dss34_canberra.name.clone(), cfg.clone());
+    configs.insert(dss13_goldstone.name.clone(), cfg);
+
+    let all_stations = vec![dss65_madrid, dss34_canberra, dss13_goldstone];
+
+    // Define the propagator information.
+    let step_size = 10.0 * Unit::Second;
+    let opts = PropOpts::with_fixed_step(step_size);
+
+    // Define state information.
+    let eme2k = cosm.frame("EME2000");
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, epoch, eme2k);
+
+    let dry_mass_kg = 100.0; // in kg
+    let sc_area = 5.0; // m^2
+
+    let sc_dynamics = SpacecraftDynamics::from_model(
+        OrbitalDynamics::two_body(),
+        SolarPressure::default(eme2k, cosm.clone()),
+    );
+
+    let sc_truth = Spacecraft::from_srp_defaults(initial_state, dry_mass_kg, sc_area);
+    assert_eq!(sc_truth.srp.cr, 1.8);
+
+    let setup = Propagator::new::<RK4Fixed>(sc_dynamics, opts);
+    let (_, traj) = setup
+        .with(sc_truth)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    // Simulate tracking data
+    let mut arc_sim = TrackingArcSim::with_seed(all_stations, traj, configs, 0).unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    // The filter starts with a wrong Cr, and estimates it.
+    let mut sc_init_est = sc_truth.with_stm();
+    sc_init_est.srp.cr = 1.5;
+    let prop_est = setup.with(sc_init_est);
+
+    let covar_radius_km = 1.0e-3_f64.powi(2);
+    let covar_velocity_km_s = 1.0e-6_f64.powi(2);
+    let initial_estimate = KfEstimate::from_diag(
+        sc_init_est,
+        OVector::<f64, Const<9>>::from_column_slice(&[
+            covar_radius_km,
+            covar_radius_km,
+            covar_radius_km,
+            covar_velocity_km_s,
+            covar_velocity_km_s,
+            covar_velocity_km_s,
+            0.5_f64.powi(2),
+            0.0,
+            0.0,
+        ]),
+    );
+
+    let measurement_noise =
+        Matrix2::from_diagonal(&Vector2::new(15e-3_f64.powi(2), 1e-5_f64.powi(2)));
+
+    let ckf = KF::no_snc(initial_estimate, measurement_noise);
+
+    let mut odp = ODProcess::ckf(prop_est, ckf, None, cosm);
+
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "spacecraft_cr_od_results.parquet",
+    ]
+    .iter()
+    .collect();
+
+    odp.to_parquet(path, ExportCfg::timestamped()).unwrap();
+
+    let est = odp.estimates.last().unwrap();
+    let cr_est = est.state().srp.cr;
+    println!(
+        "estimated Cr = {cr_est:.6}\tσ = {:.3e}",
+        est.covar[(6, 6)].sqrt()
+    );
+
+    assert!(
+        (cr_est - 1.8).abs() < 0.1,
+        "Cr estimate should converge toward the truth (got {cr_est})"
+    );
+    assert!(
+        est.covar[(6, 6)] < 0.5_f64.powi(2),
+        "Cr covariance should have decreased"
+    );
+}
+
+#[allow(clippy::identity_op)]
+#[test]
+fn od_val_sc_drag_estimate_cd() {
+    /*
+     * This tests the estimation of the coefficient of drag, which is observed through the partials
+     * of the drag acceleration with respect to the Cd, i.e. through the spacecraft STM.
+     * The truth uses a Cd of 2.2 in low Earth orbit, whereas the filter starts with a Cd of 2.0.
+     **/
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+
+    let iau_earth = cosm.frame("IAU Earth");
+    // Define the ground stations.
+    let elevation_mask = 0.0;
+    let dss65_madrid = GroundStation::dss65_madrid(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss34_canberra = GroundStation::dss34_canberra(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss13_goldstone = GroundStation::dss13_goldstone(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let prop_time = 1 * Unit::Day;
+
+    // Define the tracking configurations
+    let mut configs = BTreeMap::new();
+    let cfg = TrkConfig::builder()
+        .strands(vec![Strand {
+            start: epoch,
+            end: epoch + prop_time,
+        }])
+        .build();
+
+    configs.insert(dss65_madrid.name.clone(), cfg.clone());
+    configs.insert(dss34_canberra.name.clone(), cfg.clone());
+    configs.insert(dss13_goldstone.name.clone(), cfg);
+
+    let all_stations = vec![dss65_madrid, dss34_canberra, dss13_goldstone];
+
+    // Define the propagator information.
+    let step_size = 10.0 * Unit::Second;
+    let opts = PropOpts::with_fixed_step(step_size);
+
+    // Define state information.
+    let eme2k = cosm.frame("EME2000");
+    let initial_state = Orbit::keplerian(
+        eme2k.equatorial_radius() + 350.0,
+        0.001,
+        51.6,
+        80.0,
+        40.0,
+        0.0,
+        epoch,
+        eme2k,
+    );
+
+    let dry_mass_kg = 100.0; // in kg
+    let sc_area = 5.0; // m^2
+
+    let sc_dynamics = SpacecraftDynamics::from_model(
+        OrbitalDynamics::two_body(),
+        Drag::std_atm1976(cosm.clone()),
+    );
+
+    let sc_truth =
+        Spacecraft::from_srp_defaults(initial_state, dry_mass_kg, sc_area).with_drag(sc_area, 2.2);
+
+    let setup = Propagator::new::<RK4Fixed>(sc_dynamics, opts);
+    let (_, traj) = setup
+        .with(sc_truth)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    // Simulate tracking data
+    let mut arc_sim = TrackingArcSim::with_seed(all_stations, traj, configs, 0).unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    // The filter starts with a wrong Cd, and estimates it.
+    let sc_init_est = sc_truth.with_cd(2.0).with_stm();
+    let prop_est = setup.with(sc_init_est);
+
+    let covar_radius_km = 1.0e-3_f64.powi(2);
+    let covar_velocity_km_s = 1.0e-6_f64.powi(2);
+    let initial_estimate = KfEstimate::from_diag(
+        sc_init_est,
+        OVector::<f64, Const<9>>::from_column_slice(&[
+            covar_radius_km,
+            covar_radius_km,
+            covar_radius_km,
+            covar_velocity_km_s,
+            covar_velocity_km_s,
+            covar_velocity_km_s,
+            0.0,
+            0.5_f64.powi(2),
+            0.0,
+        ]),
+    );
+
+    let measurement_noise =
+        Matrix2::from_diagonal(&Vector2::new(15e-3_f64.powi(2), 1e-5_f64.powi(2)));
+
+    let ckf = KF::no_snc(initial_estimate, measurement_noise);
+
+    let mut odp = ODProcess::ckf(prop_est, ckf, None, cosm);
+
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    let est = odp.estimates.last().unwrap();
+    let cd_est = est.state().drag.cd;
+    let cd_sigma = est.covar[(CD_IDX, CD_IDX)].sqrt();
+    println!("estimated Cd = {cd_est:.6}\tσ = {cd_sigma:.3e}");
+
+    assert!(cd_sigma < 0.5, "Cd covariance should have decreased");
+    assert!(
+        (cd_est - 2.2).abs() < 3.0 * cd_sigma,
+        "Cd estimate should match the truth within the reported 3σ (got {cd_est}, σ = {cd_sigma:.3e})"
+    );
+}
@@ -6,7 +6,7 @@ use nyx::dynamics::orbital::OrbitalDynamics;
 use nyx::dynamics::sph_harmonics::Harmonics;
 use nyx::io::ConfigRepr;
 use nyx::io::{gravity::*, ExportCfg};
-use nyx::linalg::{Matrix2, Matrix6, Vector2, Vector6};
+use nyx::linalg::{DMatrix, Matrix2, Matrix6, Vector2, Vector6};
 use nyx::od::noise::GaussMarkov;
 use nyx::od::prelude::*;
 use nyx::propagators::{PropOpts, Propagator, RK4Fixed};
@@ -1223,3 +1223,494 @@ fn od_tb_ckf_fixed_step_perfect_stations_several_snc_covar_map() {
     assert!(delta.rmag_km() < 2e-16, "Position error should be zero");
     assert!(delta.vmag_km_s() < 2e-16, "Velocity error should be zero");
 }
+
+#[allow(clippy::identity_op)]
+#[test]
+fn od_tb_ckf_station_range_bias() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let elevation_mask = 0.0;
+    let dss65_madrid = GroundStation::dss65_madrid(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss34_canberra = GroundStation::dss34_canberra(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+
+    // Load the tracking configurations
+    let mut configs = BTreeMap::new();
+    let trkconfig_yaml: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "data",
+        "tests",
+        "config",
+        "trk_cfg_od_val.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let cfg = TrkConfig::load(trkconfig_yaml).unwrap();
+
+    configs.insert(dss65_madrid.name.clone(), cfg.clone());
+    configs.insert(dss34_canberra.name.clone(), cfg);
+
+    let all_stations = vec![dss65_madrid, dss34_canberra];
+
+    let prop_time = 1 * Unit::Day;
+    let step_size = 10.0 * Unit::Second;
+    let opts = PropOpts::with_fixed_step(step_size);
+
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    let setup = Propagator::new::<RK4Fixed>(OrbitalDynamics::two_body(), opts);
+    let (_, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let mut arc_sim = TrackingArcSim::with_seed(all_stations, traj.clone(), configs, 0).unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+
+    let mut arc: TrackingArc<RangeDoppler> = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    // Madrid has an unmodeled range bias of 25 meters
+    let range_bias_km = 25.0e-3;
+    for (device, msr) in arc.measurements.iter_mut() {
+        if *device == "Madrid" {
+            msr.obs[0] += range_bias_km;
+        }
+    }
+
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(1e-6, 1e-6, 1e-6, 1e-9, 1e-9, 1e-9));
+    let initial_estimate = KfEstimate::from_covar(initial_state.with_stm(), init_covar);
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-9));
+
+    // The bias is a random constant with an a priori sigma of 100 m
+    let bias_process = GaussMarkov::new(Duration::MAX, 0.1, 0.0).unwrap();
+    let kf = KF::no_snc(initial_estimate, measurement_noise).with_msr_biases(vec![
+        MsrBias::solve_for("Madrid", BiasKind::Range, bias_process),
+        MsrBias::solve_for("Canberra", BiasKind::Range, bias_process),
+    ]);
+
+    let mut odp = ODProcess::ckf(setup.with(initial_state.with_stm()), kf, None, cosm);
+
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    assert!(!odp.bias_estimates.is_empty());
+    let biases = odp.kf.bias_estimate().unwrap();
+    println!("{biases}");
+
+    let madrid_bias = biases.value_of("Madrid", BiasKind::Range).unwrap();
+    let canberra_bias = biases.value_of("Canberra", BiasKind::Range).unwrap();
+    assert!(
+        (madrid_bias - range_bias_km).abs() < 1e-3,
+        "Madrid range bias not recovered: {madrid_bias:.6e} km"
+    );
+    assert!(
+        canberra_bias.abs() < 1e-3,
+        "Canberra range bias should be nil: {canberra_bias:.6e} km"
+    );
+    assert!(
+        biases.sigma_of("Madrid", BiasKind::Range).unwrap() < 0.1,
+        "Madrid range bias covariance did not decrease"
+    );
+
+    let est = &odp.estimates[odp.estimates.len() - 1];
+    let truth = traj.at(est.epoch()).unwrap();
+    let delta = est.state() - truth;
+    println!(
+        "RMAG error = {:.3} m\tVMAG error = {:.3} mm/s",
+        delta.rmag_km() * 1e3,
+        delta.vmag_km_s() * 1e6
+    );
+    assert!(
+        delta.rmag_km() < 0.05,
+        "Position error should be small when estimating the range bias"
+    );
+
+    // Restarting the filter from a new estimate, as done when iterating, restores the a priori biases
+    let first_est = odp.estimates[0];
+    odp.kf.set_previous_estimate(&first_est);
+    let biases = odp.kf.bias_estimate().unwrap();
+    assert_eq!(biases.value_of("Madrid", BiasKind::Range).unwrap(), 0.0);
+    assert!((biases.sigma_of("Madrid", BiasKind::Range).unwrap() - 0.1).abs() < 1e-12);
+    assert!(biases.cross_covar.iter().all(|c| *c == 0.0));
+}
+
+#[allow(clippy::identity_op)]
+#[test]
+fn od_tb_ukf_fixed_step_perfect_stations() {
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let iau_earth = cosm.frame("IAU Earth");
+
+    let elevation_mask = 0.0;
+    let dss65_madrid = GroundStation::dss65_madrid(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss34_canberra = GroundStation::dss34_canberra(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss13_goldstone = GroundStation::dss13_goldstone(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+
+    // Load the tracking configurations
+    let mut configs = BTreeMap::new();
+    let trkconfig_yaml: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "data",
+        "tests",
+        "config",
+        "trk_cfg_od_val.yaml",
+    ]
+    .iter()
+    .collect();
+
+    let cfg = TrkConfig::load(trkconfig_yaml).unwrap();
+
+    configs.insert(dss65_madrid.name.clone(), cfg.clone());
+    configs.insert(dss34_canberra.name.clone(), cfg.clone());
+    configs.insert(dss13_goldstone.name.clone(), cfg);
+
+    let all_stations = vec![dss65_madrid, dss34_canberra, dss13_goldstone];
+
+    let step_size = 10.0 * Unit::Second;
+    let opts = PropOpts::with_fixed_step(step_size);
+
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    let setup = Propagator::new::<RK4Fixed>(OrbitalDynamics::two_body(), opts);
+    let (_, traj) = setup
+        .with(initial_state)
+        .for_duration_with_traj(6 * Unit::Hour)
+        .unwrap();
+
+    let mut arc_sim = TrackingArcSim::with_seed(all_stations, traj.clone(), configs, 0).unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    // Start the filter from a dispersed state
+    let mut initial_guess = initial_state;
+    initial_guess.x_km += 0.5;
+    initial_guess.vy_km_s -= 5e-4;
+
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(1.0, 1.0, 1.0, 1e-6, 1e-6, 1e-6));
+    let initial_estimate = KfEstimate::from_covar(initial_guess, init_covar);
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-9));
+
+    let ukf = UKF::no_snc(&setup, initial_estimate, measurement_noise);
+
+    let mut odp = ODProcess::ckf(setup.with(initial_guess.with_stm()), ukf, None, cosm);
+
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    let est = &odp.estimates[odp.estimates.len() - 1];
+    println!("Final UKF estimate:\n{est}");
+    for i in 0..6 {
+        assert!(est.covar[(i, i)] > 0.0, "covar diagonal must be positive");
+        assert!(
+            est.covar[(i, i)] < init_covar[(i, i)],
+            "covar did not decrease"
+        );
+    }
+
+    let delta = est.state() - traj.at(est.epoch()).unwrap();
+    println!(
+        "RMAG error = {:.3} m\tVMAG error = {:.3} mm/s",
+        delta.rmag_km() * 1e3,
+        delta.vmag_km_s() * 1e6
+    );
+    assert!(
+        delta.rmag_km() < 1e-2,
+        "Position error should be below 10 m"
+    );
+    assert!(
+        delta.vmag_km_s() < 1e-5,
+        "Velocity error should be below 1 cm/s"
+    );
+
+    // The residual ratios are normalized by the innovation covariance, so they remain of the order of the measurement size
+    let rms_ratio = odp.rms_residual_ratios();
+    println!("RMS of the residual ratios = {rms_ratio:.3}");
+    assert!(
+        rms_ratio.is_finite() && rms_ratio < 10.0,
+        "UKF residual ratios are inconsistent with the innovation covariance"
+    );
+}
+
+#[allow(clippy::identity_op)]
+#[test]
+fn od_tb_ckf_ud_covar_matches_joseph() {
+    // Tests that the UD factorized filter matches the conventional filter and keeps a symmetric covariance
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let iau_earth = cosm.frame("IAU Earth");
+
+    // Define the ground stations.
+    let elevation_mask = 0.0;
+    let dss65_madrid = GroundStation::dss65_madrid(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss34_canberra = GroundStation::dss34_canberra(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+
+    // Define the tracking configurations
+    let mut configs = BTreeMap::new();
+    let cfg = TrkConfig::from_sample_rate(10.seconds());
+    configs.insert(dss65_madrid.name.clone(), cfg.clone());
+    configs.insert(dss34_canberra.name.clone(), cfg);
+
+    let all_stations = vec![dss65_madrid, dss34_canberra];
+
+    // Define the propagator information.
+    let prop_time = 1 * Unit::Day;
+    let step_size = 10.0 * Unit::Second;
+    let opts = PropOpts::with_fixed_step(step_size);
+
+    // Define state information.
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    let orbital_dyn = OrbitalDynamics::two_body();
+    let setup = Propagator::new::<RK4Fixed>(orbital_dyn, opts);
+
+    let mut prop = setup.with(initial_state);
+    let (final_truth, traj) = prop.for_duration_with_traj(prop_time).unwrap();
+
+    // Simulate tracking data
+    let mut arc_sim = TrackingArcSim::with_seed(all_stations, traj, configs, 0).unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    // Set up the filters
+    let covar_radius_km = 1.0e-3;
+    let covar_velocity_km_s = 1.0e-6;
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(
+        covar_radius_km,
+        covar_radius_km,
+        covar_radius_km,
+        covar_velocity_km_s,
+        covar_velocity_km_s,
+        covar_velocity_km_s,
+    ));
+
+    let initial_estimate = KfEstimate::from_covar(initial_state, init_covar);
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-3));
+
+    let sigma_q = 1e-8_f64.powi(2);
+    let process_noise = SNC3::from_diagonal(2 * Unit::Minute, &[sigma_q, sigma_q, sigma_q]);
+
+    let ckf = KF::new(initial_estimate, process_noise.clone(), measurement_noise);
+    let mut odp = ODProcess::ckf(
+        setup.with(initial_state.with_stm()),
+        ckf,
+        None,
+        cosm.clone(),
+    );
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    let ud_ckf = KF::new(initial_estimate, process_noise, measurement_noise)
+        .with_covar_formulation(CovarFormulation::UD);
+    let mut ud_odp = ODProcess::ckf(setup.with(initial_state.with_stm()), ud_ckf, None, cosm);
+    ud_odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    assert_eq!(odp.estimates.len(), ud_odp.estimates.len());
+
+    for (est, ud_est) in odp.estimates.iter().zip(ud_odp.estimates.iter()) {
+        assert_eq!(
+            ud_est.covar,
+            ud_est.covar.transpose(),
+            "UD covariance not symmetric @ {}",
+            ud_est.epoch()
+        );
+
+        let (u, d) = ud_est
+            .ud_factors
+            .expect("UD filter estimate without UD factors");
+        for i in 0..6 {
+            assert!(d[i] > 0.0, "UD covariance not positive definite");
+            assert_eq!(u[(i, i)], 1.0);
+        }
+
+        let rel_err = (ud_est.covar - est.covar).norm() / est.covar.norm();
+        assert!(
+            rel_err < 1e-6,
+            "UD covariance differs from Joseph by {rel_err:.3e} @ {}",
+            ud_est.epoch()
+        );
+    }
+
+    // Check the final estimate
+    let est = &ud_odp.estimates[ud_odp.estimates.len() - 1];
+    let delta = est.state() - final_truth;
+    println!(
+        "RMAG error = {:.3} m\tVMAG error = {:.3} mm/s",
+        delta.rmag_km() * 1e3,
+        delta.vmag_km_s() * 1e6
+    );
+
+    assert!(delta.rmag_km() < 1e-3, "More than 1 meter error");
+    assert!(delta.vmag_km_s() < 1e-6, "More than 1 mm/s error");
+}
+
+#[allow(clippy::identity_op)]
+#[test]
+fn od_tb_ckf_consider_covariance() {
+    // Tests that the consider parameters inflate the covariance without affecting the estimate
+    let _ = pretty_env_logger::try_init();
+
+    let cosm = Cosm::de438();
+    let iau_earth = cosm.frame("IAU Earth");
+
+    // Define the ground stations.
+    let elevation_mask = 0.0;
+    let dss65_madrid = GroundStation::dss65_madrid(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+    let dss34_canberra = GroundStation::dss34_canberra(
+        elevation_mask,
+        GaussMarkov::ZERO,
+        GaussMarkov::ZERO,
+        iau_earth,
+    );
+
+    // Define the tracking configurations
+    let mut configs = BTreeMap::new();
+    let cfg = TrkConfig::from_sample_rate(1.minutes());
+    configs.insert(dss65_madrid.name.clone(), cfg.clone());
+    configs.insert(dss34_canberra.name.clone(), cfg);
+
+    let madrid_name = dss65_madrid.name.clone();
+    let all_stations = vec![dss65_madrid, dss34_canberra];
+
+    // Define the propagator information.
+    let prop_time = 1 * Unit::Day;
+    let step_size = 10.0 * Unit::Second;
+    let opts = PropOpts::with_fixed_step(step_size);
+
+    // Define state information.
+    let eme2k = cosm.frame("EME2000");
+    let dt = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let initial_state = Orbit::keplerian(22000.0, 0.01, 30.0, 80.0, 40.0, 0.0, dt, eme2k);
+
+    let orbital_dyn = OrbitalDynamics::two_body();
+    let setup = Propagator::new::<RK4Fixed>(orbital_dyn, opts);
+
+    let mut prop = setup.with(initial_state);
+    let (final_truth, traj) = prop.for_duration_with_traj(prop_time).unwrap();
+
+    // Simulate tracking data
+    let mut arc_sim = TrackingArcSim::with_seed(all_stations, traj, configs, 0).unwrap();
+    arc_sim.build_schedule(cosm.clone()).unwrap();
+
+    let arc = arc_sim.generate_measurements(cosm.clone()).unwrap();
+
+    // Set up the filter with the GM of the Earth and the location of Madrid as consider parameters
+    let covar_radius_km = 1.0e-3;
+    let covar_velocity_km_s = 1.0e-6;
+    let init_covar = Matrix6::from_diagonal(&Vector6::new(
+        covar_radius_km,
+        covar_radius_km,
+        covar_radius_km,
+        covar_velocity_km_s,
+        covar_velocity_km_s,
+        covar_velocity_km_s,
+    ));
+
+    let initial_estimate = KfEstimate::from_covar(initial_state, init_covar);
+    let measurement_noise = Matrix2::from_diagonal(&Vector2::new(1e-6, 1e-3));
+
+    let consider_params = vec![
+        ConsiderParam::grav_param(1e-2),
+        ConsiderParam::device_location(&madrid_name, 0, 5e-3).unwrap(),
+        ConsiderParam::device_location(&madrid_name, 1, 5e-3).unwrap(),
+        ConsiderParam::device_location(&madrid_name, 2, 5e-3).unwrap(),
+    ];
+
+    let ckf = KF::no_snc(initial_estimate, measurement_noise).with_consider_params(consider_params);
+
+    let mut odp = ODProcess::ckf(setup.with(initial_state.with_stm()), ckf, None, cosm);
+
+    odp.process_arc::<GroundStation>(&arc).unwrap();
+
+    assert_eq!(odp.consider_estimates.len(), odp.estimates.len());
+
+    for (est, consider) in odp.estimates.iter().zip(odp.consider_estimates.iter()) {
+        let covar = DMatrix::from_column_slice(6, 6, est.covar.as_slice());
+        let consider_covar = consider.consider_covar(&covar);
+        for i in 0..6 {
+            assert!(
+                consider_covar[(i, i)] >= covar[(i, i)],
+                "consider covariance smaller than noise-only covariance @ {}",
+                est.epoch()
+            );
+        }
+        assert_eq!(consider.cross_covar().shape(), (6, 4));
+    }
+
+    // The consider parameters must visibly inflate the final covariance
+    let est = &odp.estimates[odp.estimates.len() - 1];
+    let consider = &odp.consider_estimates[odp.consider_estimates.len() - 1];
+    let consider_covar =
+        consider.consider_covar(&DMatrix::from_column_slice(6, 6, est.covar.as_slice()));
+    println!(
+        "noise-only position sigma = {:.3} m\tconsider position sigma = {:.3} m",
+        est.covar[(0, 0)].sqrt() * 1e3,
+        consider_covar[(0, 0)].sqrt() * 1e3
+    );
+    assert!(consider_covar[(0, 0)] > est.covar[(0, 0)]);
+
+    // The estimate itself ignores the consider parameters
+    let delta = est.state() - final_truth;
+    assert!(delta.rmag_km() < 1e-3, "More than 1 meter error");
+    assert!(delta.vmag_km_s() < 1e-6, "More than 1 mm/s error");
+
+    // Export both the noise-only and the consider covariance
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "od_tb_ckf_consider.parquet",
+    ]
+    .iter()
+    .collect();
+
+    odp.to_parquet(path, ExportCfg::default()).unwrap();
+}
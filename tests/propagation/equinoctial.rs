@@ -0,0 +1,119 @@
+extern crate nyx_space as nyx;
+use hifitime::J2000_OFFSET;
+use nyx::cosmic::{AstroError, Cosm, GuidanceMode, Orbit, Spacecraft};
+use nyx::dynamics::guidance::{Objective, Ruggiero, StateParameter, Thruster};
+use nyx::dynamics::{
+    cartesian_to_equinoctial, equinoctial_to_cartesian, EquinoctialDynamics, OrbitalDynamics,
+    SpacecraftDynamics,
+};
+use nyx::linalg::Vector6;
+use nyx::propagators::*;
+use nyx::time::{Epoch, Unit};
+use nyx::utils::rss_orbit_errors;
+
+#[test]
+fn equinoctial_two_body() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(8_000.0, 0.05, 28.5, 10.0, 20.0, 30.0, dt, eme2k);
+
+    // The conversion to the modified equinoctial elements and back is the identity
+    let cartesian = init.to_cartesian_vec();
+    let mee = cartesian_to_equinoctial(&cartesian, eme2k.gm()).unwrap();
+    println!("{mee}");
+    assert!((mee[0] - init.semi_parameter_km()).abs() < 1e-6);
+    let round_trip = equinoctial_to_cartesian(&mee, eme2k.gm());
+    assert!((round_trip - cartesian).norm() < 1e-8);
+    // The elements are undefined without angular momentum
+    let radial = Vector6::new(7_000.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+    assert!(matches!(
+        cartesian_to_equinoctial(&radial, eme2k.gm()),
+        Err(AstroError::RectilinearOrbit)
+    ));
+
+    let prop_time = 1 * Unit::Day;
+    // Two body dynamics, so the Keplerian propagation is the truth
+    let truth = init.at_epoch(dt + prop_time).unwrap();
+
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    let (cartesian, cartesian_traj) = setup.with(init).for_duration_with_traj(prop_time).unwrap();
+    let (err_r_cartesian, _) = rss_orbit_errors(&cartesian, &truth);
+
+    let dynamics = EquinoctialDynamics::new(OrbitalDynamics::two_body());
+    println!("{dynamics}");
+    let setup = Propagator::equinoctial(dynamics);
+    let (final_state, traj) = setup.with(init).for_duration_with_traj(prop_time).unwrap();
+    let (err_r, err_v) = rss_orbit_errors(&final_state, &truth);
+    println!(
+        "==> equinoctial err_r = {err_r:.3e} km\terr_v = {err_v:.3e} km/s in {} steps\tCartesian err_r = {err_r_cartesian:.3e} km in {} steps",
+        traj.states.len(),
+        cartesian_traj.states.len()
+    );
+    assert_eq!(final_state.epoch, dt + prop_time);
+    assert!(err_r < 1e-3);
+    assert!(err_v < 1e-6);
+    // Only the true longitude varies, so the steps are much larger
+    assert!(2 * traj.states.len() < cartesian_traj.states.len());
+}
+
+#[test]
+fn equinoctial_ruggiero_sma() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let start_time = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let orbit = Orbit::keplerian(24_396.0, 0.001, 5.0, 30.0, 0.0, 0.0, start_time, eme2k);
+
+    let prop_time = 2 * Unit::Day;
+
+    // Define the thruster
+    let lowt = Thruster {
+        thrust_N: 89e-3,
+        isp_s: 1650.0,
+    };
+
+    // Define the objectives
+    let objectives = &[Objective::within_tolerance(
+        StateParameter::SMA,
+        42_164.0,
+        1.0,
+    )];
+
+    let guid_law = Ruggiero::new(objectives, orbit).unwrap();
+
+    let sc_state = Spacecraft::from_thruster(orbit, 300.0, 67.0, lowt, GuidanceMode::Thrust);
+
+    let sc = SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), guid_law);
+
+    let (cartesian, cartesian_traj) = Propagator::default(sc.clone())
+        .with(sc_state)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let (final_state, traj) = Propagator::equinoctial(EquinoctialDynamics::new(sc))
+        .with(sc_state)
+        .for_duration_with_traj(prop_time)
+        .unwrap();
+
+    let sma_err_km = (final_state.orbit.sma_km() - cartesian.orbit.sma_km()).abs();
+    println!(
+        "==> SMA raised by {:.3} km, equinoctial err = {sma_err_km:.3e} km in {} steps vs {} Cartesian steps",
+        final_state.orbit.sma_km() - orbit.sma_km(),
+        traj.states.len(),
+        cartesian_traj.states.len()
+    );
+    assert!(final_state.orbit.sma_km() > orbit.sma_km() + 100.0);
+    assert!(sma_err_km < 1e-1);
+    // The rest of the orbit matches too
+    let (err_r, err_v) = rss_orbit_errors(&final_state.orbit, &cartesian.orbit);
+    println!("==> err_r = {err_r:.3e} km\terr_v = {err_v:.3e} km/s");
+    assert!(err_r < 1.0);
+    assert!((final_state.orbit.ecc() - cartesian.orbit.ecc()).abs() < 1e-6);
+    assert!((final_state.orbit.inc_deg() - cartesian.orbit.inc_deg()).abs() < 1e-6);
+    assert!((final_state.orbit.raan_deg() - cartesian.orbit.raan_deg()).abs() < 1e-5);
+    assert!((final_state.fuel_mass_kg - cartesian.fuel_mass_kg).abs() < 1e-6);
+    assert!(2 * traj.states.len() < cartesian_traj.states.len());
+}
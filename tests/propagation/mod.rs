@@ -1,5 +1,7 @@
+mod equinoctial;
 mod events;
 mod propagators;
+mod regularization;
 mod stm;
 mod stopcond;
 mod trajectory;
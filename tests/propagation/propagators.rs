@@ -465,3 +465,232 @@ fn gmat_val_leo_day_fixed() {
         println!();
     }
 }
+
+#[allow(clippy::identity_op)]
+#[test]
+fn abm8_leo_day() {
+    use nyx::md::Event;
+    use nyx::propagators::error_ctrl::RSSCartesianStep;
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let prop_time = 1 * Unit::Day;
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, eme2k,
+    );
+    // Two body dynamics, so the Keplerian propagation is the truth
+    let truth = init.at_epoch(dt + prop_time).unwrap();
+
+    let dynamics = OrbitalDynamics::two_body();
+
+    // Fixed step, started up with an RK89
+    let setup = Propagator::abm8(
+        dynamics.clone(),
+        PropOpts::with_fixed_step(10.0 * Unit::Second),
+    );
+    let mut prop = setup.with(init);
+    let final_fixed = prop.for_duration(prop_time).unwrap();
+    let (err_r, err_v) = rss_orbit_errors(&final_fixed, &truth);
+    println!("==> ABM8 fixed\terr_r = {err_r:.3e} km\terr_v = {err_v:.3e} km/s");
+    assert!(err_r < 1e-4);
+    assert!(err_v < 1e-7);
+    assert_eq!(prop.latest_details().step, 10.0 * Unit::Second);
+
+    // Modifying the state outside of the integrator restarts the history, so the propagation matches that of a new instance
+    let mut prop = setup.with(init);
+    let mut maneuvered = prop.for_duration(1 * Unit::Hour).unwrap();
+    maneuvered.vx_km_s += 1e-3;
+    prop.state = maneuvered;
+    let continued = prop.for_duration(1 * Unit::Hour).unwrap();
+    let restarted = setup.with(maneuvered).for_duration(1 * Unit::Hour).unwrap();
+    assert_eq!(continued.to_cartesian_vec(), restarted.to_cartesian_vec());
+
+    // Adaptive step
+    let setup = Propagator::abm8(
+        dynamics.clone(),
+        PropOpts::with_adaptive_step_s(1.0, 120.0, 1e-12, RSSCartesianStep {}),
+    );
+    let mut prop = setup.with(init);
+    let final_adaptive = prop.for_duration(prop_time).unwrap();
+    let (err_r, err_v) = rss_orbit_errors(&final_adaptive, &truth);
+    println!(
+        "==> ABM8 adaptive\terr_r = {err_r:.3e} km\terr_v = {err_v:.3e} km/s\t{}",
+        prop.latest_details()
+    );
+    assert!(err_r < 1e-3);
+    assert!(err_v < 1e-6);
+
+    // Events and trajectories are supported as with any other propagator
+    let (apoapsis, traj) = setup
+        .with(init)
+        .until_event(init.period(), &Event::apoapsis())
+        .unwrap();
+    println!("==> ABM8 apoapsis: {apoapsis:x}");
+    assert!((apoapsis.ta_deg() - 180.0).abs() < 1e-2);
+    assert!(traj.states.len() > 10);
+    assert_eq!(traj.last().epoch, dt + init.period());
+}
+
+#[test]
+fn dense_output_leo() {
+    use nyx::md::Event;
+    use nyx::propagators::error_ctrl::RSSCartesianStep;
+
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, eme2k,
+    );
+
+    let setup = Propagator::new::<Dormand45>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_adaptive_step_s(1.0, 120.0, 1e-12, RSSCartesianStep {}),
+    );
+
+    // The continuous extension of the latest step is available on the instance
+    let mut prop = setup.with(init).with_dense_output();
+    prop.single_step().unwrap();
+    let step = prop.latest_interpolant().unwrap();
+    assert_eq!(step.degree(), 4);
+    assert_eq!(step.start_epoch(), dt);
+    assert_eq!(step.end_epoch(), prop.state.epoch);
+    let (err_r, err_v) = rss_orbit_errors(&step.at(step.end_epoch()).unwrap(), &prop.state);
+    assert!(err_r < 1e-9 && err_v < 1e-12);
+    assert!(step.at(step.end_epoch() + 1 * Unit::Second).is_err());
+
+    // Trajectories store one interpolant per step, used instead of the Hermite interpolation
+    let (_, traj) = setup
+        .with(init)
+        .with_dense_output()
+        .for_duration_with_traj(init.period())
+        .unwrap();
+    assert_eq!(traj.num_steps(), traj.states.len() - 1);
+    let hermite = traj.clone().without_steps();
+
+    let mut max_err_dense: f64 = 0.0;
+    let mut max_err_hermite: f64 = 0.0;
+    for state in traj.every(17 * Unit::Second) {
+        // Two body dynamics, so the Keplerian propagation is the truth
+        let truth = init.at_epoch(state.epoch).unwrap();
+        max_err_dense = max_err_dense.max(rss_orbit_errors(&state, &truth).0);
+        let hermite_state = hermite.at(state.epoch).unwrap();
+        max_err_hermite = max_err_hermite.max(rss_orbit_errors(&hermite_state, &truth).0);
+    }
+    println!("==> DP45 dense output max err_r = {max_err_dense:.3e} km\tHermite: {max_err_hermite:.3e} km");
+    assert!(max_err_dense < 1e-3);
+
+    // Events are searched with the interpolant of the integrator
+    let (apoapsis, traj) = setup
+        .with(init)
+        .with_dense_output()
+        .until_event(init.period(), &Event::apoapsis())
+        .unwrap();
+    println!("==> DP45 dense apoapsis: {apoapsis:x}");
+    assert!((apoapsis.ta_deg() - 180.0).abs() < 1e-2);
+    assert!(traj.num_steps() > 0);
+
+    // Verner56 also has a native continuous extension
+    let setup = Propagator::new::<Verner56>(
+        OrbitalDynamics::two_body(),
+        PropOpts::with_fixed_step(60.0 * Unit::Second),
+    );
+    let (_, traj) = setup
+        .with(init)
+        .with_dense_output()
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+    let first_step = traj.step(0).unwrap();
+    assert_eq!(first_step.degree(), 4);
+    let mid = first_step.start_epoch() + first_step.step / 2;
+    let (err_r, _) = rss_orbit_errors(&traj.at(mid).unwrap(), &init.at_epoch(mid).unwrap());
+    println!("==> Verner56 dense step err_r = {err_r:.3e} km");
+    assert!(err_r < 1e-4);
+
+    // Integrators without a continuous extension use a cubic Hermite interpolation of each step
+    let setup = Propagator::default(OrbitalDynamics::two_body());
+    let (_, traj) = setup
+        .with(init)
+        .with_dense_output()
+        .for_duration_with_traj(1 * Unit::Hour)
+        .unwrap();
+    let first_step = traj.step(0).unwrap();
+    assert_eq!(first_step.degree(), 3);
+    let mid = first_step.start_epoch() + first_step.step / 2;
+    let (err_r, _) = rss_orbit_errors(&traj.at(mid).unwrap(), &init.at_epoch(mid).unwrap());
+    println!("==> RK89 Hermite step err_r = {err_r:.3e} km");
+    assert!(err_r < 1e-1);
+}
+
+#[test]
+fn implicit_symplectic_energy() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let prop_time = 1 * Unit::Day;
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::cartesian(
+        -2436.45, -2436.45, 6891.037, 5.088_611, -5.088_611, 0.0, dt, eme2k,
+    );
+    // Two body dynamics, so the Keplerian propagation is the truth
+    let truth = init.at_epoch(dt + prop_time).unwrap();
+
+    let dynamics = OrbitalDynamics::two_body();
+    let opts = PropOpts::with_fixed_step(10.0 * Unit::Second);
+
+    // Integrator, maximum position error and maximum energy drift
+    let setups = [
+        (
+            Propagator::new::<GaussLegendre6>(dynamics.clone(), opts),
+            1e-3,
+            1e-10,
+        ),
+        (
+            Propagator::new::<GaussLegendre4>(dynamics.clone(), opts),
+            1e-1,
+            1e-7,
+        ),
+        (
+            Propagator::new::<Yoshida4>(dynamics.clone(), opts),
+            1.0,
+            1e-6,
+        ),
+    ];
+
+    for (setup, max_err_r, max_drift) in setups {
+        let mut prop = setup.with(init);
+        let final_state = prop.for_duration(prop_time).unwrap();
+        let (err_r, err_v) = rss_orbit_errors(&final_state, &truth);
+        let details = prop.latest_details();
+        println!("==> err_r = {err_r:.3e} km\terr_v = {err_v:.3e} km/s\t{details}");
+        assert!(err_r < max_err_r);
+        assert!(details.energy_drift.unwrap().abs() < max_drift);
+        assert_eq!(details.step, 10.0 * Unit::Second);
+    }
+
+    // The energy drift is computed from the creation of the instance, even over successive propagations
+    let setup = Propagator::new::<GaussLegendre6>(dynamics.clone(), opts);
+    let mut prop = setup.with(init);
+    prop.for_duration(1 * Unit::Hour).unwrap();
+    let final_state = prop.for_duration(1 * Unit::Hour).unwrap();
+    let drift = (final_state.energy_km2_s2() - init.energy_km2_s2()) / init.energy_km2_s2().abs();
+    assert_eq!(prop.latest_details().energy_drift, Some(drift));
+
+    // The energy drift is also reported by explicit integrators
+    let setup = Propagator::new::<RK4Fixed>(dynamics.clone(), opts);
+    let mut prop = setup.with(init);
+    prop.for_duration(prop_time).unwrap();
+    println!("==> RK4 {}", prop.latest_details());
+    assert!(prop.latest_details().energy_drift.is_some());
+
+    // The fixed point iterations do not converge if the step is too large
+    let setup =
+        Propagator::new::<GaussLegendre6>(dynamics, PropOpts::with_fixed_step(2.0 * Unit::Hour));
+    assert!(matches!(
+        setup.with(init).for_duration(prop_time),
+        Err(PropagationError::ImplicitConvergence { .. })
+    ));
+}
@@ -0,0 +1,87 @@
+extern crate nyx_space as nyx;
+use hifitime::J2000_OFFSET;
+use nyx::cosmic::{Cosm, Orbit};
+use nyx::dynamics::orbital::{OrbitalDynamics, Sundman};
+use nyx::propagators::*;
+use nyx::time::{Epoch, Unit};
+use nyx::utils::rss_orbit_errors;
+
+#[test]
+fn sundman_eccentric_orbit() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    // Highly eccentric orbit with a LEO periapsis
+    let init = Orbit::keplerian(66_780.0, 0.9, 28.5, 10.0, 20.0, 0.0, dt, eme2k);
+    let period = init.period();
+    // Two body dynamics, so the Keplerian propagation is the truth
+    let truth = init.at_epoch(dt + period).unwrap();
+
+    let opts = PropOpts::with_fixed_step(10 * Unit::Minute);
+
+    // With a fixed step in physical time, the periapsis passage is poorly integrated
+    let setup = Propagator::rk89(OrbitalDynamics::two_body(), opts);
+    let cartesian = setup.with(init).for_duration(period).unwrap();
+    let (err_r_cartesian, _) = rss_orbit_errors(&cartesian, &truth);
+
+    // With the same fixed step in fictitious time, the steps are short at periapsis and long at apoapsis
+    let dynamics =
+        OrbitalDynamics::two_body().with_sundman(Sundman::eccentric_anomaly(init.sma_km()));
+    println!("{dynamics}");
+    let setup = Propagator::rk89(dynamics, opts);
+    let (regularized, traj) = setup.with(init).for_duration_with_traj(period).unwrap();
+    let (err_r, err_v) = rss_orbit_errors(&regularized, &truth);
+    println!(
+        "==> Sundman err_r = {err_r:.3e} km\terr_v = {err_v:.3e} km/s\tCartesian err_r = {err_r_cartesian:.3e} km"
+    );
+    assert!(err_r < 1e-2);
+    assert!(err_r_cartesian > 10.0 * err_r);
+
+    // The trajectory is stored in physical time and ends at the requested epoch
+    assert_eq!(regularized.epoch, dt + period);
+    assert_eq!(traj.last().epoch, dt + period);
+    let steps: Vec<f64> = traj
+        .states
+        .windows(2)
+        .map(|pair| (pair[1].epoch - pair[0].epoch).to_seconds())
+        .collect();
+    let shortest = steps.iter().cloned().fold(f64::INFINITY, f64::min);
+    let longest = steps.iter().cloned().fold(0.0, f64::max);
+    println!(
+        "==> {} steps from {shortest:.3} s to {longest:.3} s",
+        steps.len()
+    );
+    assert!(longest > 10.0 * shortest);
+
+    // Interpolated states are in physical time too
+    let mid = dt + period / 2;
+    let (err_r, _) = rss_orbit_errors(&traj.at(mid).unwrap(), &init.at_epoch(mid).unwrap());
+    assert!(err_r < 1e-1);
+}
+
+#[test]
+fn sundman_unsupported() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+
+    let dt = Epoch::from_mjd_tai(J2000_OFFSET);
+    let init = Orbit::keplerian(66_780.0, 0.9, 28.5, 10.0, 20.0, 0.0, dt, eme2k);
+    let dynamics =
+        OrbitalDynamics::two_body().with_sundman(Sundman::eccentric_anomaly(init.sma_km()));
+    let opts = PropOpts::with_fixed_step(10 * Unit::Minute);
+
+    // The STM would be with respect to the fictitious time
+    let setup = Propagator::rk89(dynamics.clone(), opts);
+    assert!(setup
+        .with(init.with_stm())
+        .for_duration(1 * Unit::Hour)
+        .is_err());
+
+    // Multistep propagators do not support the time transformation
+    let setup = Propagator::abm8(dynamics, opts);
+    assert!(matches!(
+        setup.with(init).for_duration(1 * Unit::Hour),
+        Err(PropagationError::PropConfigError { .. })
+    ));
+}
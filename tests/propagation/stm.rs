@@ -339,10 +339,10 @@ fn sc_set_unset_static() {
     let mut init_sc = Spacecraft::from_srp_defaults(init, 100.0, 1.0);
 
     // Change the full vector
-    let data = (0..90).map(|x| x as f64).collect::<Vec<f64>>();
+    let data = (0..94).map(|x| x as f64).collect::<Vec<f64>>();
     init_sc.set(
         init.epoch(),
-        &OVector::<f64, Const<90>>::from_column_slice(&data),
+        &OVector::<f64, Const<94>>::from_column_slice(&data),
     );
 
     let init_vec = init_sc.as_vector();
@@ -1,3 +1,6 @@
 mod closedloop_multi_oe_ruggiero;
 mod closedloop_single_oe_ruggiero;
+mod multi_engine;
 mod schedule;
+mod sep_power;
+mod tanks;
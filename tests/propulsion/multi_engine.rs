@@ -0,0 +1,187 @@
+extern crate nyx_space as nyx;
+
+use self::nyx::cosmic::{Cosm, Orbit, Spacecraft, STD_GRAVITY};
+use self::nyx::dynamics::guidance::{GuidanceErrors, GuidanceLaw, Thruster};
+use self::nyx::dynamics::{
+    Engine, OrbitalDynamics, Propulsion, SpacecraftDynamics, ThrottlePoint, ThrottleTable,
+};
+use self::nyx::linalg::Vector3;
+use self::nyx::propagators::Propagator;
+use self::nyx::time::{Epoch, Unit};
+use std::fmt;
+use std::sync::Arc;
+
+/// Thrusts along the velocity, optionally with a custom allocation of the throttle levels
+struct AlongVelocity {
+    allocation: Option<Vec<f64>>,
+}
+
+impl fmt::Display for AlongVelocity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "along velocity")
+    }
+}
+
+impl GuidanceLaw for AlongVelocity {
+    fn direction(&self, osc_state: &Spacecraft) -> Vector3<f64> {
+        osc_state.orbit.velocity() / osc_state.orbit.vmag_km_s()
+    }
+
+    fn throttle(&self, _osc_state: &Spacecraft) -> f64 {
+        1.0
+    }
+
+    fn allocate(&self, osc_state: &Spacecraft, propulsion: &Propulsion) -> Vec<f64> {
+        match &self.allocation {
+            Some(allocation) => allocation.clone(),
+            None => vec![self.throttle(osc_state); propulsion.engines.len()],
+        }
+    }
+
+    fn next(&self, _next_state: &mut Spacecraft) {}
+}
+
+/// Four engines canted by 10 degrees away from the +Z axis, around which they are evenly spread
+fn canted_quad() -> Propulsion {
+    let thruster = Thruster {
+        thrust_N: 1.0,
+        isp_s: 220.0,
+    };
+    let engines = [Vector3::x(), Vector3::y(), -Vector3::x(), -Vector3::y()]
+        .iter()
+        .enumerate()
+        .map(|(i, lateral)| {
+            Engine::from_thruster(&format!("RCS{i}"), thruster, Vector3::z())
+                .with_cant(10.0, Vector3::z().cross(lateral))
+        })
+        .collect();
+    Propulsion::new(engines, Vector3::z())
+}
+
+#[test]
+fn multi_engine_burn() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.001, 28.5, 0.0, 0.0, 0.0, epoch, eme2k);
+    let fuel_mass_kg = 50.0;
+    let sc = Spacecraft::new(orbit, 500.0, fuel_mass_kg, 1.0, 1.0, 1.0, 2.2);
+
+    let propulsion = canted_quad();
+    println!("{propulsion}");
+
+    // The lateral components cancel out, and the cosine loss is applied
+    let (thrust, mass_flow) = propulsion.thrust_body(&[1.0; 4]).unwrap();
+    assert!(thrust.fixed_rows::<2>(0).norm() < 1e-12);
+    assert!((thrust[2] - 4.0 * 10.0_f64.to_radians().cos()).abs() < 1e-12);
+    assert!((mass_flow - 4.0 / (220.0 * STD_GRAVITY)).abs() < 1e-15);
+
+    // Fuel is consumed by each engine
+    let sc_dyn = SpacecraftDynamics::from_guidance_law(
+        OrbitalDynamics::two_body(),
+        Arc::new(AlongVelocity { allocation: None }),
+    )
+    .with_propulsion(propulsion.clone());
+    println!("{sc_dyn}");
+    let burn = 10 * Unit::Minute;
+    let final_state = Propagator::default(sc_dyn)
+        .with(sc)
+        .for_duration(burn)
+        .unwrap();
+    let fuel_used = fuel_mass_kg - final_state.fuel_mass_kg;
+    let expected = 4.0 / (220.0 * STD_GRAVITY) * burn.to_seconds();
+    println!("fuel used: {fuel_used:.6} kg (expected {expected:.6} kg)");
+    assert!((fuel_used - expected).abs() < 1e-9);
+    assert!(final_state.orbit.sma_km() > orbit.sma_km());
+
+    // An engine out creates a lateral thrust and only three engines consume fuel
+    let engine_out = SpacecraftDynamics::from_guidance_law(
+        OrbitalDynamics::two_body(),
+        Arc::new(AlongVelocity {
+            allocation: Some(vec![1.0, 1.0, 1.0, 0.0]),
+        }),
+    )
+    .with_propulsion(propulsion.clone());
+    let final_engine_out = Propagator::default(engine_out)
+        .with(sc)
+        .for_duration(burn)
+        .unwrap();
+    let fuel_used = fuel_mass_kg - final_engine_out.fuel_mass_kg;
+    assert!((fuel_used - 0.75 * expected).abs() < 1e-9);
+    let (thrust, _) = propulsion.thrust_body(&[1.0, 1.0, 1.0, 0.0]).unwrap();
+    assert!(thrust.fixed_rows::<2>(0).norm() > 0.1);
+
+    // The allocation must cover all of the engines with valid throttle levels
+    assert_eq!(
+        propulsion.outputs(&[1.0; 3]),
+        Err(GuidanceErrors::AllocationSize {
+            expected: 4,
+            got: 3
+        })
+    );
+    assert_eq!(
+        propulsion.outputs(&[1.0, 1.0, 1.5, 0.0]),
+        Err(GuidanceErrors::ThrottleRatio { ratio: 1.5 })
+    );
+}
+
+#[test]
+fn throttle_table_and_min_impulse_bit() {
+    let table = ThrottleTable::new(vec![
+        ThrottlePoint {
+            power_kW: 1.0,
+            thrust_N: 0.04,
+            isp_s: 1500.0,
+        },
+        ThrottlePoint {
+            power_kW: 0.5,
+            thrust_N: 0.02,
+            isp_s: 1200.0,
+        },
+        ThrottlePoint {
+            power_kW: 2.0,
+            thrust_N: 0.09,
+            isp_s: 1800.0,
+        },
+    ])
+    .unwrap();
+    assert!(table.at(0.3).is_none());
+
+    // A throttle table must have finite points
+    assert!(ThrottleTable::new(Vec::new()).is_err());
+    assert!(ThrottleTable::new(vec![ThrottlePoint {
+        power_kW: f64::NAN,
+        thrust_N: 0.02,
+        isp_s: 1200.0,
+    }])
+    .is_err());
+    let perf = table.at(1.5).unwrap();
+    assert!((perf.thrust_N - 0.065).abs() < 1e-12);
+    assert!((perf.isp_s - 1650.0).abs() < 1e-9);
+    let capped = table.at(3.0).unwrap();
+    assert_eq!(capped.thrust_N, 0.09);
+
+    // Continuously throttled electric engine: a throttle of 0.75 is an input power of 1.5 kW
+    let propulsion = Propulsion::new(vec![Engine::new("EP", table, Vector3::x())], Vector3::x());
+    let outputs = propulsion.outputs(&[0.75]).unwrap();
+    assert!((outputs[0].thrust_N - 0.065).abs() < 1e-12);
+    assert!((outputs[0].mass_flow_kg_s - 0.065 / (1650.0 * STD_GRAVITY)).abs() < 1e-15);
+    // Below the minimum power, the engine is off
+    assert_eq!(propulsion.outputs(&[0.1]).unwrap()[0].thrust_N, 0.0);
+
+    // Pulse modulated thruster: pulses shorter than the minimum impulse bit are not fired
+    let thruster = Thruster {
+        thrust_N: 1.0,
+        isp_s: 220.0,
+    };
+    let pulsed = Propulsion::new(
+        vec![Engine::from_thruster("RCS", thruster, Vector3::z()).with_min_impulse_bit(0.05)],
+        Vector3::z(),
+    )
+    .with_duty_cycle(1 * Unit::Second);
+    println!("{pulsed}");
+    assert_eq!(pulsed.outputs(&[0.01]).unwrap()[0].thrust_N, 0.0);
+    let outputs = pulsed.outputs(&[0.1]).unwrap();
+    assert!((outputs[0].thrust_N - 0.1).abs() < 1e-12);
+    assert!((outputs[0].mass_flow_kg_s - 0.1 / (220.0 * STD_GRAVITY)).abs() < 1e-15);
+}
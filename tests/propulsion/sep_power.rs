@@ -0,0 +1,185 @@
+extern crate nyx_space as nyx;
+
+use self::nyx::cosmic::eclipse::EclipseLocator;
+use self::nyx::cosmic::{Cosm, GuidanceMode, LightTimeCalc, Orbit, Spacecraft, AU};
+use self::nyx::dynamics::guidance::{GuidanceLaw, Thruster};
+use self::nyx::dynamics::{
+    Engine, OrbitalDynamics, PowerLimited, Propulsion, SolarArrayPower, SpacecraftDynamics,
+    ThrottlePoint, ThrottleTable,
+};
+use self::nyx::linalg::Vector3;
+use self::nyx::propagators::Propagator;
+use self::nyx::time::{Epoch, Unit};
+use std::fmt;
+use std::sync::Arc;
+
+/// Thrusts along the velocity at full throttle
+struct AlongVelocity;
+
+impl fmt::Display for AlongVelocity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "along velocity")
+    }
+}
+
+impl GuidanceLaw for AlongVelocity {
+    fn direction(&self, osc_state: &Spacecraft) -> Vector3<f64> {
+        osc_state.orbit.velocity() / osc_state.orbit.vmag_km_s()
+    }
+
+    fn throttle(&self, _osc_state: &Spacecraft) -> f64 {
+        1.0
+    }
+
+    fn next(&self, _next_state: &mut Spacecraft) {}
+}
+
+#[test]
+fn sep_power_model() {
+    let cosm = Cosm::de438();
+    let sun_j2k = cosm.frame("Sun J2000");
+    let bol = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let power = SolarArrayPower::new(10.0, bol, EclipseLocator::cislunar(cosm.clone()))
+        .with_degradation(0.02)
+        .with_bus_power(1.0);
+    println!("{power}");
+
+    // Inverse square law at 1.5 AU, with the degradation after two years
+    let helio = Orbit::cartesian(1.5 * AU, 0.0, 0.0, 0.0, 24.0, 0.0, bol, sun_j2k);
+    assert!((power.generated_power_kW(&helio).unwrap() - 10.0 / 2.25).abs() < 1e-9);
+    let two_years = bol + 730.5 * Unit::Day;
+    let helio_later = Orbit::cartesian(1.5 * AU, 0.0, 0.0, 0.0, 24.0, 0.0, two_years, sun_j2k);
+    assert!(
+        (power.generated_power_kW(&helio_later).unwrap() - 10.0 * 0.98_f64.powi(2) / 2.25).abs()
+            < 1e-9
+    );
+    let available = 10.0 / 2.25 - 1.0;
+    assert!((power.available_power_kW(&helio).unwrap() - available).abs() < 1e-9);
+
+    // Custom fit: a constant power regardless of the distance to the Sun
+    let flat = SolarArrayPower::new(10.0, bol, EclipseLocator::cislunar(cosm.clone()))
+        .with_fit([0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+    assert!((flat.generated_power_kW(&helio).unwrap() - 10.0 / 1.5_f64.powi(4)).abs() < 1e-9);
+
+    // The five coefficients of GMAT map onto the numerator and denominator of the fit
+    let gmat = SolarArrayPower::new(10.0, bol, EclipseLocator::cislunar(cosm.clone()))
+        .with_gmat_fit([1.0, 0.5, 0.0, 0.1, 0.0]);
+    let expected = 10.0 / 2.25 * (1.0 + 0.5 / 1.5) / (1.0 + 0.1 * 1.5);
+    assert!((gmat.generated_power_kW(&helio).unwrap() - expected).abs() < 1e-9);
+
+    // No power in the shadow of the Earth
+    let eme2k = cosm.frame("EME2000");
+    let sun_dir = cosm
+        .celestial_state(&sun_j2k.ephem_path(), bol, eme2k, LightTimeCalc::None)
+        .radius()
+        .normalize();
+    let shadow = -7000.0 * sun_dir;
+    let in_shadow = Orbit::cartesian(shadow[0], shadow[1], shadow[2], 0.0, 0.0, 7.5, bol, eme2k);
+    assert_eq!(power.generated_power_kW(&in_shadow).unwrap(), 0.0);
+
+    // The throttle of the single thruster is capped by the available power
+    let limited = PowerLimited::new(Arc::new(AlongVelocity), power.clone(), 5.0);
+    println!("{limited}");
+    let sc = Spacecraft::from_thruster(
+        helio,
+        500.0,
+        100.0,
+        Thruster {
+            thrust_N: 0.25,
+            isp_s: 2000.0,
+        },
+        GuidanceMode::Thrust,
+    );
+    assert!((limited.throttle(&sc) - available / 5.0).abs() < 1e-9);
+    // A thruster which does not draw power is not limited
+    let unpowered = PowerLimited::new(Arc::new(AlongVelocity), power.clone(), 0.0);
+    assert_eq!(unpowered.throttle(&sc), 1.0);
+
+    // Without the ephemeris of the Sun, the power is an error and the limited thruster is shut down
+    let beyond_ephem = Epoch::from_gregorian_tai_at_midnight(2200, 1, 1);
+    let leo = Orbit::keplerian(7000.0, 1e-3, 28.5, 0.0, 0.0, 0.0, beyond_ephem, eme2k);
+    assert!(power.generated_power_kW(&leo).is_err());
+    assert!(power.available_power_kW(&leo).is_err());
+    assert_eq!(limited.throttle(&sc.with_orbit(leo)), 0.0);
+
+    // The engines of a propulsion subsystem share the available power, mapped to thrust and Isp by their throttle tables
+    let table = ThrottleTable::new(vec![
+        ThrottlePoint {
+            power_kW: 0.5,
+            thrust_N: 0.02,
+            isp_s: 1200.0,
+        },
+        ThrottlePoint {
+            power_kW: 2.0,
+            thrust_N: 0.09,
+            isp_s: 1800.0,
+        },
+    ])
+    .unwrap();
+    let propulsion = Propulsion::new(
+        vec![
+            Engine::new("EP1", table.clone(), Vector3::z()),
+            Engine::new("EP2", table.clone(), Vector3::z()),
+        ],
+        Vector3::z(),
+    );
+    let throttles = limited.allocate(&sc, &propulsion);
+    let used: f64 = throttles.iter().map(|throttle| throttle * 2.0).sum();
+    assert!((used - available).abs() < 1e-9);
+    let outputs = propulsion.outputs(&throttles).unwrap();
+    let expected = table.at(available / 2.0).unwrap();
+    assert!((outputs[0].thrust_N - expected.thrust_N).abs() < 1e-12);
+    assert!((outputs[1].thrust_N - expected.thrust_N).abs() < 1e-12);
+}
+
+#[test]
+fn sep_eclipse_coast() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+
+    let orbit = Orbit::keplerian(7000.0, 0.001, 10.0, 0.0, 0.0, 0.0, epoch, eme2k);
+    let fuel_mass_kg = 100.0;
+    let sc = Spacecraft::from_thruster(
+        orbit,
+        500.0,
+        fuel_mass_kg,
+        Thruster {
+            thrust_N: 0.25,
+            isp_s: 2000.0,
+        },
+        GuidanceMode::Thrust,
+    );
+
+    let power = SolarArrayPower::new(1.5, epoch, EclipseLocator::cislunar(cosm.clone()))
+        .with_bus_power(0.4);
+
+    let unlimited =
+        SpacecraftDynamics::from_guidance_law(OrbitalDynamics::two_body(), Arc::new(AlongVelocity));
+    let limited = SpacecraftDynamics::from_guidance_law(
+        OrbitalDynamics::two_body(),
+        PowerLimited::new(Arc::new(AlongVelocity), power, 1.0),
+    );
+
+    let prop_time = orbit.period();
+    let fuel_unlimited = fuel_mass_kg
+        - Propagator::default(unlimited)
+            .with(sc)
+            .for_duration(prop_time)
+            .unwrap()
+            .fuel_mass_kg;
+    let fuel_limited = fuel_mass_kg
+        - Propagator::default(limited)
+            .with(sc)
+            .for_duration(prop_time)
+            .unwrap()
+            .fuel_mass_kg;
+
+    println!(
+        "fuel used over one orbit: {fuel_limited:.6} kg with eclipses vs {fuel_unlimited:.6} kg"
+    );
+    // The thruster is off during the eclipse, which lasts about a third of the orbit
+    assert!(fuel_limited < 0.8 * fuel_unlimited);
+    assert!(fuel_limited > 0.5 * fuel_unlimited);
+}
@@ -0,0 +1,315 @@
+extern crate nyx_space as nyx;
+
+use self::nyx::cosmic::{Cosm, Orbit, Spacecraft, MAX_TANKS, STD_GRAVITY};
+use self::nyx::dynamics::guidance::{GuidanceErrors, GuidanceLaw, Thruster};
+use self::nyx::dynamics::{
+    Blowdown, Engine, OrbitalDynamics, Propulsion, SpacecraftDynamics, Tank,
+};
+use self::nyx::io::ExportCfg;
+use self::nyx::linalg::Vector3;
+use self::nyx::md::trajectory::Interpolatable;
+use self::nyx::md::StateParameter;
+use self::nyx::propagators::Propagator;
+use self::nyx::time::{Epoch, Unit};
+use self::nyx::{NyxError, State};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Fires the main engine along the velocity and keeps the attitude control thrusters off
+struct MainEngineBurn;
+
+impl fmt::Display for MainEngineBurn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "main engine burn")
+    }
+}
+
+impl GuidanceLaw for MainEngineBurn {
+    fn direction(&self, osc_state: &Spacecraft) -> Vector3<f64> {
+        osc_state.orbit.velocity() / osc_state.orbit.vmag_km_s()
+    }
+
+    fn throttle(&self, _osc_state: &Spacecraft) -> f64 {
+        1.0
+    }
+
+    fn allocate(&self, _osc_state: &Spacecraft, propulsion: &Propulsion) -> Vec<f64> {
+        let mut throttles = vec![0.0; propulsion.engines.len()];
+        throttles[0] = 1.0;
+        throttles
+    }
+
+    fn next(&self, _next_state: &mut Spacecraft) {}
+}
+
+const MIXTURE_RATIO: f64 = 1.65;
+
+/// A bipropellant main engine fed by oxidizer and fuel tanks, and a cold gas thruster fed by its own tank
+fn biprop_and_cold_gas(blowdown: bool) -> Propulsion {
+    let main = Engine::from_thruster(
+        "main",
+        Thruster {
+            thrust_N: 400.0,
+            isp_s: 318.0,
+        },
+        Vector3::z(),
+    )
+    .with_bipropellant(0, 1, MIXTURE_RATIO)
+    .with_pressure_sensitivity(1800.0, 1.0, 0.05);
+
+    let cold_gas = Engine::from_thruster(
+        "cold gas",
+        Thruster {
+            thrust_N: 1.0,
+            isp_s: 65.0,
+        },
+        Vector3::z(),
+    )
+    .with_tank(2);
+
+    let (oxidizer, fuel) = if blowdown {
+        (
+            Tank::new("NTO").with_blowdown(Blowdown {
+                initial_pressure_kPa: 1800.0,
+                initial_propellant_kg: 165.0,
+                volume_m3: 0.15,
+                density_kg_m3: 1440.0,
+                polytropic_index: 1.0,
+            }),
+            Tank::new("MMH").with_blowdown(Blowdown {
+                initial_pressure_kPa: 1800.0,
+                initial_propellant_kg: 100.0,
+                volume_m3: 0.15,
+                density_kg_m3: 880.0,
+                polytropic_index: 1.0,
+            }),
+        )
+    } else {
+        (Tank::new("NTO"), Tank::new("MMH"))
+    };
+
+    Propulsion::new(vec![main, cold_gas], Vector3::z())
+        .with_tanks(vec![oxidizer, fuel, Tank::new("GN2")])
+        .unwrap()
+}
+
+#[test]
+fn tank_state_parameters() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.001, 28.5, 0.0, 0.0, 0.0, epoch, eme2k);
+
+    let sc = Spacecraft::new(orbit, 800.0, 0.0, 1.0, 1.0, 1.0, 2.2)
+        .with_tank_masses(&[165.0, 100.0, 10.0])
+        .unwrap();
+    assert_eq!(sc.fuel_mass_kg, 275.0);
+    assert_eq!(sc.value(StateParameter::TankMass2).unwrap(), 100.0);
+    assert_eq!(
+        sc.value(StateParameter::TankMass4),
+        Err(NyxError::NoTankAvail { index: 3 })
+    );
+
+    // Setting the propellant of a tank also updates the fuel mass
+    let mut refilled = sc;
+    refilled.set_value(StateParameter::TankMass3, 12.0).unwrap();
+    assert_eq!(refilled.fuel_mass_kg, 277.0);
+
+    // Setting the fuel mass splits it across the tanks in proportion to their propellant, or evenly if they are empty
+    refilled.set_value(StateParameter::FuelMass, 138.5).unwrap();
+    assert!((refilled.value(StateParameter::TankMass1).unwrap() - 82.5).abs() < 1e-12);
+    assert!((refilled.value(StateParameter::TankMass2).unwrap() - 50.0).abs() < 1e-12);
+    assert!((refilled.value(StateParameter::TankMass3).unwrap() - 6.0).abs() < 1e-12);
+    let mut empty = Spacecraft::new(orbit, 800.0, 0.0, 1.0, 1.0, 1.0, 2.2)
+        .with_tank_masses(&[0.0, 0.0])
+        .unwrap();
+    empty.set_value(StateParameter::FuelMass, 30.0).unwrap();
+    assert_eq!(empty.tank_masses(), [15.0, 15.0, 0.0, 0.0]);
+    assert_eq!(
+        empty.value(StateParameter::TankMass3),
+        Err(NyxError::NoTankAvail { index: 2 })
+    );
+
+    // At most MAX_TANKS tanks are supported
+    assert!(sc.with_tank_masses(&[10.0; MAX_TANKS + 1]).is_err());
+
+    // The tank masses are stored in the state vector
+    let mut copy = Spacecraft::new(orbit, 800.0, 0.0, 1.0, 1.0, 1.0, 2.2)
+        .with_tank_masses(&[0.0, 0.0, 0.0])
+        .unwrap();
+    copy.set(epoch, &sc.as_vector());
+    assert_eq!(copy, sc);
+
+    assert_eq!(
+        StateParameter::from_str("tank1_mass").unwrap(),
+        StateParameter::TankMass1
+    );
+    assert!(Spacecraft::export_params().contains(&StateParameter::TankMass4));
+}
+
+#[test]
+fn bipropellant_and_cold_gas_tanks() {
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.001, 28.5, 0.0, 0.0, 0.0, epoch, eme2k);
+
+    let sc = Spacecraft::new(orbit, 800.0, 0.0, 1.0, 1.0, 1.0, 2.2)
+        .with_tank_masses(&[165.0, 100.0, 10.0])
+        .unwrap();
+
+    let propulsion = biprop_and_cold_gas(false);
+    println!("{propulsion}");
+
+    let sc_dyn = SpacecraftDynamics::from_guidance_law(
+        OrbitalDynamics::two_body(),
+        Arc::new(MainEngineBurn),
+    )
+    .with_propulsion(propulsion);
+
+    let burn = 5 * Unit::Minute;
+    let (final_state, traj) = Propagator::default(sc_dyn)
+        .with(sc)
+        .for_duration_with_traj(burn)
+        .unwrap();
+    println!("{final_state}");
+
+    let tanks = final_state.tank_masses();
+    let oxidizer_used = 165.0 - tanks[0];
+    let fuel_used = 100.0 - tanks[1];
+    let expected = 400.0 / (318.0 * STD_GRAVITY) * burn.to_seconds();
+    println!("oxidizer used: {oxidizer_used:.6} kg\tfuel used: {fuel_used:.6} kg");
+
+    // The oxidizer and fuel are consumed with the mixture ratio, and the cold gas tank is untouched
+    assert!((oxidizer_used + fuel_used - expected).abs() < 1e-6);
+    assert!((oxidizer_used / fuel_used - MIXTURE_RATIO).abs() < 1e-9);
+    assert_eq!(tanks[2], 10.0);
+    assert!(
+        (final_state.fuel_mass_kg - tanks.iter().sum::<f64>()).abs() < 1e-9,
+        "fuel mass is not the sum of the tanks"
+    );
+
+    // Each tank mass is exported with the trajectory, but not the unused tanks
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "output_data",
+        "biprop_tanks.parquet",
+    ]
+    .iter()
+    .collect();
+    traj.to_parquet_with_cfg(path, ExportCfg::timestamped())
+        .unwrap();
+    assert!(traj.first().value(StateParameter::TankMass3).is_ok());
+    assert!(traj.first().value(StateParameter::TankMass4).is_err());
+    let mid = traj.at(epoch + burn / 2).unwrap();
+    assert!(
+        (mid.value(StateParameter::TankMass1).unwrap() - (165.0 - oxidizer_used / 2.0)).abs()
+            < 1e-3
+    );
+}
+
+#[test]
+fn blowdown_tanks() {
+    let propulsion = biprop_and_cold_gas(true);
+    let blowdown = propulsion.tanks[0].blowdown.unwrap();
+
+    // Initial pressure when full, and the pressure drops as the propellant is consumed
+    assert!((blowdown.pressure_kPa(165.0) - 1800.0).abs() < 1e-9);
+    let initial_ullage = 0.15 - 165.0 / 1440.0;
+    let half_ullage = 0.15 - 82.5 / 1440.0;
+    assert!((blowdown.pressure_kPa(82.5) - 1800.0 * initial_ullage / half_ullage).abs() < 1e-9);
+    assert!(propulsion.tanks[2].pressure_kPa(10.0).is_none());
+
+    // The thrust and Isp of the main engine scale with its feed pressure
+    let full = propulsion
+        .outputs_with_tanks(&[1.0, 0.0], &[165.0, 100.0, 10.0])
+        .unwrap();
+    assert!((full[0].thrust_N - 400.0).abs() < 1e-9);
+    let half = propulsion
+        .outputs_with_tanks(&[1.0, 0.0], &[82.5, 50.0, 10.0])
+        .unwrap();
+    let pressure = propulsion
+        .feed_pressure_kPa(&propulsion.engines[0], &[82.5, 50.0, 10.0])
+        .unwrap();
+    let ratio = pressure / 1800.0;
+    assert!(ratio < 0.6);
+    assert!((half[0].thrust_N - 400.0 * ratio).abs() < 1e-9);
+    assert!(
+        (half[0].mass_flow_kg_s - 400.0 * ratio / (318.0 * ratio.powf(0.05) * STD_GRAVITY)).abs()
+            < 1e-12
+    );
+
+    // Over a long burn, the blowdown reduces the propellant consumption compared to regulated tanks
+    let cosm = Cosm::de438();
+    let eme2k = cosm.frame("EME2000");
+    let epoch = Epoch::from_gregorian_tai_at_midnight(2020, 1, 1);
+    let orbit = Orbit::keplerian(7000.0, 0.001, 28.5, 0.0, 0.0, 0.0, epoch, eme2k);
+    let sc = Spacecraft::new(orbit, 800.0, 0.0, 1.0, 1.0, 1.0, 2.2)
+        .with_tank_masses(&[165.0, 100.0, 10.0])
+        .unwrap();
+
+    let burn = 15 * Unit::Minute;
+    let mut fuel_used = Vec::new();
+    for blowdown in [false, true] {
+        let sc_dyn = SpacecraftDynamics::from_guidance_law(
+            OrbitalDynamics::two_body(),
+            Arc::new(MainEngineBurn),
+        )
+        .with_propulsion(biprop_and_cold_gas(blowdown));
+        let final_state = Propagator::default(sc_dyn)
+            .with(sc)
+            .for_duration(burn)
+            .unwrap();
+        fuel_used.push(sc.fuel_mass_kg - final_state.fuel_mass_kg);
+    }
+    println!(
+        "propellant used: {:.3} kg regulated vs {:.3} kg in blowdown",
+        fuel_used[0], fuel_used[1]
+    );
+    assert!(fuel_used[1] < 0.9 * fuel_used[0]);
+}
+
+#[test]
+fn tank_feeds() {
+    let thruster = Thruster {
+        thrust_N: 1.0,
+        isp_s: 300.0,
+    };
+
+    // Without tanks, the engines draw from the first tank like the single thruster
+    let untanked = Propulsion::new(
+        vec![Engine::from_thruster("main", thruster, Vector3::z())],
+        Vector3::z(),
+    );
+    let outputs = untanked.outputs(&[1.0]).unwrap();
+    let flows = untanked.tank_flows(&outputs);
+    assert!(outputs[0].mass_flow_kg_s > 0.0);
+    assert_eq!(flows[0], outputs[0].mass_flow_kg_s);
+    assert_eq!(flows.iter().sum::<f64>(), outputs[0].mass_flow_kg_s);
+
+    // Feeding an engine from a tank beyond the supported ones is an error
+    let mut misfed = untanked;
+    misfed.engines[0].feed = vec![(MAX_TANKS, 1.0)];
+    assert_eq!(
+        misfed.outputs(&[1.0]),
+        Err(GuidanceErrors::FeedTank {
+            engine: 0,
+            tank: MAX_TANKS,
+            max: MAX_TANKS,
+        })
+    );
+
+    // The tanks must feed all of the engines, and their number is limited
+    let main = Engine::from_thruster("main", thruster, Vector3::z()).with_tank(1);
+    let propulsion = Propulsion::new(vec![main], Vector3::z());
+    assert!(propulsion.clone().with_tanks(vec![Tank::new("A")]).is_err());
+    assert!(propulsion
+        .clone()
+        .with_tanks(vec![Tank::new("A"); MAX_TANKS + 1])
+        .is_err());
+    assert!(propulsion
+        .with_tanks(vec![Tank::new("A"), Tank::new("B")])
+        .is_ok());
+}